@@ -1,4 +1,5 @@
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn test() {
     assert!(cfg!(did_run_build_script));
 }
@@ -0,0 +1,164 @@
+//! A reference server for the remote build cache protocol of
+//! `-Zshared-build-cache`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use cargo_test_support::build_cache::BuildCacheServer;
+//! use cargo_test_support::project;
+//!
+//! let server = BuildCacheServer::new();
+//! let p = project()
+//!     .file(".cargo/config.toml", &server.config())
+//!     .file("src/lib.rs", "")
+//!     .build();
+//!
+//! p.cargo("build -Zshared-build-cache")
+//!     .masquerade_as_nightly_cargo(&["shared-build-cache"])
+//!     .run();
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use url::Url;
+
+/// State shared between the server thread and its handle.
+#[derive(Default)]
+struct State {
+    /// Stored archives by key.
+    entries: BTreeMap<String, Vec<u8>>,
+    /// Log of handled requests, as `"<METHOD> <status>"`.
+    requests: Vec<String>,
+}
+
+/// A remote build cache server that keeps entries in memory.
+///
+/// The server is stopped when this is dropped.
+pub struct BuildCacheServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BuildCacheServer {
+    /// Starts a new server on a random local port.
+    pub fn new() -> BuildCacheServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(State::default()));
+        let thread_state = Arc::clone(&state);
+        let handle = Some(thread::spawn(move || serve(listener, thread_state)));
+        BuildCacheServer {
+            addr,
+            state,
+            handle,
+        }
+    }
+
+    /// The URL to use for `build-cache.remote`.
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}/", self.addr)).unwrap()
+    }
+
+    /// A cargo config file pointing `build-cache.remote` to this server.
+    pub fn config(&self) -> String {
+        format!("[build-cache]\nremote = \"{}\"\n", self.url())
+    }
+
+    /// Keys of all entries stored in the server.
+    pub fn keys(&self) -> Vec<String> {
+        self.state.lock().unwrap().entries.keys().cloned().collect()
+    }
+
+    /// Removes all entries from the server.
+    pub fn clear(&self) {
+        self.state.lock().unwrap().entries.clear();
+    }
+
+    /// Returns the requests handled so far as `"<METHOD> <status>"`, and
+    /// clears the log.
+    pub fn requests(&self) -> Vec<String> {
+        std::mem::take(&mut self.state.lock().unwrap().requests)
+    }
+}
+
+impl Drop for BuildCacheServer {
+    fn drop(&mut self) {
+        if let Ok(mut stream) = TcpStream::connect(self.addr) {
+            // shutdown the server
+            let _ = stream.write_all(b"stop");
+            let _ = stream.flush();
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn serve(listener: TcpListener, state: Arc<Mutex<State>>) {
+    let mut line = String::new();
+    'server: loop {
+        let (socket, _) = listener.accept().unwrap();
+        let mut buf = BufReader::new(socket);
+        line.clear();
+        if buf.read_line(&mut line).unwrap() == 0 {
+            // Connection terminated.
+            continue;
+        }
+        // Read the "GET path HTTP/1.1" line.
+        let mut parts = line.split_ascii_whitespace();
+        let method = parts.next().unwrap().to_string();
+        if method == "stop" {
+            return;
+        }
+        let path = parts.next().unwrap().to_string();
+
+        let mut content_len = 0;
+        loop {
+            line.clear();
+            if buf.read_line(&mut line).unwrap() == 0 {
+                continue 'server;
+            }
+            if line == "\r\n" {
+                // End of headers.
+                break;
+            }
+            let (name, value) = line.split_once(':').unwrap();
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_len = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0u8; content_len];
+        buf.read_exact(&mut body).unwrap();
+
+        let (code, response) = {
+            let mut state = state.lock().unwrap();
+            let (code, response) = match (method.as_str(), path.strip_prefix("/v1/entries/")) {
+                ("GET", Some(key)) => match state.entries.get(key) {
+                    Some(archive) => (200, archive.clone()),
+                    None => (404, Vec::new()),
+                },
+                ("PUT", Some(key)) => {
+                    state.entries.insert(key.to_string(), body);
+                    (201, Vec::new())
+                }
+                (_, Some(_)) => (405, Vec::new()),
+                (_, None) => (404, Vec::new()),
+            };
+            state.requests.push(format!("{method} {code}"));
+            (code, response)
+        };
+
+        let buf = buf.get_mut();
+        write!(buf, "HTTP/1.1 {code}\r\n").unwrap();
+        write!(buf, "Content-Length: {}\r\n", response.len()).unwrap();
+        write!(buf, "Connection: close\r\n").unwrap();
+        write!(buf, "\r\n").unwrap();
+        buf.write_all(&response).unwrap();
+        buf.flush().unwrap();
+    }
+}
@@ -290,6 +290,7 @@ static E2E_LITERAL_REDACTIONS: &[(&str, &str)] = &[
     ("[DOCUMENTING]", " Documenting"),
     ("[SCRAPING]", "    Scraping"),
     ("[FRESH]", "       Fresh"),
+    ("[RESTORED]", "    Restored"),
     ("[DIRTY]", "       Dirty"),
     ("[LOCKING]", "     Locking"),
     ("[UPDATING]", "    Updating"),
@@ -306,6 +307,9 @@ static E2E_LITERAL_REDACTIONS: &[(&str, &str)] = &[
     ("[UPLOADING]", "   Uploading"),
     ("[UPLOADED]", "    Uploaded"),
     ("[VERIFYING]", "   Verifying"),
+    ("[REVERTING]", "   Reverting"),
+    ("[REVERTED]", "    Reverted"),
+    ("[KEPT]", "        Kept"),
     ("[ARCHIVING]", "   Archiving"),
     ("[INSTALLING]", "  Installing"),
     ("[REPLACING]", "   Replacing"),
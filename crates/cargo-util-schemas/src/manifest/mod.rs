@@ -901,12 +901,24 @@ pub struct TomlProfile {
     pub strip: Option<StringOrBool>,
     // Note that `rustflags` is used for the cargo-feature `profile_rustflags`
     pub rustflags: Option<Vec<String>>,
-    // These two fields must be last because they are sub-tables, and TOML
+    /// Unstable feature `profile-linker`.
+    pub linker: Option<String>,
+    /// Unstable feature `profile-linker`.
+    pub link_args: Option<Vec<String>>,
+    // These fields must be last because they are sub-tables, and TOML
     // requires all non-tables to be listed first.
+    /// Environment variables set for rustc and build scripts.
+    ///
+    /// Unstable feature `profile-env`.
+    pub env: Option<BTreeMap<String, String>>,
     pub package: Option<BTreeMap<ProfilePackageSpec, TomlProfile>>,
     pub build_override: Option<Box<TomlProfile>>,
     /// Unstable feature `-Ztrim-paths`.
     pub trim_paths: Option<TomlTrimPaths>,
+    /// Overrides for a target triple or `cfg()` expression.
+    ///
+    /// Unstable feature `profile-target-overrides`.
+    pub target: Option<BTreeMap<String, TomlProfile>>,
 }
 
 impl TomlProfile {
@@ -960,6 +972,20 @@ impl TomlProfile {
             self.rustflags = Some(v.clone());
         }
 
+        if let Some(v) = &profile.linker {
+            self.linker = Some(v.clone());
+        }
+
+        if let Some(v) = &profile.link_args {
+            self.link_args = Some(v.clone());
+        }
+
+        if let Some(other_env) = &profile.env {
+            self.env
+                .get_or_insert_with(BTreeMap::new)
+                .extend(other_env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+
         if let Some(other_package) = &profile.package {
             match &mut self.package {
                 Some(self_package) => {
@@ -983,6 +1009,22 @@ impl TomlProfile {
             }
         }
 
+        if let Some(other_target) = &profile.target {
+            match &mut self.target {
+                Some(self_target) => {
+                    for (platform, other_target_profile) in other_target {
+                        match self_target.get_mut(platform) {
+                            Some(p) => p.merge(other_target_profile),
+                            None => {
+                                self_target.insert(platform.clone(), other_target_profile.clone());
+                            }
+                        }
+                    }
+                }
+                None => self.target = Some(other_target.clone()),
+            }
+        }
+
         if let Some(v) = &profile.inherits {
             self.inherits = Some(v.clone());
         }
@@ -16,7 +16,8 @@
 //! 1. Call `rustc` and collect the JSON data.
 //! 2. Pass the json data to [`get_suggestions_from_json`].
 //! 3. Create a [`CodeFix`] with the source of a file to modify.
-//! 4. Call [`CodeFix::apply`] to apply a change.
+//! 4. Call [`CodeFix::apply`] to apply a change,
+//!    or [`CodeFix::apply_all`] to apply as many as possible and get back the rejected ones.
 //! 5. Call [`CodeFix::finish`] to get the result and write it back to disk.
 //!
 //! > This crate is maintained by the Cargo team, primarily for use by Cargo and Rust compiler test suite
@@ -26,6 +27,8 @@
 use std::collections::HashSet;
 use std::ops::Range;
 
+use serde::{Deserialize, Serialize};
+
 pub mod diagnostics;
 mod error;
 mod replace;
@@ -61,7 +64,7 @@ pub fn get_suggestions_from_json<S: ::std::hash::BuildHasher>(
     Ok(result)
 }
 
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LinePosition {
     pub line: usize,
     pub column: usize,
@@ -73,7 +76,7 @@ impl std::fmt::Display for LinePosition {
     }
 }
 
-#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LineRange {
     pub start: LinePosition,
     pub end: LinePosition,
@@ -86,7 +89,7 @@ impl std::fmt::Display for LineRange {
 }
 
 /// An error/warning and possible solutions for fixing it
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Suggestion {
     pub message: String,
     pub snippets: Vec<Snippet>,
@@ -94,7 +97,7 @@ pub struct Suggestion {
 }
 
 /// Solution to a diagnostic item.
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Solution {
     /// The error message of the diagnostic item.
     pub message: String,
@@ -103,7 +106,7 @@ pub struct Solution {
 }
 
 /// Represents code that will get replaced.
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Snippet {
     pub file_name: String,
     pub line_range: LineRange,
@@ -111,7 +114,7 @@ pub struct Snippet {
 }
 
 /// Represents a replacement of a `snippet`.
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Replacement {
     /// Code snippet that gets replaced.
     pub snippet: Snippet,
@@ -257,6 +260,41 @@ impl CodeFix {
         Ok(())
     }
 
+    /// Applies as many of the given suggestions as possible, in order.
+    ///
+    /// A suggestion is applied entirely or not at all. Suggestions that fail
+    /// to apply, usually because they overlap with one applied earlier in this
+    /// call, are skipped and returned as [`Rejected`], so the result is a
+    /// maximal non-conflicting subset of `suggestions`.
+    pub fn apply_all<'a>(
+        &mut self,
+        suggestions: impl IntoIterator<Item = &'a Suggestion>,
+    ) -> Vec<Rejected> {
+        let mut applied: Vec<&Suggestion> = Vec::new();
+        let mut rejected = Vec::new();
+        for suggestion in suggestions {
+            match self.apply(suggestion) {
+                Ok(()) => applied.push(suggestion),
+                Err(error) => {
+                    let conflicts_with = match &error {
+                        Error::AlreadyReplaced { range, .. } => applied
+                            .iter()
+                            .filter(|s| replacements(s).any(|r| overlaps(&r.snippet.range, range)))
+                            .map(|s| (*s).clone())
+                            .collect(),
+                        _ => Vec::new(),
+                    };
+                    rejected.push(Rejected {
+                        suggestion: suggestion.clone(),
+                        error,
+                        conflicts_with,
+                    });
+                }
+            }
+        }
+        rejected
+    }
+
     /// Gets the result of the "fixed" code.
     pub fn finish(&self) -> Result<String, Error> {
         Ok(String::from_utf8(self.data.to_vec())?)
@@ -268,6 +306,45 @@ impl CodeFix {
     }
 }
 
+/// A [`Suggestion`] that [`CodeFix::apply_all`] could not apply.
+#[derive(Debug)]
+pub struct Rejected {
+    /// The suggestion that was skipped.
+    pub suggestion: Suggestion,
+    /// Why the suggestion could not be applied.
+    pub error: Error,
+    /// Previously applied suggestions that overlap with the skipped one.
+    ///
+    /// This is empty if the suggestion was rejected for another reason,
+    /// such as a span outside of the source.
+    pub conflicts_with: Vec<Suggestion>,
+}
+
+impl Rejected {
+    /// Returns `true` if the suggestion repeats a replacement that was already applied.
+    ///
+    /// Most clients will want to ignore these.
+    pub fn is_identical(&self) -> bool {
+        matches!(
+            self.error,
+            Error::AlreadyReplaced {
+                is_identical: true,
+                ..
+            }
+        )
+    }
+}
+
+fn replacements(suggestion: &Suggestion) -> impl Iterator<Item = &Replacement> {
+    suggestion.solutions.iter().flat_map(|s| &s.replacements)
+}
+
+/// Whether two replacements of the original source would conflict,
+/// following the rules of [`replace::Data::replace_range`].
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a == b || (a.start < b.end && b.start < a.end)
+}
+
 /// Applies multiple `suggestions` to the given `code`, handling certain conflicts automatically.
 ///
 /// If a replacement in a suggestion exactly matches a replacement of a previously applied solution,
@@ -280,13 +357,9 @@ impl CodeFix {
 /// looping until either there are no more suggestions to apply or some budget is exhausted.
 pub fn apply_suggestions(code: &str, suggestions: &[Suggestion]) -> Result<String, Error> {
     let mut fix = CodeFix::new(code);
-    for suggestion in suggestions.iter().rev() {
-        fix.apply(suggestion).or_else(|err| match err {
-            Error::AlreadyReplaced {
-                is_identical: true, ..
-            } => Ok(()),
-            _ => Err(err),
-        })?;
+    let rejected = fix.apply_all(suggestions.iter().rev());
+    if let Some(rejected) = rejected.into_iter().find(|r| !r.is_identical()) {
+        return Err(rejected.error);
     }
     fix.finish()
 }
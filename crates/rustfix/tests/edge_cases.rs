@@ -1,6 +1,8 @@
 use std::collections::HashSet;
 use std::fs;
 
+use rustfix::{CodeFix, LinePosition, LineRange, Replacement, Snippet, Solution, Suggestion};
+
 macro_rules! expect_empty_json_test {
     ($name:ident, $file:expr) => {
         #[test]
@@ -22,3 +24,58 @@ expect_empty_json_test! {utf8_identifiers_test, "utf8_idents.recorded.json"}
 expect_empty_json_test! {empty, "empty.json"}
 expect_empty_json_test! {no_main, "no_main.json"}
 expect_empty_json_test! {indented_whitespace, "indented_whitespace.json"}
+
+fn suggestion(message: &str, range: std::ops::Range<usize>, replacement: &str) -> Suggestion {
+    let snippet = Snippet {
+        file_name: "lib.rs".to_owned(),
+        line_range: LineRange {
+            start: LinePosition {
+                line: 1,
+                column: range.start + 1,
+            },
+            end: LinePosition {
+                line: 1,
+                column: range.end + 1,
+            },
+        },
+        range,
+    };
+    Suggestion {
+        message: message.to_owned(),
+        snippets: vec![snippet.clone()],
+        solutions: vec![Solution {
+            message: format!("replace with `{replacement}`"),
+            replacements: vec![Replacement {
+                snippet,
+                replacement: replacement.to_owned(),
+            }],
+        }],
+    }
+}
+
+#[test]
+fn apply_all_reports_conflicts() {
+    let first = suggestion("first", 4..7, "one");
+    let overlapping = suggestion("overlapping", 5..9, "two");
+    let identical = suggestion("identical", 4..7, "one");
+    let separate = suggestion("separate", 8..11, "three");
+    let out_of_bounds = suggestion("out of bounds", 20..24, "four");
+
+    let mut fix = CodeFix::new("foo bar baz");
+    let rejected = fix.apply_all([&first, &overlapping, &identical, &separate, &out_of_bounds]);
+    assert_eq!(fix.finish().unwrap(), "foo one three");
+
+    assert_eq!(rejected.len(), 3);
+    assert_eq!(rejected[0].suggestion, overlapping);
+    assert!(!rejected[0].is_identical());
+    assert_eq!(rejected[0].conflicts_with, [first.clone()]);
+    assert_eq!(rejected[1].suggestion, identical);
+    assert!(rejected[1].is_identical());
+    assert_eq!(rejected[1].conflicts_with, [first]);
+    assert_eq!(rejected[2].suggestion, out_of_bounds);
+    assert!(matches!(
+        rejected[2].error,
+        rustfix::Error::DataLengthExceeded(..)
+    ));
+    assert!(rejected[2].conflicts_with.is_empty());
+}
@@ -69,6 +69,8 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     let ops = TestOptions {
         no_run: args.flag("no-run"),
         no_fail_fast: args.flag("no-fail-fast"),
+        test_jobs: None,
+        junit: None,
         compile_opts,
     };
 
@@ -1,5 +1,6 @@
 use crate::command_prelude::*;
 
+use cargo::core::compiler;
 use cargo::ops;
 
 pub fn cli() -> Command {
@@ -36,6 +37,14 @@ pub fn cli() -> Command {
         .arg_target_dir()
         .arg_artifact_dir()
         .arg_build_plan()
+        .arg(
+            opt(
+                "from-plan",
+                "Run the steps of a build plan from `--build-plan=2` (unstable)",
+            )
+            .value_name("PATH")
+            .help_heading(heading::COMPILATION_OPTIONS),
+        )
         .arg_unit_graph()
         .arg_timings()
         .arg_manifest_path()
@@ -47,6 +56,14 @@ pub fn cli() -> Command {
 }
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    if let Some(plan) = args.value_of_path("from-plan", gctx) {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--from-plan", 5579)?;
+        // The plan has all the commands to run, so the workspace isn't loaded.
+        compiler::replay_build_plan(gctx, &plan)?;
+        return Ok(());
+    }
+
     let ws = args.workspace(gctx)?;
     let mut compile_opts =
         args.compile_options(gctx, CompileMode::Build, Some(&ws), ProfileChecking::Custom)?;
@@ -28,6 +28,61 @@ pub fn cli() -> Command {
                         .default_value("yes"),
                 ),
         )
+        .subcommand(
+            subcommand("set")
+                .about("Set a config value in a config file")
+                .arg(
+                    Arg::new("key")
+                        .action(ArgAction::Set)
+                        .required(true)
+                        .help("The config key to set"),
+                )
+                .arg(
+                    Arg::new("value")
+                        .action(ArgAction::Set)
+                        .required(true)
+                        .help("The value to set, as TOML or a plain string"),
+                )
+                .args(location_args()),
+        )
+        .subcommand(
+            subcommand("unset")
+                .about("Remove a config value from a config file")
+                .arg(
+                    Arg::new("key")
+                        .action(ArgAction::Set)
+                        .required(true)
+                        .help("The config key to remove"),
+                )
+                .args(location_args()),
+        )
+        .subcommand(subcommand("list").about("List all config values and where they are defined"))
+}
+
+fn location_args() -> [Arg; 2] {
+    [
+        flag(
+            "user",
+            "Use the config file in the Cargo home directory instead of `.cargo/config.toml`",
+        )
+        .conflicts_with("file"),
+        opt(
+            "file",
+            "Use the given config file instead of `.cargo/config.toml`",
+        )
+        .value_name("PATH")
+        .value_parser(clap::value_parser!(std::path::PathBuf)),
+    ]
+}
+
+fn location(args: &ArgMatches) -> cargo_config::ConfigLocation {
+    if args.flag("user") {
+        cargo_config::ConfigLocation::User
+    } else if let Some(path) = args.get_one::<std::path::PathBuf>("file") {
+        cargo_config::ConfigLocation::File(path.clone())
+    } else {
+        cargo_config::ConfigLocation::Project
+    }
 }
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
@@ -48,6 +103,24 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
             };
             cargo_config::get(gctx, &opts)?;
         }
+        Some(("set", args)) => {
+            let opts = cargo_config::SetOptions {
+                key: args.get_one::<String>("key").unwrap(),
+                value: args.get_one::<String>("value").unwrap(),
+                location: location(args),
+            };
+            cargo_config::set(gctx, &opts)?;
+        }
+        Some(("unset", args)) => {
+            let opts = cargo_config::UnsetOptions {
+                key: args.get_one::<String>("key").unwrap(),
+                location: location(args),
+            };
+            cargo_config::unset(gctx, &opts)?;
+        }
+        Some(("list", _)) => {
+            cargo_config::list(gctx)?;
+        }
         Some((cmd, _)) => {
             unreachable!("unexpected command {}", cmd)
         }
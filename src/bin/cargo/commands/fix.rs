@@ -27,6 +27,13 @@ pub fn cli() -> Command {
             "allow-staged",
             "Fix code even if the working directory has staged changes",
         ))
+        .arg(
+            flag(
+                "interactive",
+                "Review the fixes before applying them, or print them as a diff (unstable)",
+            )
+            .conflicts_with("edition"),
+        )
         .arg_message_format()
         .arg_silent_suggestion()
         .arg_package_spec(
@@ -87,6 +94,10 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     }
 
     let allow_dirty = args.flag("allow-dirty");
+    if args.flag("interactive") {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--interactive", None)?;
+    }
 
     ops::fix(
         gctx,
@@ -100,6 +111,7 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
             allow_staged: allow_dirty || args.flag("allow-staged"),
             allow_no_vcs: args.flag("allow-no-vcs"),
             broken_code: args.flag("broken-code"),
+            interactive: args.flag("interactive"),
             requested_lockfile_path: lockfile_path,
         },
     )?;
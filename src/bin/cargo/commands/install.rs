@@ -79,6 +79,56 @@ pub fn cli() -> Command {
             "list",
             "List all installed packages and their versions",
         ))
+        .arg(
+            flag(
+                "outdated",
+                "With --list, only list packages with a newer version available (unstable)",
+            )
+            .requires("list"),
+        )
+        .arg(
+            flag(
+                "update-all",
+                "Reinstall all installed packages with a newer version available (unstable)",
+            )
+            .conflicts_with_all(&[
+                "crate",
+                "git",
+                "path",
+                "list",
+                "force",
+                "no-track",
+                "bin",
+                "bins",
+                "example",
+                "examples",
+                "features",
+                "all-features",
+                "no-default-features",
+                "debug",
+                "profile",
+                "target",
+                "lockfile-path",
+            ]),
+        )
+        .arg(
+            flag(
+                "from-manifest",
+                "Install the tools listed in the workspace's tools.toml (unstable)",
+            )
+            .conflicts_with_all(&[
+                "crate",
+                "git",
+                "path",
+                "list",
+                "update-all",
+                "no-track",
+                "features",
+                "all-features",
+                "no-default-features",
+                "lockfile-path",
+            ]),
+        )
         .arg_ignore_rust_version()
         .arg_message_format()
         .arg_silent_suggestion()
@@ -112,6 +162,11 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     let path = args.value_of_path("path", gctx);
     if let Some(path) = &path {
         gctx.reload_rooted_at(path)?;
+    } else if args.flag("from-manifest") {
+        // Tools are installed for the workspace, so use its configuration,
+        // and the versions pinned by their lock files.
+        gctx.reload_rooted_at(gctx.cwd().to_path_buf())?;
+        gctx.set_locked(true);
     } else {
         // TODO: Consider calling set_search_stop_path(home).
         gctx.reload_rooted_at(gctx.home().clone().into_path_unlocked())?;
@@ -209,6 +264,17 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     if args.dry_run() {
         gctx.cli_unstable().fail_if_stable_opt("--dry-run", 11123)?;
     }
+    if args.flag("outdated") {
+        gctx.cli_unstable().fail_if_stable_opt("--outdated", None)?;
+    }
+    if args.flag("update-all") {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--update-all", None)?;
+    }
+    if args.flag("from-manifest") {
+        gctx.cli_unstable()
+            .fail_if_stable_opt("--from-manifest", None)?;
+    }
 
     let requested_lockfile_path = args.lockfile_path(gctx)?;
     // 14421: lockfile path should imply --locked on running `install`
@@ -217,7 +283,12 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     }
 
     if args.flag("list") {
-        ops::install_list(root, gctx)?;
+        ops::install_list(root, gctx, args.flag("outdated"))?;
+    } else if args.flag("from-manifest") {
+        let ws = args.workspace(gctx)?;
+        ops::install_from_manifest(&ws, root, &compile_opts, args.flag("force"), args.dry_run())?;
+    } else if args.flag("update-all") {
+        ops::install_update_all(gctx, root, &compile_opts, args.dry_run())?;
     } else {
         ops::install(
             gctx,
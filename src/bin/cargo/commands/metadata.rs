@@ -1,4 +1,5 @@
-use cargo::ops::{self, OutputMetadataOptions};
+use cargo::core::compiler::CompileKind;
+use cargo::ops::{self, OutputMetadataOptions, Packages};
 
 use crate::command_prelude::*;
 
@@ -24,8 +25,15 @@ pub fn cli() -> Command {
                 .value_name("VERSION")
                 .value_parser(["1"]),
         )
+        .arg(flag(
+            "units",
+            "Output the units of a build of the workspace, \
+             with how they are compiled (unstable)",
+        ))
         .arg_silent_suggestion()
         .arg_features()
+        .arg_release("Output the units of a build in release mode, with `--units`")
+        .arg_profile("Output the units of a build with the specified profile, with `--units`")
         .arg_manifest_path()
         .arg_lockfile_path()
         .after_help(color_print::cstr!(
@@ -47,11 +55,32 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         Some(version) => version.parse().unwrap(),
     };
 
+    let filter_platforms = args._values_of("filter-platform");
+    let units = if args.flag("units") {
+        if !gctx.cli_unstable().unstable_options {
+            return Err(anyhow::format_err!("`--units` requires `-Zunstable-options`").into());
+        }
+        let mut compile_opts =
+            args.compile_options(gctx, CompileMode::Build, Some(&ws), ProfileChecking::Custom)?;
+        // Like the resolve, the units are for the whole workspace.
+        compile_opts.spec = Packages::All(Vec::new());
+        if !filter_platforms.is_empty() {
+            compile_opts.build_config.requested_kinds =
+                CompileKind::from_requested_targets(gctx, &filter_platforms)?;
+        }
+        Some(compile_opts)
+    } else if args.flag("release") || args.contains_id("profile") {
+        return Err(anyhow::format_err!("`--release` and `--profile` require `--units`").into());
+    } else {
+        None
+    };
+
     let options = OutputMetadataOptions {
         cli_features: args.cli_features()?,
         no_deps: args.flag("no-deps"),
-        filter_platforms: args._values_of("filter-platform"),
+        filter_platforms,
         version,
+        units,
     };
 
     let result = ops::output_metadata(&ws, &options)?;
@@ -1,6 +1,8 @@
 use crate::command_prelude::*;
 use cargo::core::compiler::future_incompat::{OnDiskReports, REPORT_PREAMBLE};
 use cargo::drop_println;
+use cargo::ops;
+use std::path::PathBuf;
 
 pub fn cli() -> Command {
     subcommand("report")
@@ -23,11 +25,58 @@ pub fn cli() -> Command {
                 )
                 .arg_package("Package to display a report for"),
         )
+        .subcommand(
+            subcommand("timings")
+                .about("Reports how long the units of a build took to compile (unstable)")
+                .arg(
+                    Arg::new("path")
+                        .value_name("PATH")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help(
+                            "Report saved by `--timings=json` [default: \
+                             target/cargo-timings/cargo-timing.json]",
+                        ),
+                )
+                .arg(
+                    opt(
+                        "compare",
+                        "Compare two reports, listing the units that regressed",
+                    )
+                    .value_names(["OLD", "NEW"])
+                    .num_args(2)
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .conflicts_with("path"),
+                )
+                .arg(
+                    opt(
+                        "threshold",
+                        "How much slower in percent a unit must be to have regressed",
+                    )
+                    .value_name("PERCENT")
+                    .value_parser(clap::value_parser!(f64))
+                    .default_value("10")
+                    .requires("compare"),
+                )
+                .arg_manifest_path(),
+        )
+        .subcommand(
+            subcommand("rebuild-reasons")
+                .about("Reports why units were rebuilt by the last build (unstable)")
+                .arg_manifest_path(),
+        )
+        .subcommand(
+            subcommand("build-scripts")
+                .about("Reports what the build scripts of the last build did (unstable)")
+                .arg_manifest_path(),
+        )
 }
 
 pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
     match args.subcommand() {
         Some(("future-incompatibilities", args)) => report_future_incompatibilities(gctx, args),
+        Some(("timings", args)) => report_timings(gctx, args),
+        Some(("rebuild-reasons", args)) => report_rebuild_reasons(gctx, args),
+        Some(("build-scripts", args)) => report_build_scripts(gctx, args),
         Some((cmd, _)) => {
             unreachable!("unexpected command {}", cmd)
         }
@@ -49,3 +98,64 @@ fn report_future_incompatibilities(gctx: &GlobalContext, args: &ArgMatches) -> C
     drop(gctx.shell().print_ansi_stdout(report.as_bytes()));
     Ok(())
 }
+
+fn report_timings(gctx: &GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "report timings",
+        7405,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    if let Some(mut reports) = args.get_many::<PathBuf>("compare") {
+        let old = reports.next().unwrap();
+        let new = reports.next().unwrap();
+        let threshold = *args.get_one::<f64>("threshold").unwrap();
+        ops::compare_timings(
+            gctx,
+            &ops::CompareTimingsOptions {
+                old: &gctx.cwd().join(old),
+                new: &gctx.cwd().join(new),
+                threshold,
+            },
+        )?;
+        return Ok(());
+    }
+    let path = match args.get_one::<PathBuf>("path") {
+        Some(path) => gctx.cwd().join(path),
+        None => {
+            let ws = args.workspace(gctx)?;
+            ws.target_dir()
+                .as_path_unlocked()
+                .join("cargo-timings/cargo-timing.json")
+        }
+    };
+    ops::report_timings(gctx, &path)?;
+    Ok(())
+}
+
+fn report_rebuild_reasons(gctx: &GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "report rebuild-reasons",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let ws = args.workspace(gctx)?;
+    ops::report_rebuild_reasons(&ws)?;
+    Ok(())
+}
+
+fn report_build_scripts(gctx: &GlobalContext, args: &ArgMatches) -> CliResult {
+    gctx.cli_unstable().fail_if_stable_command(
+        gctx,
+        "report build-scripts",
+        None,
+        "unstable-options",
+        gctx.cli_unstable().unstable_options,
+    )?;
+    let ws = args.workspace(gctx)?;
+    ops::report_build_scripts(&ws)?;
+    Ok(())
+}
@@ -20,6 +20,27 @@ pub fn cli() -> Command {
         )
         .arg(flag("no-run", "Compile, but don't run tests"))
         .arg(flag("no-fail-fast", "Run all tests regardless of failure"))
+        .arg(
+            opt(
+                "shard",
+                "Only build and run the N-th of M disjoint subsets of the test targets (unstable)",
+            )
+            .value_name("N/M"),
+        )
+        .arg(
+            opt(
+                "test-jobs",
+                "Number of test executables to run in parallel (unstable)",
+            )
+            .value_name("N"),
+        )
+        .arg(
+            opt(
+                "junit",
+                "Write a JUnit XML report of the test results (unstable)",
+            )
+            .value_name("PATH"),
+        )
         .arg_future_incompat_report()
         .arg_message_format()
         .arg(
@@ -105,9 +126,41 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         compile_opts.filter = ops::CompileFilter::all_test_targets();
     }
 
+    if let Some(shard) = args.get_one::<String>("shard") {
+        if !gctx.cli_unstable().unstable_options {
+            return Err(anyhow::format_err!("`--shard` requires `-Zunstable-options`").into());
+        }
+        compile_opts.shard = Some(shard.parse()?);
+    }
+
+    let test_jobs = args.value_of_u32("test-jobs")?;
+    if let Some(test_jobs) = test_jobs {
+        if !gctx.cli_unstable().unstable_options {
+            return Err(anyhow::format_err!("`--test-jobs` requires `-Zunstable-options`").into());
+        }
+        if test_jobs == 0 {
+            return Err(anyhow::format_err!("test jobs may not be 0").into());
+        }
+        if !args.flag("no-fail-fast") {
+            gctx.shell().warn(
+                "`--test-jobs` implies `--no-fail-fast`, \
+                 all test executables are run even if some of them fail",
+            )?;
+        }
+    }
+
+    let junit = args.value_of_path("junit", gctx);
+    if junit.is_some() && !gctx.cli_unstable().unstable_options {
+        return Err(anyhow::format_err!("`--junit` requires `-Zunstable-options`").into());
+    }
+
     let ops = ops::TestOptions {
         no_run,
-        no_fail_fast: args.flag("no-fail-fast"),
+        // Test executables running in parallel are not stopped when one of
+        // them fails, so make the rest of the run behave the same way.
+        no_fail_fast: args.flag("no-fail-fast") || test_jobs.is_some(),
+        test_jobs,
+        junit,
         compile_opts,
     };
 
@@ -6,6 +6,7 @@ use cargo::ops::tree::{self, DisplayDepth, EdgeKind};
 use cargo::ops::Packages;
 use cargo::util::print_available_packages;
 use cargo::util::CargoResult;
+use clap::parser::ValueSource;
 use std::collections::HashSet;
 use std::str::FromStr;
 
@@ -77,6 +78,15 @@ pub fn cli() -> Command {
                 .short('f')
                 .default_value("{p}"),
         )
+        .arg(
+            opt(
+                "output-format",
+                "Print the dependency graph in another format (unstable)",
+            )
+            .value_name("FMT")
+            .value_parser(["text", "json", "dot"])
+            .default_value("text"),
+        )
         .arg(
             // Backwards compatibility with old cargo-tree.
             flag("version", "Print version info and exit")
@@ -216,6 +226,33 @@ subtree of the package given to -p.\n\
             Charset::Ascii => gctx.shell().set_unicode(false)?,
         }
     }
+    let output_format =
+        tree::OutputFormat::from_str(args.get_one::<String>("output-format").unwrap())
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+    if output_format != tree::OutputFormat::Text {
+        if !gctx.cli_unstable().unstable_options {
+            return Err(format_err!("`--output-format` requires `-Zunstable-options`").into());
+        }
+        if args.flag("duplicates") {
+            return Err(
+                format_err!("the `--output-format` flag does not support `--duplicates`").into(),
+            );
+        }
+        // `--prefix` has a default value, so only reject it when it was given.
+        if args.value_source("prefix") == Some(ValueSource::CommandLine)
+            || args.flag("no-indent")
+            || args.flag("prefix-depth")
+        {
+            return Err(
+                format_err!("the `--output-format` flag does not support `--prefix`").into(),
+            );
+        }
+        if no_dedupe {
+            return Err(
+                format_err!("the `--output-format` flag does not support `--no-dedupe`").into(),
+            );
+        }
+    }
     let opts = tree::TreeOptions {
         cli_features: args.cli_features()?,
         packages,
@@ -230,6 +267,7 @@ subtree of the package given to -p.\n\
         graph_features,
         display_depth,
         no_proc_macro,
+        output_format,
     };
 
     if opts.graph_features && opts.duplicates {
@@ -1,7 +1,7 @@
 use crate::command_prelude::*;
 
 use anyhow::anyhow;
-use cargo::ops::{self, UpdateOptions};
+use cargo::ops::{self, UpdateOptions, VerifyCommand};
 use cargo::util::print_available_packages;
 
 pub fn cli() -> Command {
@@ -45,6 +45,15 @@ pub fn cli() -> Command {
             )
             .short('b'),
         )
+        .arg(
+            opt(
+                "verify",
+                "Verify each update on its own with `cargo <COMMAND>`, reverting failures (unstable)",
+            )
+            .value_name("COMMAND")
+            .value_parser(["check", "test"])
+            .conflicts_with_all(["dry-run", "precise", "recursive"]),
+        )
         .arg_silent_suggestion()
         .arg(
             flag("workspace", "Only update the workspace packages")
@@ -91,7 +100,17 @@ pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
         gctx,
     };
 
-    if args.flag("breaking") {
+    if let Some(verify) = args.get_one::<String>("verify") {
+        if !gctx.cli_unstable().unstable_options {
+            return Err(anyhow!("`--verify` requires `-Zunstable-options`").into());
+        }
+        if args.flag("breaking") {
+            gctx.cli_unstable()
+                .fail_if_stable_opt("--breaking", 12425)?;
+        }
+        let verify = verify.parse::<VerifyCommand>()?;
+        ops::update_verified(&ws, &update_opts, args.flag("breaking"), verify)?;
+    } else if args.flag("breaking") {
         gctx.cli_unstable()
             .fail_if_stable_opt("--breaking", 12425)?;
 
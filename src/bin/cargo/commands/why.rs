@@ -0,0 +1,35 @@
+use crate::command_prelude::*;
+
+use anyhow::Context as _;
+use cargo::core::PackageIdSpec;
+use cargo::ops;
+
+pub fn cli() -> Command {
+    subcommand("why")
+        .about("Explain why a package was selected at its version (unstable)")
+        .arg(
+            Arg::new("package")
+                .required(true)
+                .value_name("SPEC")
+                .help_heading(heading::PACKAGE_SELECTION)
+                .help("Package to explain"),
+        )
+        .arg_silent_suggestion()
+        .arg_manifest_path()
+        .arg_lockfile_path()
+}
+
+pub fn exec(gctx: &mut GlobalContext, args: &ArgMatches) -> CliResult {
+    if !gctx.cli_unstable().unstable_options {
+        return Err(anyhow::format_err!(
+            "the `cargo why` command is unstable, pass `-Z unstable-options` to enable it"
+        )
+        .into());
+    }
+    let ws = args.workspace(gctx)?;
+    let package = args.get_one::<String>("package").unwrap();
+    let spec = PackageIdSpec::parse(package)
+        .with_context(|| format!("invalid package ID specification: `{package}`"))?;
+    ops::why(&ws, &spec)?;
+    Ok(())
+}
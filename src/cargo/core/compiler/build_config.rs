@@ -1,4 +1,5 @@
 use crate::core::compiler::CompileKind;
+use crate::core::gc::parse_human_size;
 use crate::util::context::JobsConfig;
 use crate::util::interning::InternedString;
 use crate::util::{CargoResult, GlobalContext, RustfixDiagnosticServer};
@@ -29,6 +30,8 @@ pub struct BuildConfig {
     pub force_rebuild: bool,
     /// Output a build plan to stdout instead of actually compiling.
     pub build_plan: bool,
+    /// The version of the build plan format, see `--build-plan`.
+    pub build_plan_version: u32,
     /// Output the unit graph to stdout instead of actually compiling.
     pub unit_graph: bool,
     /// `true` to avoid really compiling.
@@ -50,6 +53,15 @@ pub struct BuildConfig {
     pub timing_outputs: Vec<TimingOutput>,
     /// Output SBOM precursor files.
     pub sbom: bool,
+    /// Whether to use file checksums rather than mtimes to tell if units are
+    /// fresh.
+    pub checksum_freshness: bool,
+    /// The memory that the jobs of the build are expected to use at most, in
+    /// bytes.
+    pub memory_limit: Option<u64>,
+    /// Whether to check the memory available on the system before starting a
+    /// job.
+    pub sample_free_memory: bool,
 }
 
 fn default_parallelism() -> CargoResult<u32> {
@@ -64,6 +76,9 @@ impl BuildConfig {
     ///
     /// * `build.jobs`
     /// * `build.target`
+    /// * `build.checksum-freshness`
+    /// * `build.memory-limit`
+    /// * `build.sample-free-memory`
     /// * `target.$target.ar`
     /// * `target.$target.linker`
     /// * `target.$target.libfoo.metadata`
@@ -112,6 +127,30 @@ impl BuildConfig {
             (None, _) => false,
         };
 
+        // The memory limits require the unstable feature
+        let mut memory_limit = None;
+        let mut sample_free_memory = false;
+        if gctx.cli_unstable().memory_limit {
+            memory_limit = cfg
+                .memory_limit
+                .as_deref()
+                .map(parse_human_size)
+                .transpose()
+                .context("failed to parse `build.memory-limit`")?;
+            sample_free_memory = cfg.sample_free_memory.unwrap_or(false);
+        } else {
+            if cfg.memory_limit.is_some() {
+                gctx.shell().warn(
+                    "ignoring 'build.memory-limit' config, pass `-Zmemory-limit` to enable it",
+                )?;
+            }
+            if cfg.sample_free_memory.is_some() {
+                gctx.shell().warn(
+                    "ignoring 'build.sample-free-memory' config, pass `-Zmemory-limit` to enable it",
+                )?;
+            }
+        }
+
         Ok(BuildConfig {
             requested_kinds,
             jobs,
@@ -121,6 +160,7 @@ impl BuildConfig {
             message_format: MessageFormat::Human,
             force_rebuild: false,
             build_plan: false,
+            build_plan_version: 1,
             unit_graph: false,
             dry_run: false,
             primary_unit_rustc: None,
@@ -129,6 +169,10 @@ impl BuildConfig {
             future_incompat_report: false,
             timing_outputs: Vec::new(),
             sbom,
+            checksum_freshness: cfg.checksum_freshness.unwrap_or(false)
+                || gctx.cli_unstable().checksum_freshness,
+            memory_limit,
+            sample_free_memory,
         })
     }
 
@@ -0,0 +1,440 @@
+//! A graph-like structure used to represent the rustc commands to build the package and the
+//! interdependencies between them.
+//!
+//! The `BuildPlan` structure is used to store the dependency graph of a dry run so that it can be
+//! shared with an external build system. Each Invocation in the `BuildPlan` comprises a single
+//! subprocess and defines the build environment, the outputs produced by the subprocess, and the
+//! dependencies on other Invocations.
+//!
+//! Version 2 of the format, requested with `--build-plan=2`, can be replayed
+//! with `cargo build --from-plan`, see the [`replay`] module. It records the
+//! runs of build scripts as steps like any other, and for each step which
+//! parts of the output of those build scripts are added to its command. Build
+//! scripts are not run when the plan is generated, so their output is only
+//! known once the plan is replayed.
+
+mod replay;
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use cargo_util_schemas::manifest::RustVersion;
+use serde::{Deserialize, Serialize};
+
+use super::build_runner::OutputFile;
+use super::{BuildRunner, CompileKind, CompileMode, LinkArgTarget, Unit, UnitHash};
+use crate::core::{PackageIdSpec, Target, TargetKind};
+use crate::util::{internal, CargoResult, GlobalContext};
+use cargo_util::ProcessBuilder;
+
+pub use self::replay::replay_build_plan;
+
+#[derive(Debug, Serialize)]
+struct Invocation {
+    package_name: String,
+    package_version: semver::Version,
+    target_kind: TargetKind,
+    kind: CompileKind,
+    compile_mode: CompileMode,
+    deps: Vec<usize>,
+    outputs: Vec<PathBuf>,
+    links: BTreeMap<PathBuf, PathBuf>,
+    program: String,
+    args: Vec<String>,
+    env: BTreeMap<String, String>,
+    cwd: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct BuildPlan {
+    invocation_map: BTreeMap<String, usize>,
+    plan: SerializedBuildPlan,
+    /// The version of the format to output.
+    version: u32,
+    /// What version 2 records on top of each invocation.
+    steps: Vec<StepInfo>,
+    /// The steps running build scripts, by the metadata hash of their unit.
+    script_steps: HashMap<UnitHash, usize>,
+    host_dest: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+struct SerializedBuildPlan {
+    invocations: Vec<Invocation>,
+    inputs: Vec<PathBuf>,
+}
+
+#[derive(Debug, Serialize)]
+struct SerializedBuildPlanV2 {
+    version: u32,
+    steps: Vec<Step>,
+    inputs: Vec<PathBuf>,
+    /// Where build scripts may put dynamic libraries, see
+    /// [`ScriptOutputUse::dylib_path`].
+    host_dest: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+struct Step {
+    id: usize,
+    #[serde(flatten)]
+    invocation: Invocation,
+    #[serde(flatten)]
+    info: StepInfo,
+}
+
+#[derive(Debug, Serialize)]
+struct StepInfo {
+    package_id: PackageIdSpec,
+    target_name: String,
+    /// Set for the steps running a build script.
+    build_script: Option<BuildScriptRun>,
+    /// How the output of build scripts run by earlier steps is added to the
+    /// command of this step.
+    build_script_outputs: Vec<ScriptOutputUse>,
+}
+
+/// What is needed to interpret the output of a build script.
+#[derive(Debug, Serialize, Deserialize)]
+struct BuildScriptRun {
+    /// The `OUT_DIR` of the build script.
+    out_dir: PathBuf,
+    /// Where the standard output of the build script is kept.
+    output_file: PathBuf,
+    /// The `links` key of the package, which names the `DEP_<links>_<key>`
+    /// variables set from its metadata for dependent build scripts.
+    links: Option<String>,
+    library_name: Option<String>,
+    rust_version: Option<RustVersion>,
+    /// The targets of the package, that some instructions must name.
+    package_targets: Vec<PlanTarget>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PlanTarget {
+    name: String,
+    /// One of `lib`, `bin`, `example`, `test`, `bench` or `custom-build`.
+    kind: String,
+    crate_types: Vec<String>,
+    /// `None` for metabuild.
+    src_path: Option<PathBuf>,
+    edition: String,
+}
+
+/// Which parts of the output of a build script are added to a command.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScriptOutputUse {
+    /// The step running the build script.
+    step: usize,
+    /// `--cfg` and `--check-cfg` from `cargo::rustc-cfg` and
+    /// `cargo::rustc-check-cfg`.
+    cfgs: bool,
+    /// Environment variables from `cargo::rustc-env`.
+    env: bool,
+    /// `-L` from `cargo::rustc-link-search`.
+    link_search: bool,
+    /// `-l` from `cargo::rustc-link-lib`.
+    link_libs: bool,
+    /// The instructions whose arguments are passed with `-C link-arg`, like
+    /// `rustc-link-arg` or `rustc-link-arg-bin=foo`.
+    link_args: Vec<String>,
+    /// The `cargo::rustc-link-search` paths within `host_dest` are added to
+    /// the dynamic library search path.
+    dylib_path: bool,
+    /// `DEP_<links>_<key>` environment variables from `cargo::metadata`.
+    metadata: bool,
+}
+
+impl Invocation {
+    pub fn new(unit: &Unit, deps: Vec<usize>) -> Invocation {
+        let id = unit.pkg.package_id();
+        Invocation {
+            package_name: id.name().to_string(),
+            package_version: id.version().clone(),
+            kind: unit.kind,
+            target_kind: unit.target.kind().clone(),
+            compile_mode: unit.mode,
+            deps,
+            outputs: Vec::new(),
+            links: BTreeMap::new(),
+            program: String::new(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            cwd: None,
+        }
+    }
+
+    pub fn add_output(&mut self, path: &Path, link: &Option<PathBuf>) {
+        self.outputs.push(path.to_path_buf());
+        if let Some(ref link) = *link {
+            self.links.insert(link.clone(), path.to_path_buf());
+        }
+    }
+
+    pub fn update_cmd(&mut self, cmd: &ProcessBuilder) -> CargoResult<()> {
+        self.program = cmd
+            .get_program()
+            .to_str()
+            .ok_or_else(|| anyhow::format_err!("unicode program string required"))?
+            .to_string();
+        self.cwd = Some(cmd.get_cwd().unwrap().to_path_buf());
+        for arg in cmd.get_args() {
+            self.args.push(
+                arg.to_str()
+                    .ok_or_else(|| anyhow::format_err!("unicode argument string required"))?
+                    .to_string(),
+            );
+        }
+        for (var, value) in cmd.get_envs() {
+            let Some(value) = value else { continue };
+            self.env.insert(
+                var.clone(),
+                value
+                    .to_str()
+                    .ok_or_else(|| anyhow::format_err!("unicode environment value required"))?
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl BuildPlan {
+    pub fn new(version: u32) -> BuildPlan {
+        BuildPlan {
+            invocation_map: BTreeMap::new(),
+            plan: SerializedBuildPlan::new(),
+            version,
+            steps: Vec::new(),
+            script_steps: HashMap::new(),
+            host_dest: PathBuf::new(),
+        }
+    }
+
+    pub fn add(&mut self, build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<()> {
+        let id = self.plan.invocations.len();
+        self.invocation_map.insert(unit.buildkey(), id);
+        let deps = build_runner
+            .unit_deps(unit)
+            .iter()
+            .map(|dep| self.invocation_map[&dep.unit.buildkey()])
+            .collect();
+        let invocation = Invocation::new(unit, deps);
+        self.plan.invocations.push(invocation);
+        if self.version >= 2 {
+            let build_script = if unit.mode.is_run_custom_build() {
+                self.script_steps
+                    .insert(build_runner.get_run_build_script_metadata(unit), id);
+                Some(BuildScriptRun::new(build_runner, unit)?)
+            } else {
+                None
+            };
+            let build_script_outputs = self.script_output_uses(build_runner, unit)?;
+            self.steps.push(StepInfo {
+                package_id: unit.pkg.package_id().to_spec(),
+                target_name: unit.target.name().to_string(),
+                build_script,
+                build_script_outputs,
+            });
+            self.host_dest = build_runner.files().host_dest().to_path_buf();
+        }
+        Ok(())
+    }
+
+    /// Mirrors how the output of build scripts is added to the commands when
+    /// building, in `compiler::rustc` and `custom_build::build_work`.
+    fn script_output_uses(
+        &self,
+        build_runner: &BuildRunner<'_, '_>,
+        unit: &Unit,
+    ) -> CargoResult<Vec<ScriptOutputUse>> {
+        let mut uses = Vec::new();
+        let build_scripts = build_runner.build_scripts.get(unit);
+        if unit.mode.is_run_custom_build() {
+            for dep in build_runner.unit_deps(unit) {
+                if dep.unit.mode.is_run_custom_build() {
+                    let metadata = build_runner.get_run_build_script_metadata(&dep.unit);
+                    self.use_of(&mut uses, metadata)?.metadata = true;
+                }
+            }
+        } else if let Some(build_scripts) = build_scripts {
+            // Binaries don't link with the libraries of a package that also
+            // has a library, as the library does.
+            let pass_l_flag =
+                unit.target.is_lib() || !unit.pkg.targets().iter().any(|t| t.is_lib());
+            for (pkg_id, metadata) in &build_scripts.to_link {
+                let is_own = *pkg_id == unit.pkg.package_id();
+                let script_use = self.use_of(&mut uses, *metadata)?;
+                script_use.link_search = true;
+                script_use.link_libs = is_own && pass_l_flag;
+                script_use.link_args = link_arg_instructions(&unit.target, is_own);
+            }
+        }
+        if let Some(build_scripts) = build_scripts {
+            for (_, metadata) in &build_scripts.plugins {
+                self.use_of(&mut uses, *metadata)?.dylib_path = true;
+            }
+            if !unit.mode.is_run_custom_build() {
+                if let Some(metadata) = build_runner.find_build_script_metadata(unit) {
+                    let script_use = self.use_of(&mut uses, metadata)?;
+                    script_use.cfgs = true;
+                    script_use.env = true;
+                }
+            }
+        }
+        Ok(uses)
+    }
+
+    fn use_of<'a>(
+        &self,
+        uses: &'a mut Vec<ScriptOutputUse>,
+        metadata: UnitHash,
+    ) -> CargoResult<&'a mut ScriptOutputUse> {
+        let step = *self
+            .script_steps
+            .get(&metadata)
+            .ok_or_else(|| internal(format!("no build plan step runs build script {metadata}")))?;
+        let index = match uses.iter().position(|u| u.step == step) {
+            Some(index) => index,
+            None => {
+                uses.push(ScriptOutputUse {
+                    step,
+                    ..Default::default()
+                });
+                uses.len() - 1
+            }
+        };
+        Ok(&mut uses[index])
+    }
+
+    pub fn update(
+        &mut self,
+        invocation_name: &str,
+        cmd: &ProcessBuilder,
+        outputs: &[OutputFile],
+    ) -> CargoResult<()> {
+        let id = self.invocation_map[invocation_name];
+        let invocation =
+            self.plan.invocations.get_mut(id).ok_or_else(|| {
+                internal(format!("couldn't find invocation for {}", invocation_name))
+            })?;
+
+        invocation.update_cmd(cmd)?;
+        for output in outputs.iter() {
+            invocation.add_output(&output.path, &output.hardlink);
+        }
+
+        Ok(())
+    }
+
+    pub fn set_inputs(&mut self, inputs: Vec<PathBuf>) {
+        self.plan.inputs = inputs;
+    }
+
+    pub fn output_plan(self, gctx: &GlobalContext) {
+        let encoded = if self.version >= 2 {
+            let steps = self
+                .plan
+                .invocations
+                .into_iter()
+                .zip(self.steps)
+                .enumerate()
+                .map(|(id, (invocation, info))| Step {
+                    id,
+                    invocation,
+                    info,
+                })
+                .collect();
+            serde_json::to_string(&SerializedBuildPlanV2 {
+                version: self.version,
+                steps,
+                inputs: self.plan.inputs,
+                host_dest: self.host_dest,
+            })
+        } else {
+            serde_json::to_string(&self.plan)
+        };
+        crate::drop_println!(gctx, "{}", encoded.unwrap());
+    }
+}
+
+impl BuildScriptRun {
+    fn new(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<BuildScriptRun> {
+        let metadata = build_runner.get_run_build_script_metadata(unit);
+        if build_runner
+            .build_script_outputs
+            .lock()
+            .unwrap()
+            .get(metadata)
+            .is_some()
+        {
+            anyhow::bail!(
+                "the build script of `{}` is overridden in the configuration, \
+                 which version 2 of the build plan doesn't support",
+                unit.pkg.package_id()
+            );
+        }
+        Ok(BuildScriptRun {
+            out_dir: build_runner.files().build_script_out_dir(unit),
+            output_file: build_runner
+                .files()
+                .build_script_run_dir(unit)
+                .join("output"),
+            links: unit.pkg.manifest().links().map(str::to_string),
+            library_name: unit.pkg.library().map(|t| t.crate_name()),
+            rust_version: unit.pkg.rust_version().cloned(),
+            package_targets: unit.pkg.targets().iter().map(PlanTarget::new).collect(),
+        })
+    }
+}
+
+impl PlanTarget {
+    fn new(target: &Target) -> PlanTarget {
+        let kind = match target.kind() {
+            TargetKind::Lib(_) => "lib",
+            TargetKind::Bin => "bin",
+            TargetKind::ExampleBin | TargetKind::ExampleLib(_) => "example",
+            TargetKind::Test => "test",
+            TargetKind::Bench => "bench",
+            TargetKind::CustomBuild => "custom-build",
+        };
+        PlanTarget {
+            name: target.name().to_string(),
+            kind: kind.to_string(),
+            crate_types: target
+                .rustc_crate_types()
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            src_path: target.src_path().path().map(Path::to_path_buf),
+            edition: target.edition().to_string(),
+        }
+    }
+}
+
+/// The build script instructions whose linker arguments are passed to rustc
+/// for `target`, see `add_native_deps` in `compiler::rustc`.
+fn link_arg_instructions(target: &Target, is_own: bool) -> Vec<String> {
+    [
+        LinkArgTarget::All,
+        LinkArgTarget::Cdylib,
+        LinkArgTarget::Bin,
+        LinkArgTarget::SingleBin(target.name().to_string()),
+        LinkArgTarget::Test,
+        LinkArgTarget::Bench,
+        LinkArgTarget::Example,
+    ]
+    .into_iter()
+    .filter(|lt| lt.applies_to(target) && (is_own || *lt == LinkArgTarget::Cdylib))
+    .map(|lt| lt.instruction())
+    .collect()
+}
+
+impl SerializedBuildPlan {
+    pub fn new() -> SerializedBuildPlan {
+        SerializedBuildPlan {
+            invocations: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Replaying a version 2 build plan, for `cargo build --from-plan`.
+//!
+//! The steps are run one at a time, in the order of the plan, which lists the
+//! dependencies of a step before it. Nothing is resolved, and nothing is
+//! checked for freshness: every step runs, with the output of the build
+//! scripts of earlier steps added to its command as the plan describes.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{bail, Context as _};
+use cargo_util::{paths, ProcessBuilder};
+use serde::Deserialize;
+
+use super::{BuildScriptRun, PlanTarget, ScriptOutputUse};
+use crate::core::compiler::custom_build::Severity;
+use crate::core::compiler::{envify, filter_dynamic_search_path, BuildOutput, CrateType};
+use crate::core::{Edition, Target, Verbosity};
+use crate::util::{CargoResult, GlobalContext};
+
+#[derive(Deserialize)]
+struct PlanHeader {
+    version: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct Plan {
+    steps: Vec<PlanStep>,
+    host_dest: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct PlanStep {
+    id: usize,
+    package_name: String,
+    package_version: String,
+    deps: Vec<usize>,
+    outputs: Vec<PathBuf>,
+    links: BTreeMap<PathBuf, PathBuf>,
+    program: String,
+    args: Vec<String>,
+    env: BTreeMap<String, String>,
+    cwd: Option<PathBuf>,
+    build_script: Option<BuildScriptRun>,
+    build_script_outputs: Vec<ScriptOutputUse>,
+}
+
+/// Runs the steps of the build plan at `path`.
+pub fn replay_build_plan(gctx: &GlobalContext, path: &Path) -> CargoResult<()> {
+    let contents = paths::read(path)?;
+    let header: PlanHeader = serde_json::from_str(&contents)
+        .with_context(|| format!("failed to parse build plan `{}`", path.display()))?;
+    if header.version != Some(2) {
+        bail!(
+            "build plan `{}` can't be replayed, only version 2 of the format can\n\
+             help: generate it with `cargo build --build-plan=2`",
+            path.display()
+        );
+    }
+    let plan: Plan = serde_json::from_str(&contents)
+        .with_context(|| format!("failed to parse build plan `{}`", path.display()))?;
+
+    let start = Instant::now();
+    let mut script_outputs = HashMap::new();
+    let mut compiling = HashSet::new();
+    for (index, step) in plan.steps.iter().enumerate() {
+        if step.id != index || step.deps.iter().any(|dep| *dep >= index) {
+            bail!(
+                "step {} of build plan `{}` is not listed after its dependencies",
+                step.id,
+                path.display()
+            );
+        }
+        let cmd = step.command(&plan, &script_outputs)?;
+        if compiling.insert((&step.package_name, &step.package_version)) {
+            gctx.shell().status(
+                "Compiling",
+                format!("{} v{}", step.package_name, step.package_version),
+            )?;
+        }
+        if gctx.shell().verbosity() == Verbosity::Verbose {
+            gctx.shell().status("Running", format!("`{cmd}`"))?;
+        }
+        match &step.build_script {
+            Some(script) => {
+                let output = step.run_build_script(gctx, &cmd, script)?;
+                script_outputs.insert(index, output);
+            }
+            None => step.run_rustc(gctx, &cmd)?,
+        }
+    }
+    gctx.shell().status(
+        "Finished",
+        format!(
+            "replaying build plan in {:.2}s",
+            start.elapsed().as_secs_f64()
+        ),
+    )?;
+    Ok(())
+}
+
+impl PlanStep {
+    /// The command of the step, with the output of build scripts added as in
+    /// `compiler::rustc` and `custom_build::build_work`.
+    fn command(
+        &self,
+        plan: &Plan,
+        script_outputs: &HashMap<usize, BuildOutput>,
+    ) -> CargoResult<ProcessBuilder> {
+        let mut cmd = ProcessBuilder::new(&self.program);
+        cmd.args(&self.args);
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+        if let Some(cwd) = &self.cwd {
+            cmd.cwd(cwd);
+        }
+
+        let mut uses = Vec::new();
+        for script_use in &self.build_script_outputs {
+            let Some(output) = script_outputs.get(&script_use.step) else {
+                bail!(
+                    "step {} of the build plan uses the output of step {}, \
+                     which is not a build script run before it",
+                    self.id,
+                    script_use.step
+                );
+            };
+            let links = plan.steps[script_use.step]
+                .build_script
+                .as_ref()
+                .and_then(|script| script.links.as_deref());
+            uses.push((script_use, output, links));
+        }
+
+        for (script_use, output, links) in &uses {
+            if script_use.link_search {
+                for path in &output.library_paths {
+                    cmd.arg("-L").arg(path);
+                }
+            }
+            if script_use.link_libs {
+                for name in &output.library_links {
+                    cmd.arg("-l").arg(name);
+                }
+            }
+            for (lt, arg) in &output.linker_args {
+                if script_use.link_args.contains(&lt.instruction()) {
+                    cmd.arg("-C").arg(format!("link-arg={arg}"));
+                }
+            }
+            if let (true, Some(links)) = (script_use.metadata, links) {
+                for (key, value) in &output.metadata {
+                    cmd.env(&format!("DEP_{}_{}", envify(links), envify(key)), value);
+                }
+            }
+        }
+
+        if uses.iter().any(|(script_use, ..)| script_use.dylib_path) {
+            let var = paths::dylib_path_envvar();
+            let search_path = cmd.get_env(var).unwrap_or_default();
+            let mut search_path = env::split_paths(&search_path).collect::<Vec<_>>();
+            for (script_use, output, _) in &uses {
+                if script_use.dylib_path {
+                    search_path.append(&mut filter_dynamic_search_path(
+                        output.library_paths.iter(),
+                        &plan.host_dest,
+                    ));
+                }
+            }
+            let search_path = paths::join_paths(&search_path, var)?;
+            cmd.env(var, &search_path);
+        }
+
+        for (script_use, output, _) in &uses {
+            if script_use.cfgs {
+                for cfg in &output.cfgs {
+                    cmd.arg("--cfg").arg(cfg);
+                }
+                for check_cfg in &output.check_cfgs {
+                    cmd.arg("--check-cfg").arg(check_cfg);
+                }
+            }
+            if script_use.env {
+                for (name, value) in &output.env {
+                    cmd.env(name, value);
+                }
+            }
+        }
+        Ok(cmd)
+    }
+
+    fn run_build_script(
+        &self,
+        gctx: &GlobalContext,
+        cmd: &ProcessBuilder,
+        script: &BuildScriptRun,
+    ) -> CargoResult<BuildOutput> {
+        let pkg_descr = format!("{} v{}", self.package_name, self.package_version);
+        paths::create_dir_all(&script.out_dir)?;
+        let output = cmd
+            .exec_with_output()
+            .with_context(|| format!("failed to run custom build command for `{pkg_descr}`"))?;
+
+        // Keep the output where a build would, so it's found by later builds
+        // and build plans.
+        let run_dir = script
+            .output_file
+            .parent()
+            .expect("output file is in the run directory");
+        paths::create_dir_all(run_dir)?;
+        paths::write(&script.output_file, &output.stdout)?;
+        paths::write(run_dir.join("stderr"), &output.stderr)?;
+        paths::write(
+            run_dir.join("root-output"),
+            paths::path2bytes(&script.out_dir)?,
+        )?;
+
+        let targets = script
+            .package_targets
+            .iter()
+            .map(PlanTarget::to_target)
+            .collect::<CargoResult<Vec<_>>>()?;
+        let output = BuildOutput::parse(
+            &output.stdout,
+            script.library_name.clone(),
+            &pkg_descr,
+            &script.out_dir,
+            &script.out_dir,
+            gctx.nightly_features_allowed,
+            &targets,
+            &script.rust_version,
+        )?;
+        let prefix = format!("{}@{}: ", self.package_name, self.package_version);
+        for (severity, message) in &output.log_messages {
+            match severity {
+                Severity::Error => gctx.shell().error(format!("{prefix}{message}"))?,
+                Severity::Warning => gctx.shell().warn(format!("{prefix}{message}"))?,
+            }
+        }
+        if output
+            .log_messages
+            .iter()
+            .any(|(severity, _)| *severity == Severity::Error)
+        {
+            bail!("build script logged errors");
+        }
+        Ok(output)
+    }
+
+    fn run_rustc(&self, gctx: &GlobalContext, cmd: &ProcessBuilder) -> CargoResult<()> {
+        for output in &self.outputs {
+            if let Some(parent) = output.parent() {
+                paths::create_dir_all(parent)?;
+            }
+        }
+        cmd.exec_with_streaming(
+            &mut |line| {
+                writeln!(gctx.shell().out(), "{line}")?;
+                Ok(())
+            },
+            &mut |line| on_stderr_line(gctx, line),
+            false,
+        )
+        .with_context(|| format!("could not compile `{}`", self.package_name))?;
+        for (link, path) in &self.links {
+            // Not all outputs are always produced, like `.dwp` files.
+            if path.exists() {
+                paths::link_or_copy(path, link)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prints the diagnostics rustc emits as JSON, as the plan asks for them with
+/// `--error-format=json`.
+fn on_stderr_line(gctx: &GlobalContext, line: &str) -> CargoResult<()> {
+    #[derive(Deserialize)]
+    struct CompilerMessage {
+        rendered: Option<String>,
+    }
+
+    if line.starts_with('{') {
+        if let Ok(msg) = serde_json::from_str::<CompilerMessage>(line) {
+            // Other messages, like artifact notifications, are skipped.
+            if let Some(rendered) = msg.rendered {
+                write!(gctx.shell().err(), "{rendered}")?;
+            }
+            return Ok(());
+        }
+    }
+    writeln!(gctx.shell().err(), "{line}")?;
+    Ok(())
+}
+
+impl PlanTarget {
+    /// The target the plan describes, with enough of it for parsing the
+    /// output of build scripts.
+    fn to_target(&self) -> CargoResult<Target> {
+        let edition: Edition = self.edition.parse()?;
+        let Some(src_path) = self.src_path.clone() else {
+            return Ok(Target::metabuild_target(&self.name));
+        };
+        let crate_types = self.crate_types.iter().map(CrateType::from).collect();
+        Ok(match self.kind.as_str() {
+            "bin" => Target::bin_target(&self.name, None, src_path, None, edition),
+            "example" => Target::example_target(&self.name, crate_types, src_path, None, edition),
+            "test" => Target::test_target(&self.name, src_path, None, edition),
+            "bench" => Target::bench_target(&self.name, src_path, None, edition),
+            "custom-build" => Target::custom_build_target(&self.name, src_path, edition),
+            _ => Target::lib_target(&self.name, crate_types, src_path, edition),
+        })
+    }
+}
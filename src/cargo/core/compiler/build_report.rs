@@ -0,0 +1,213 @@
+//! Facts about the last build, kept for `cargo report`.
+//!
+//! With `-Zunstable-options`, while draining the job queue, Cargo records why
+//! each unit it rebuilt was dirty, and what each build script did: how long
+//! it took, the inputs it asked to be rerun for, and the cfgs it emitted.
+//! When the build ends, the
+//! [`BuildReport`] is saved in [`BUILD_REPORT_FILE`], replacing the one of
+//! the previous build.
+//!
+//! `cargo report rebuild-reasons` and `cargo report build-scripts` display
+//! it, after loading it with [`BuildReport::load`].
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{bail, Context as _};
+use serde::{Deserialize, Serialize};
+
+use super::job_queue::JobId;
+use super::{BuildRunner, CompileMode, Freshness, Unit};
+use crate::core::{PackageIdSpec, Workspace};
+use crate::util::CargoResult;
+
+/// Current version of the on-disk format.
+const ON_DISK_VERSION: u32 = 0;
+
+/// The filename in the top-level `build-dir` directory where we store the
+/// report.
+const BUILD_REPORT_FILE: &str = ".last-build-report.json";
+
+/// The structure saved to disk describing the last build.
+#[derive(Serialize, Deserialize)]
+pub struct BuildReport {
+    /// A schema version number, to handle older cargo's from trying to read
+    /// something that they don't understand.
+    version: u32,
+    /// Whether the build succeeded.
+    pub success: bool,
+    /// The number of units that were up to date.
+    pub fresh_units: u32,
+    /// The units that were rebuilt, in the order they started.
+    pub dirty_units: Vec<DirtyUnit>,
+    /// The build scripts of the build, in the order they finished.
+    pub build_scripts: Vec<BuildScriptReport>,
+    /// When the build scripts being run started.
+    #[serde(skip)]
+    script_starts: HashMap<JobId, Instant>,
+}
+
+/// A unit that was rebuilt, and why.
+#[derive(Serialize, Deserialize)]
+pub struct DirtyUnit {
+    pub package_id: PackageIdSpec,
+    /// The package, target and mode of the unit, like
+    /// `foo v0.1.0 bin "foo" (check)`.
+    pub unit: String,
+    /// See [`DirtyReason::description`](super::DirtyReason::description).
+    pub reason: String,
+}
+
+/// What a build script did, either during the last build or, if it was
+/// fresh, the last time it ran.
+#[derive(Serialize, Deserialize)]
+pub struct BuildScriptReport {
+    pub package_id: PackageIdSpec,
+    /// The package, like `foo v0.1.0`.
+    pub package: String,
+    /// How long it took to run in seconds, `None` if it didn't run.
+    pub duration: Option<f64>,
+    /// The `cargo::rerun-if-changed` paths, relative to the package root.
+    pub rerun_if_changed: Vec<PathBuf>,
+    /// The `cargo::rerun-if-env-changed` variables.
+    pub rerun_if_env_changed: Vec<String>,
+    /// The `cargo::rustc-cfg` cfgs.
+    pub cfgs: Vec<String>,
+}
+
+impl BuildReport {
+    pub fn new() -> BuildReport {
+        BuildReport {
+            version: ON_DISK_VERSION,
+            success: false,
+            fresh_units: 0,
+            dirty_units: Vec::new(),
+            build_scripts: Vec::new(),
+            script_starts: HashMap::new(),
+        }
+    }
+
+    /// Records whether a unit about to run is fresh, and if not why.
+    pub fn unit_freshness(&mut self, unit: &Unit, fresh: &Freshness, ws_root: &Path) {
+        match fresh {
+            Freshness::Fresh => self.fresh_units += 1,
+            Freshness::Dirty(reason) => self.dirty_units.push(DirtyUnit {
+                package_id: unit.pkg.package_id().to_spec(),
+                unit: describe_unit(unit),
+                reason: reason.description(ws_root),
+            }),
+        }
+    }
+
+    /// Marks that the build script of job `id` started running.
+    pub fn build_script_started(&mut self, id: JobId) {
+        self.script_starts.insert(id, Instant::now());
+    }
+
+    /// Records the output of the build script run by `unit`, which just
+    /// finished.
+    pub fn build_script_finished(
+        &mut self,
+        id: JobId,
+        unit: &Unit,
+        build_runner: &BuildRunner<'_, '_>,
+    ) {
+        let duration = self
+            .script_starts
+            .remove(&id)
+            .map(|start| start.elapsed().as_secs_f64());
+        let metadata = build_runner.get_run_build_script_metadata(unit);
+        let outputs = build_runner.build_script_outputs.lock().unwrap();
+        let output = outputs.get(metadata);
+        self.build_scripts.push(BuildScriptReport {
+            package_id: unit.pkg.package_id().to_spec(),
+            package: format!("{} v{}", unit.pkg.name(), unit.pkg.version()),
+            duration,
+            rerun_if_changed: output
+                .map(|o| o.rerun_if_changed.clone())
+                .unwrap_or_default(),
+            rerun_if_env_changed: output
+                .map(|o| o.rerun_if_env_changed.clone())
+                .unwrap_or_default(),
+            cfgs: output.map(|o| o.cfgs.clone()).unwrap_or_default(),
+        });
+    }
+
+    /// Saves the report of the build that just ended, replacing the last one.
+    pub fn save(&mut self, ws: &Workspace<'_>, success: bool) {
+        self.success = success;
+        let on_disk = serde_json::to_vec(&self).unwrap();
+        if let Err(e) = ws
+            .build_dir()
+            .open_rw_exclusive_create(BUILD_REPORT_FILE, ws.gctx(), "Build report")
+            .and_then(|file| {
+                let mut file = file.file();
+                file.set_len(0)?;
+                file.write_all(&on_disk)?;
+                Ok(())
+            })
+        {
+            crate::display_warning_with_error(
+                "failed to write on-disk build report",
+                &e,
+                &mut ws.gctx().shell(),
+            );
+        }
+    }
+
+    /// Loads the report of the last build.
+    pub fn load(ws: &Workspace<'_>) -> CargoResult<BuildReport> {
+        let report_file =
+            match ws
+                .build_dir()
+                .open_ro_shared(BUILD_REPORT_FILE, ws.gctx(), "Build report")
+            {
+                Ok(r) => r,
+                Err(e) => {
+                    if let Some(io_err) = e.downcast_ref::<std::io::Error>() {
+                        if io_err.kind() == std::io::ErrorKind::NotFound {
+                            bail!(
+                                "no build report is available, \
+                                 run a build with `-Zunstable-options` first"
+                            );
+                        }
+                    }
+                    return Err(e);
+                }
+            };
+
+        let mut file_contents = String::new();
+        report_file
+            .file()
+            .read_to_string(&mut file_contents)
+            .context("failed to read build report")?;
+        let report: BuildReport =
+            serde_json::from_str(&file_contents).context("failed to load build report")?;
+        if report.version != ON_DISK_VERSION {
+            bail!("unable to read build report; it was saved from a future version of Cargo");
+        }
+        Ok(report)
+    }
+}
+
+fn describe_unit(unit: &Unit) -> String {
+    let mode = match unit.mode {
+        CompileMode::Test => " (test)",
+        CompileMode::Build => "",
+        CompileMode::Check { test: true } => " (check-test)",
+        CompileMode::Check { test: false } => " (check)",
+        CompileMode::Bench => " (bench)",
+        CompileMode::Doc { .. } => " (doc)",
+        CompileMode::Doctest => " (doc test)",
+        CompileMode::Docscrape => " (doc scrape)",
+        CompileMode::RunCustomBuild => " (run)",
+    };
+    format!(
+        "{} v{} {}{mode}",
+        unit.pkg.name(),
+        unit.pkg.version(),
+        unit.target.description_named()
+    )
+}
@@ -6,7 +6,7 @@ use std::sync::{Arc, Mutex};
 
 use crate::core::compiler::compilation::{self, UnitOutput};
 use crate::core::compiler::{self, artifact, Unit};
-use crate::core::PackageId;
+use crate::core::{PackageId, Workspace};
 use crate::util::cache_lock::CacheLockMode;
 use crate::util::errors::CargoResult;
 use anyhow::{bail, Context as _};
@@ -20,15 +20,21 @@ use super::fingerprint::{Checksum, Fingerprint};
 use super::job_queue::JobQueue;
 use super::layout::Layout;
 use super::lto::Lto;
+use super::shared_cache::SharedCache;
 use super::unit_graph::UnitDep;
 use super::{
-    BuildContext, Compilation, CompileKind, CompileMode, Executor, FileFlavor, RustDocFingerprint,
+    BuildContext, Compilation, CompileKind, CompileMode, CompileTarget, Executor, FileFlavor,
+    RustDocFingerprint,
 };
 
 mod compilation_files;
 use self::compilation_files::CompilationFiles;
 pub use self::compilation_files::{Metadata, OutputFile, UnitHash};
 
+mod unit_locks;
+pub use self::unit_locks::UnitLockContended;
+use self::unit_locks::UnitLocks;
+
 /// Collection of all the stuff that is needed to perform a build.
 ///
 /// Different from the [`BuildContext`], `Context` is a _mutable_ state used
@@ -87,6 +93,19 @@ pub struct BuildRunner<'a, 'gctx> {
     /// because the target has a type error. This is in an Arc<Mutex<..>>
     /// because it is continuously updated as the job progresses.
     pub failed_scrape_units: Arc<Mutex<HashSet<UnitHash>>>,
+
+    /// The cache of compiled units shared between workspaces, if enabled
+    /// with `-Zshared-build-cache`.
+    pub shared_cache: Option<Arc<SharedCache>>,
+
+    /// Whether units are locked on their own rather than the whole build
+    /// directory, see [`unit_locks`].
+    pub fine_grain_locking: bool,
+
+    /// Whether this build starts over after another build held one of its
+    /// units, see [`unit_locks`]. The output of preparing the build was
+    /// already printed by the previous attempt, so it is not printed again.
+    pub lock_retry: bool,
 }
 
 impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
@@ -126,6 +145,9 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
             lto: HashMap::new(),
             metadata_for_doc_units: HashMap::new(),
             failed_scrape_units: Arc::new(Mutex::new(HashSet::new())),
+            shared_cache: SharedCache::new(bcx.gctx)?,
+            fine_grain_locking: bcx.gctx.cli_unstable().fine_grain_locking,
+            lock_retry: false,
         })
     }
 
@@ -166,31 +188,12 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
             .gctx
             .acquire_package_cache_lock(CacheLockMode::Shared)?;
         let mut queue = JobQueue::new(self.bcx);
-        let mut plan = BuildPlan::new();
+        let mut plan = BuildPlan::new(self.bcx.build_config.build_plan_version);
         let build_plan = self.bcx.build_config.build_plan;
-        self.lto = super::lto::generate(self.bcx)?;
-        self.prepare_units()?;
-        self.prepare()?;
-        custom_build::build_map(&mut self)?;
-        self.check_collisions()?;
-        self.compute_metadata_for_doc_units();
-
-        // We need to make sure that if there were any previous docs
-        // already compiled, they were compiled with the same Rustc version that we're currently
-        // using. Otherwise we must remove the `doc/` folder and compile again forcing a rebuild.
-        //
-        // This is important because the `.js`/`.html` & `.css` files that are generated by Rustc don't have
-        // any versioning (See https://github.com/rust-lang/cargo/issues/8461).
-        // Therefore, we can end up with weird bugs and behaviours if we mix different
-        // versions of these files.
-        if self.bcx.build_config.mode.is_doc() {
-            RustDocFingerprint::check_rustdoc_fingerprint(&self)?
-        }
-
-        for unit in &self.bcx.roots {
-            let force_rebuild = self.bcx.build_config.force_rebuild;
-            super::compile(&mut self, &mut queue, &mut plan, unit, exec, force_rebuild)?;
-        }
+        self.bcx.gctx.shell().set_muted(self.lock_retry);
+        let prepared = self.prepare_jobs(&mut queue, &mut plan, exec);
+        self.bcx.gctx.shell().set_muted(false);
+        let _unit_locks = prepared?;
 
         // Now that we've got the full job queue and we've done all our
         // fingerprint analysis to determine what to run, bust all the memoized
@@ -203,7 +206,18 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
         }
 
         // Now that we've figured out everything that we're going to do, do it!
-        queue.execute(&mut self, &mut plan)?;
+        let result = queue.execute(&mut self, &mut plan);
+        // Record what was used from the shared cache even if the build
+        // failed, as the units that did finish were still needed.
+        if let Some(cache) = &self.shared_cache {
+            cache.save_last_use(self.bcx.gctx);
+            cache.report_remote_errors(self.bcx.gctx);
+        }
+        result?;
+
+        if self.bcx.gctx.cli_unstable().cargo_lints && !build_plan {
+            super::unused_deps::report(&mut self)?;
+        }
 
         if build_plan {
             plan.set_inputs(self.build_plan_inputs()?);
@@ -269,6 +283,10 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
                         }
                     }
                 }
+                for arg in &unit.profile.link_args {
+                    args.push("-C".into());
+                    args.push(format!("link-arg={arg}").into());
+                }
                 args.extend(unit.rustdocflags.iter().map(Into::into));
 
                 use super::MessageFormat;
@@ -284,7 +302,7 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
                     unit: unit.clone(),
                     args,
                     unstable_opts,
-                    linker: self.compilation.target_linker(unit.kind).clone(),
+                    linker: self.compilation.unit_linker(unit),
                     script_meta,
                     env: artifact::get_env(&self, self.unit_deps(unit))?,
                 });
@@ -307,6 +325,41 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
         Ok(self.compilation)
     }
 
+    /// Prepares the jobs of the build in `queue`, up to locking the units
+    /// that will be built.
+    fn prepare_jobs(
+        &mut self,
+        queue: &mut JobQueue<'gctx>,
+        plan: &mut BuildPlan,
+        exec: &Arc<dyn Executor>,
+    ) -> CargoResult<UnitLocks> {
+        self.lto = super::lto::generate(self.bcx)?;
+        self.prepare_units()?;
+        self.prepare()?;
+        UnitLocks::wait_for_writers(self)?;
+        custom_build::build_map(self)?;
+        self.check_collisions()?;
+        self.compute_metadata_for_doc_units();
+
+        // We need to make sure that if there were any previous docs
+        // already compiled, they were compiled with the same Rustc version that we're currently
+        // using. Otherwise we must remove the `doc/` folder and compile again forcing a rebuild.
+        //
+        // This is important because the `.js`/`.html` & `.css` files that are generated by Rustc don't have
+        // any versioning (See https://github.com/rust-lang/cargo/issues/8461).
+        // Therefore, we can end up with weird bugs and behaviours if we mix different
+        // versions of these files.
+        if self.bcx.build_config.mode.is_doc() {
+            RustDocFingerprint::check_rustdoc_fingerprint(self)?
+        }
+
+        for unit in &self.bcx.roots {
+            let force_rebuild = self.bcx.build_config.force_rebuild;
+            super::compile(self, queue, plan, unit, exec, force_rebuild)?;
+        }
+        UnitLocks::lock_dirty(self, &queue.dirty_units().collect())
+    }
+
     fn collect_tests_and_executables(&mut self, unit: &Unit) -> CargoResult<()> {
         for output in self.outputs(unit)?.iter() {
             if matches!(
@@ -353,12 +406,34 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
 
     #[tracing::instrument(skip_all)]
     pub fn prepare_units(&mut self) -> CargoResult<()> {
+        if self.fine_grain_locking {
+            self.prepare_units_with(|ws, target, dest| Layout::new_shared(ws, target, dest))
+        } else {
+            self.prepare_units_with(|ws, target, dest| Layout::new(ws, target, dest))
+        }
+    }
+
+    /// Same as [`BuildRunner::prepare_units`], but without creating or
+    /// locking the build directories. LTO is computed as well, since it
+    /// affects output file names.
+    ///
+    /// This is only for reporting how units would be built, like their
+    /// outputs, and must not be followed by a compilation.
+    pub fn prepare_units_unlocked(&mut self) -> CargoResult<()> {
+        self.lto = super::lto::generate(self.bcx)?;
+        self.prepare_units_with(|ws, target, dest| Ok(Layout::new_unlocked(ws, target, dest)))
+    }
+
+    fn prepare_units_with(
+        &mut self,
+        new_layout: impl Fn(&Workspace<'_>, Option<CompileTarget>, &str) -> CargoResult<Layout>,
+    ) -> CargoResult<()> {
         let dest = self.bcx.profiles.get_dir_name();
-        let host_layout = Layout::new(self.bcx.ws, None, &dest)?;
+        let host_layout = new_layout(self.bcx.ws, None, &dest)?;
         let mut targets = HashMap::new();
         for kind in self.bcx.all_kinds.iter() {
             if let CompileKind::Target(target) = *kind {
-                let layout = Layout::new(self.bcx.ws, Some(target), &dest)?;
+                let layout = new_layout(self.bcx.ws, Some(target), &dest)?;
                 targets.insert(target, layout);
             }
         }
@@ -371,6 +446,15 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
         self.record_units_requiring_metadata();
 
         let files = CompilationFiles::new(self, host_layout, targets);
+        for &kind in self.bcx.all_kinds.iter() {
+            let layout = files.layout(kind);
+            self.compilation
+                .root_output
+                .insert(kind, layout.dest().to_path_buf());
+            self.compilation
+                .deps_output
+                .insert(kind, layout.deps().to_path_buf());
+        }
         self.files = Some(files);
         Ok(())
     }
@@ -390,17 +474,6 @@ impl<'a, 'gctx> BuildRunner<'a, 'gctx> {
                 .prepare()
                 .context("couldn't prepare build directories")?;
         }
-
-        let files = self.files.as_ref().unwrap();
-        for &kind in self.bcx.all_kinds.iter() {
-            let layout = files.layout(kind);
-            self.compilation
-                .root_output
-                .insert(kind, layout.dest().to_path_buf());
-            self.compilation
-                .deps_output
-                .insert(kind, layout.deps().to_path_buf());
-        }
         Ok(())
     }
 
@@ -0,0 +1,193 @@
+//! Locking of individual units for `-Zfine-grain-locking`.
+//!
+//! Without it, a build locks its whole build directory, like `target/debug`,
+//! so a `cargo check` run by an editor blocks a `cargo run` from a terminal
+//! even though most of their units are different. With it, the build
+//! directory is only locked shared, and each unit is locked on its own
+//! through a `.cargo-lock` file in its fingerprint directory.
+//!
+//! ## The protocol
+//!
+//! 1. All units of the build are locked shared one after the other, before
+//!    looking at any of their previous outputs. This waits for builds writing
+//!    those units to finish. Each lock is released right away, so that a
+//!    build only keeps a lock file open for the units it rebuilds, rather
+//!    than for every unit of its graph.
+//! 2. Freshness is computed as usual.
+//! 3. The dirty units are locked exclusively, without blocking, as they will
+//!    be written. A lock is held until the end of the build, since the
+//!    outputs of a unit are read by the units depending on it.
+//!
+//! If a dirty unit can't be locked, another build is still using it. Waiting
+//! for it while holding other locks could deadlock, so the build instead
+//! releases all of its locks, waits for that unit, and starts over with
+//! [`UnitLockContended::wait`]. Two builds rebuilding the same units could
+//! keep colliding that way, so they wait a random delay before starting
+//! over, and after [`UnitLockContended::MAX_ATTEMPTS`] attempts a build locks
+//! its whole build directory instead.
+//!
+//! Units documented by rustdoc additionally share a lock on the `doc`
+//! directory, as they share its search index and other files.
+//!
+//! ## Limitations
+//!
+//! * Builds with units in common still wait for each other. Notably, build
+//!   scripts and proc-macros are the same units for `cargo check` and
+//!   `cargo build`, so the second build waits once it needs to rebuild one of
+//!   them.
+//! * Fresh units aren't locked while the build reads their outputs, so
+//!   another build rebuilding one of them after its sources changed may
+//!   replace those outputs in the middle of the build.
+//! * A build keeps a lock file open for every unit it rebuilds, so a build
+//!   from scratch of a large graph may need to raise the limit of open files
+//!   of the process, like `ulimit -n` on Unix.
+//! * Artifacts uplifted to the profile directory, like `target/debug/foo`,
+//!   may be replaced by another build of a unit with the same name, like the
+//!   same binary built with other features.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::core::compiler::Unit;
+use crate::util::context::GlobalContext;
+use crate::util::errors::CargoResult;
+use crate::util::{FileLock, Filesystem};
+
+use super::BuildRunner;
+
+/// The lock file of a unit, in its fingerprint directory.
+const UNIT_LOCK: &str = ".cargo-lock";
+/// The lock file of the `doc` directory, next to it.
+const DOC_LOCK: &str = ".cargo-doc-lock";
+
+/// The exclusive locks of the dirty units of a build.
+#[derive(Default)]
+pub struct UnitLocks {
+    /// Released when dropped, at the end of the build.
+    _locks: Vec<FileLock>,
+}
+
+impl UnitLocks {
+    /// Waits for other builds writing units of this build to finish, if
+    /// fine-grain locking is used.
+    pub fn wait_for_writers(build_runner: &BuildRunner<'_, '_>) -> CargoResult<()> {
+        if !build_runner.fine_grain_locking {
+            return Ok(());
+        }
+        let gctx = build_runner.bcx.gctx;
+        for ((dir, file), what) in lock_paths(build_runner, build_runner.bcx.unit_graph.keys()) {
+            Filesystem::new(dir).open_ro_shared_create(file, gctx, &what)?;
+        }
+        Ok(())
+    }
+
+    /// Locks the `dirty` units exclusively, as they are about to be built.
+    ///
+    /// Returns a [`UnitLockContended`] error if one of them is used by
+    /// another build.
+    pub fn lock_dirty(
+        build_runner: &BuildRunner<'_, '_>,
+        dirty: &HashSet<&Unit>,
+    ) -> CargoResult<UnitLocks> {
+        if !build_runner.fine_grain_locking {
+            return Ok(UnitLocks::default());
+        }
+        let mut locks = Vec::new();
+        for ((dir, file), what) in lock_paths(build_runner, dirty.iter().copied()) {
+            let Some(mut lock) = Filesystem::new(dir.clone()).try_open_rw_exclusive_create(file)?
+            else {
+                return Err(UnitLockContended { dir, file, what }.into());
+            };
+            lock.record_holder()?;
+            locks.push(lock);
+        }
+        Ok(UnitLocks { _locks: locks })
+    }
+}
+
+/// The lock files covering `units`, by their directory and file name, along
+/// with a description of what they lock for the message printed while
+/// waiting for them.
+fn lock_paths<'a>(
+    build_runner: &BuildRunner<'_, '_>,
+    units: impl Iterator<Item = &'a Unit>,
+) -> BTreeMap<(PathBuf, &'static str), String> {
+    let files = build_runner.files();
+    let mut paths = BTreeMap::new();
+    for unit in units {
+        paths
+            .entry((files.fingerprint_dir(unit), UNIT_LOCK))
+            .or_insert_with(|| describe(unit));
+        if unit.mode.is_doc() {
+            paths
+                .entry((files.layout(unit.kind).root().to_path_buf(), DOC_LOCK))
+                .or_insert_with(|| "doc directory".to_string());
+        }
+    }
+    paths
+}
+
+/// Describes `unit`, like ``lib of `foo v0.1.0` ``.
+fn describe(unit: &Unit) -> String {
+    let what = if unit.mode.is_run_custom_build() {
+        "build script run".to_string()
+    } else {
+        unit.target.description_named()
+    };
+    format!("{what} of `{} v{}`", unit.pkg.name(), unit.pkg.version())
+}
+
+/// A unit that is about to be built is still used by another build.
+#[derive(Debug)]
+pub struct UnitLockContended {
+    dir: PathBuf,
+    file: &'static str,
+    /// What the lock is for, see [`describe`].
+    what: String,
+}
+
+impl UnitLockContended {
+    /// The number of attempts after which a build locks its whole build
+    /// directory rather than its units.
+    pub const MAX_ATTEMPTS: u32 = 5;
+
+    /// Waits until the other build is done with the unit, if `err` is a
+    /// [`UnitLockContended`] error, and then for a random delay growing with
+    /// the number of `attempts` so far.
+    ///
+    /// Returns whether the build should be started over.
+    pub fn wait(err: &anyhow::Error, gctx: &GlobalContext, attempts: u32) -> CargoResult<bool> {
+        let Some(contended) = err.downcast_ref::<UnitLockContended>() else {
+            return Ok(false);
+        };
+        tracing::debug!("starting over after waiting for {contended}");
+        drop(
+            Filesystem::new(contended.dir.clone()).open_rw_exclusive_create(
+                contended.file,
+                gctx,
+                &contended.what,
+            )?,
+        );
+        let max_delay_ms = 50 << attempts.min(UnitLockContended::MAX_ATTEMPTS);
+        let delay_ms = rand::rng().random_range(0..max_delay_ms);
+        std::thread::sleep(Duration::from_millis(delay_ms));
+        Ok(true)
+    }
+}
+
+impl fmt::Display for UnitLockContended {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "file lock `{}` on {} is held by another build",
+            self.dir.join(self.file).display(),
+            self.what
+        )
+    }
+}
+
+impl std::error::Error for UnitLockContended {}
@@ -10,6 +10,7 @@ use cargo_util::{paths, ProcessBuilder};
 use crate::core::compiler::apply_env_config;
 use crate::core::compiler::BuildContext;
 use crate::core::compiler::{CompileKind, Unit, UnitHash};
+use crate::core::profiles::Profile;
 use crate::core::Package;
 use crate::util::{context, CargoResult, GlobalContext};
 
@@ -123,6 +124,10 @@ pub struct Compilation<'gctx> {
     /// The linker to use for each host or target.
     target_linkers: HashMap<CompileKind, Option<PathBuf>>,
 
+    /// The root of the workspace, which relative `linker` paths of profiles
+    /// are resolved against.
+    ws_root: PathBuf,
+
     /// The total number of warnings emitted by the compilation.
     pub warning_count: usize,
 }
@@ -144,6 +149,7 @@ impl<'gctx> Compilation<'gctx> {
             extra_env: HashMap::new(),
             to_doc_test: Vec::new(),
             gctx: bcx.gctx,
+            ws_root: bcx.ws.root().to_path_buf(),
             host: bcx.host_triple().to_string(),
             rustc_process,
             rustc_workspace_wrapper_process,
@@ -190,7 +196,14 @@ impl<'gctx> Compilation<'gctx> {
             rustc.display_env_vars();
         }
         let cmd = fill_rustc_tool_env(rustc, unit);
-        self.fill_env(cmd, &unit.pkg, None, unit.kind, ToolKind::Rustc)
+        self.fill_env(
+            cmd,
+            &unit.pkg,
+            Some(&unit.profile),
+            None,
+            unit.kind,
+            ToolKind::Rustc,
+        )
     }
 
     /// Returns a [`ProcessBuilder`] for running `rustdoc`.
@@ -204,7 +217,14 @@ impl<'gctx> Compilation<'gctx> {
             rustdoc.display_env_vars();
         }
         let cmd = fill_rustc_tool_env(rustdoc, unit);
-        let mut cmd = self.fill_env(cmd, &unit.pkg, script_meta, unit.kind, ToolKind::Rustdoc)?;
+        let mut cmd = self.fill_env(
+            cmd,
+            &unit.pkg,
+            Some(&unit.profile),
+            script_meta,
+            unit.kind,
+            ToolKind::Rustdoc,
+        )?;
         cmd.retry_with_argfile(true);
         unit.target.edition().cmd_edition_arg(&mut cmd);
 
@@ -221,14 +241,17 @@ impl<'gctx> Compilation<'gctx> {
     /// This is currently only used for running build scripts. If you use this
     /// for anything else, please be extra careful on how environment
     /// variables are set!
+    ///
+    /// `unit` is the `RunCustomBuild` unit of the build script.
     pub fn host_process<T: AsRef<OsStr>>(
         &self,
         cmd: T,
-        pkg: &Package,
+        unit: &Unit,
     ) -> CargoResult<ProcessBuilder> {
         self.fill_env(
             ProcessBuilder::new(cmd),
-            pkg,
+            &unit.pkg,
+            Some(&unit.profile),
             None,
             CompileKind::Host,
             ToolKind::HostProcess,
@@ -244,6 +267,26 @@ impl<'gctx> Compilation<'gctx> {
         self.target_linkers.get(&kind).and_then(|x| x.clone())
     }
 
+    /// Gets the linker for a unit, where the linker of its profile takes
+    /// precedence over the user-specified one for the platform.
+    ///
+    /// Like `target.<triple>.linker`, a linker of a profile that looks like a
+    /// path is made absolute, here relative to the workspace root, and
+    /// anything else is looked up in `PATH`.
+    pub fn unit_linker(&self, unit: &Unit) -> Option<PathBuf> {
+        match unit.profile.linker {
+            Some(linker) => {
+                let is_path = linker.contains('/') || (cfg!(windows) && linker.contains('\\'));
+                if is_path {
+                    Some(self.ws_root.join(linker.as_str()))
+                } else {
+                    Some(PathBuf::from(linker.as_str()))
+                }
+            }
+            None => self.target_linker(unit.kind),
+        }
+    }
+
     /// Returns a [`ProcessBuilder`] appropriate for running a process for the
     /// target platform. This is typically used for `cargo run` and `cargo
     /// test`.
@@ -267,7 +310,7 @@ impl<'gctx> Compilation<'gctx> {
             ProcessBuilder::new(cmd)
         };
         let tool_kind = ToolKind::TargetProcess;
-        let mut builder = self.fill_env(builder, pkg, script_meta, kind, tool_kind)?;
+        let mut builder = self.fill_env(builder, pkg, None, script_meta, kind, tool_kind)?;
 
         if let Some(client) = self.gctx.jobserver_from_env() {
             builder.inherit_jobserver(client);
@@ -281,10 +324,14 @@ impl<'gctx> Compilation<'gctx> {
     ///
     /// The package argument is also used to configure environment variables as
     /// well as the working directory of the child process.
+    ///
+    /// The `env` of `profile` is set for processes that are part of building
+    /// a unit.
     fn fill_env(
         &self,
         mut cmd: ProcessBuilder,
         pkg: &Package,
+        profile: Option<&Profile>,
         script_meta: Option<UnitHash>,
         kind: CompileKind,
         tool_kind: ToolKind,
@@ -373,6 +420,14 @@ impl<'gctx> Compilation<'gctx> {
 
         cmd.cwd(pkg.root());
 
+        if let Some(profile) = profile {
+            for (key, value) in &profile.env {
+                // Like with `[env]`, never override a value set by cargo.
+                if !cmd.get_envs().contains_key(key.as_str()) {
+                    cmd.env(key, value);
+                }
+            }
+        }
         apply_env_config(self.gctx, &mut cmd)?;
 
         Ok(cmd)
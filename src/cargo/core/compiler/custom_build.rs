@@ -207,6 +207,20 @@ impl LinkArgTarget {
             LinkArgTarget::Example => target.is_exe_example(),
         }
     }
+
+    /// The build script instruction this comes from, with the name of the
+    /// binary for [`LinkArgTarget::SingleBin`], like `rustc-link-arg-bin=foo`.
+    pub fn instruction(&self) -> String {
+        match self {
+            LinkArgTarget::All => "rustc-link-arg".to_string(),
+            LinkArgTarget::Cdylib => "rustc-cdylib-link-arg".to_string(),
+            LinkArgTarget::Bin => "rustc-link-arg-bins".to_string(),
+            LinkArgTarget::SingleBin(name) => format!("rustc-link-arg-bin={name}"),
+            LinkArgTarget::Test => "rustc-link-arg-tests".to_string(),
+            LinkArgTarget::Bench => "rustc-link-arg-benches".to_string(),
+            LinkArgTarget::Example => "rustc-link-arg-examples".to_string(),
+        }
+    }
 }
 
 /// Prepares a `Work` that executes the target as a custom build script.
@@ -291,7 +305,7 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
     // `Profiles::get_profile_run_custom_build` so that those flags get
     // carried over.
     let to_exec = to_exec.into_os_string();
-    let mut cmd = build_runner.compilation.host_process(to_exec, &unit.pkg)?;
+    let mut cmd = build_runner.compilation.host_process(to_exec, unit)?;
     let debug = unit.profile.debuginfo.is_turned_on();
     cmd.env("OUT_DIR", &script_out_dir)
         .env("CARGO_MANIFEST_DIR", unit.pkg.root())
@@ -420,6 +434,8 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
     );
     let build_scripts = build_runner.build_scripts.get(unit).cloned();
     let json_messages = bcx.build_config.emit_json();
+    let checksum_freshness = bcx.build_config.checksum_freshness;
+    let pkg_root = unit.pkg.root().to_path_buf();
     let extra_verbose = bcx.gctx.extra_verbose();
     let (prev_output, prev_script_out_dir) = prev_build_output(build_runner, unit);
     let metadata_hash = build_runner.get_run_build_script_metadata(unit);
@@ -583,7 +599,7 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
         paths::write(&output_file, &output.stdout)?;
         // This mtime shift allows Cargo to detect if a source file was
         // modified in the middle of the build.
-        paths::set_file_time_no_err(output_file, timestamp);
+        paths::set_file_time_no_err(&output_file, timestamp);
         paths::write(&err_file, &output.stderr)?;
         paths::write(&root_output_file, paths::path2bytes(&script_out_dir)?)?;
         let parsed_output = BuildOutput::parse(
@@ -597,6 +613,15 @@ fn build_work(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResul
             &msrv,
         )?;
 
+        if checksum_freshness {
+            fingerprint::write_checksums(
+                &parsed_output.rerun_if_changed,
+                &fingerprint::rerun_if_changed_checksums_loc(&output_file),
+                &pkg_root,
+                timestamp,
+            )?;
+        }
+
         if json_messages {
             emit_build_output(state, &parsed_output, script_out_dir.as_path(), id)?;
         }
@@ -1273,7 +1298,7 @@ pub fn build_map(build_runner: &mut BuildRunner<'_, '_>) -> CargoResult<()> {
 /// Also returns the directory containing the output, typically used later in
 /// processing.
 fn prev_build_output(
-    build_runner: &mut BuildRunner<'_, '_>,
+    build_runner: &BuildRunner<'_, '_>,
     unit: &Unit,
 ) -> (Option<BuildOutput>, PathBuf) {
     let script_out_dir = build_runner.files().build_script_out_dir(unit);
@@ -6,6 +6,8 @@
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::fmt;
+use std::fs;
+use std::fs::File;
 use std::io;
 use std::io::Read;
 use std::path::Path;
@@ -18,21 +20,24 @@ use anyhow::bail;
 use cargo_util::paths;
 use cargo_util::ProcessBuilder;
 use cargo_util::Sha256;
+use filetime::FileTime;
 
 use crate::core::manifest::ManifestMetadata;
 use crate::CargoResult;
 use crate::CARGO_ENV;
 
 /// The current format version of [`EncodedDepInfo`].
-const CURRENT_ENCODED_DEP_INFO_VERSION: u8 = 1;
+const CURRENT_ENCODED_DEP_INFO_VERSION: u8 = 2;
 
 /// The representation of the `.d` dep-info file generated by rustc
 #[derive(Default)]
 pub struct RustcDepInfo {
     /// The list of files that the main target in the dep-info file depends on.
     ///
-    /// The optional checksums are parsed from the special `# checksum:...` comments.
-    pub files: HashMap<PathBuf, Option<(u64, Checksum)>>,
+    /// The optional checksums are parsed from the special `# checksum:...` comments,
+    /// or computed by Cargo. They come with the file size and, when known, the
+    /// mtime the file had when it was checksummed.
+    pub files: HashMap<PathBuf, Option<(u64, Checksum, Option<FileTime>)>>,
     /// The list of environment variables we found that the rustc compilation
     /// depends on.
     ///
@@ -93,9 +98,18 @@ pub enum DepInfoPathType {
 ///   Each path is encoded as the following:
 ///
 ///   ```text
-///   +-----------+-------------+------------+---------------+-----------+-------+
-///   | path type | len of path | path bytes | cksum exists? | file size | cksum |
-///   +-----------+-------------+------------+---------------+-----------+-------+
+///   +-----------+-------------+------------+---------------+-----------+-------+-------+
+///   | path type | len of path | path bytes | cksum exists? | file size | cksum | mtime |
+///   +-----------+-------------+------------+---------------+-----------+-------+-------+
+///   ```
+///
+///   The mtime is the one the file had when it was checksummed. As long as the
+///   file still has it, the checksum is reused rather than computed again.
+///   It is encoded as the following:
+///   ```text
+///   +---------------+---------+-------------+
+///   | mtime exists? | seconds | nanoseconds |
+///   +---------------+---------+-------------+
 ///   ```
 /// * _Env var pairs_ --- Zero or more env vars the dep-info file depends on.
 ///   Each env key-value pair is encoded as the following:
@@ -108,7 +122,11 @@ pub enum DepInfoPathType {
 /// [`f4ca7390`]: https://github.com/rust-lang/cargo/commit/f4ca739073185ea5e1148ff100bb4a06d3bf721d
 #[derive(Default, Debug, PartialEq, Eq)]
 pub struct EncodedDepInfo {
-    pub files: Vec<(DepInfoPathType, PathBuf, Option<(u64, String)>)>,
+    pub files: Vec<(
+        DepInfoPathType,
+        PathBuf,
+        Option<(u64, String, Option<FileTime>)>,
+    )>,
     pub env: Vec<(String, Option<String>)>,
 }
 
@@ -138,7 +156,14 @@ impl EncodedDepInfo {
                     let checksum_string = read_bytes(bytes)
                         .map(Vec::from)
                         .and_then(|v| String::from_utf8(v).ok());
-                    file_len.zip(checksum_string)
+                    let mtime = read_bool(bytes)?
+                        .then(|| {
+                            let seconds = read_u64(bytes)? as i64;
+                            let nanos = read_u32(bytes)?;
+                            Some(FileTime::from_unix_time(seconds, nanos))
+                        })
+                        .flatten();
+                    Some((file_len?, checksum_string?, mtime))
                 })
                 .flatten();
             files.push((ty, path, checksum_info));
@@ -175,6 +200,12 @@ impl EncodedDepInfo {
             Some(u32::from_le_bytes(ret.try_into().unwrap()) as usize)
         }
 
+        fn read_u32(bytes: &mut &[u8]) -> Option<u32> {
+            let ret = bytes.get(..4)?;
+            *bytes = &bytes[4..];
+            Some(u32::from_le_bytes(ret.try_into().unwrap()))
+        }
+
         fn read_u64(bytes: &mut &[u8]) -> Option<u64> {
             let ret = bytes.get(..8)?;
             *bytes = &bytes[8..];
@@ -214,9 +245,14 @@ impl EncodedDepInfo {
             }
             write_bytes(dst, paths::path2bytes(file)?);
             write_bool(dst, checksum_info.is_some());
-            if let Some((len, checksum)) = checksum_info {
+            if let Some((len, checksum, mtime)) = checksum_info {
                 write_u64(dst, *len);
                 write_bytes(dst, checksum);
+                write_bool(dst, mtime.is_some());
+                if let Some(mtime) = mtime {
+                    write_u64(dst, mtime.unix_seconds() as u64);
+                    dst.extend(&u32::to_le_bytes(mtime.nanoseconds()));
+                }
             }
         }
 
@@ -287,6 +323,11 @@ impl EncodedDepInfo {
 ///
 /// The `env_config` argument is a set of environment variables that are
 /// defined in `[env]` table of the `config.toml`.
+///
+/// If `checksum` is true, every file gets a checksum, computed by Cargo when
+/// rustc didn't provide one, along with its mtime. `start` is when rustc was
+/// invoked; files modified since then aren't checksummed, as rustc may have
+/// read them before the change, so the crate will be rebuilt.
 pub fn translate_dep_info(
     rustc_dep_info: &Path,
     cargo_dep_info: &Path,
@@ -296,6 +337,8 @@ pub fn translate_dep_info(
     rustc_cmd: &ProcessBuilder,
     allow_package: bool,
     env_config: &Arc<HashMap<String, OsString>>,
+    checksum: bool,
+    start: FileTime,
 ) -> CargoResult<()> {
     let depinfo = parse_rustc_dep_info(rustc_dep_info)?;
 
@@ -364,23 +407,91 @@ pub fn translate_dep_info(
             // effect.
             (DepInfoPathType::BuildRootRelative, &*abs_file)
         };
-        Some((ty, path.to_owned()))
+        Some((ty, path.to_owned(), abs_file))
     };
 
     for (file, checksum_info) in depinfo.files {
-        let Some((path_type, path)) = serialize_path(file) else {
+        let Some((path_type, path, abs_file)) = serialize_path(file) else {
             continue;
         };
+        let checksum_info = if checksum {
+            checksum_file(&abs_file, checksum_info, start)
+        } else {
+            checksum_info
+        };
         on_disk_info.files.push((
             path_type,
             path,
-            checksum_info.map(|(len, checksum)| (len, checksum.to_string())),
+            checksum_info.map(|(len, checksum, mtime)| (len, checksum.to_string(), mtime)),
         ));
     }
     paths::write(cargo_dep_info, on_disk_info.serialize()?)?;
     Ok(())
 }
 
+/// Writes the checksums of `files`, the `rerun-if-changed` paths of a build
+/// script that was run at `start`, to `dest` in the [`EncodedDepInfo`]
+/// format, so they can be read back with [`parse_dep_info`].
+///
+/// Directories and files modified since `start` are left without a checksum.
+pub fn write_checksums(
+    files: &[PathBuf],
+    dest: &Path,
+    pkg_root: &Path,
+    start: FileTime,
+) -> CargoResult<()> {
+    let mut on_disk_info = EncodedDepInfo::default();
+    for file in files {
+        let abs_file = pkg_root.join(file);
+        let (ty, path) = match abs_file.strip_prefix(pkg_root) {
+            Ok(stripped) => (DepInfoPathType::PackageRootRelative, stripped.to_owned()),
+            // An absolute path, which joining to the build root leaves as is.
+            Err(_) => (DepInfoPathType::BuildRootRelative, abs_file.clone()),
+        };
+        let checksum_info = if abs_file.is_dir() {
+            None
+        } else {
+            checksum_file(&abs_file, None, start)
+        };
+        on_disk_info.files.push((
+            ty,
+            path,
+            checksum_info.map(|(len, checksum, mtime)| (len, checksum.to_string(), mtime)),
+        ));
+    }
+    paths::write(dest, on_disk_info.serialize()?)?;
+    Ok(())
+}
+
+/// Completes the checksum information of `path`, computing the checksum if
+/// `checksum_info` doesn't have one yet.
+///
+/// Returns `None` if the file was modified since `start`, since its content
+/// may not be what the compilation or build script run that started then
+/// used, or if it can't be read. Files with an mtime in the future, which
+/// can't have been modified in the meantime, are still checksummed.
+fn checksum_file(
+    path: &Path,
+    checksum_info: Option<(u64, Checksum, Option<FileTime>)>,
+    start: FileTime,
+) -> Option<(u64, Checksum, Option<FileTime>)> {
+    let metadata = fs::metadata(path).ok()?;
+    let mtime = FileTime::from_last_modification_time(&metadata);
+    // Like the mtime comparisons of fingerprints, an equal mtime is considered
+    // as a change, to account for filesystems with coarse timestamps.
+    if start <= mtime && mtime <= FileTime::now() {
+        return None;
+    }
+    match checksum_info {
+        Some((len, checksum, _)) => Some((len, checksum, Some(mtime))),
+        None => {
+            let file = File::open(path).ok()?;
+            let checksum = Checksum::compute(ChecksumAlgo::Blake3, file).ok()?;
+            Some((metadata.len(), checksum, Some(mtime)))
+        }
+    }
+}
+
 /// Parse the `.d` dep-info file generated by rustc.
 pub fn parse_rustc_dep_info(rustc_dep_info: &Path) -> CargoResult<RustcDepInfo> {
     let contents = paths::read(rustc_dep_info)?;
@@ -431,7 +542,7 @@ pub fn parse_rustc_dep_info(rustc_dep_info: &Path) -> CargoResult<RustcDepInfo>
                 continue;
             };
 
-            ret.files.insert(path, Some((file_len, checksum)));
+            ret.files.insert(path, Some((file_len, checksum, None)));
         }
     }
     return Ok(ret);
@@ -488,8 +599,10 @@ pub fn parse_dep_info(
         .extend(info.files.into_iter().map(|(ty, path, checksum_info)| {
             (
                 make_absolute_path(ty, pkg_root, build_root, path),
-                checksum_info.and_then(|(file_len, checksum)| {
-                    Checksum::from_str(&checksum).ok().map(|c| (file_len, c))
+                checksum_info.and_then(|(file_len, checksum, mtime)| {
+                    Checksum::from_str(&checksum)
+                        .ok()
+                        .map(|c| (file_len, c, mtime))
                 }),
             )
         }));
@@ -676,7 +789,11 @@ mod encoded_dep_info {
 
     #[track_caller]
     fn gen_test(checksum: bool) {
-        let checksum = checksum.then_some((768, "c01efc669f09508b55eced32d3c88702578a7c3e".into()));
+        let checksum = checksum.then_some((
+            768,
+            "c01efc669f09508b55eced32d3c88702578a7c3e".into(),
+            Some(FileTime::from_unix_time(1_700_000_000, 123_456_789)),
+        ));
         let lib_rs = (
             DepInfoPathType::BuildRootRelative,
             PathBuf::from("src/lib.rs"),
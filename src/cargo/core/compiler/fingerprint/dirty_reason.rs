@@ -146,103 +146,83 @@ impl DirtyReason {
     }
 
     pub fn present_to(&self, s: &mut Shell, unit: &Unit, root: &Path) -> CargoResult<()> {
+        s.dirty_because(unit, self.description(root))?;
+        if let DirtyReason::LocalLengthsChanged = self {
+            s.note(
+                "this could happen because of added/removed `cargo::rerun-if` instructions in the build script",
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Describes why the unit is dirty, with paths relative to `root`.
+    pub fn description(&self, root: &Path) -> String {
         match self {
-            DirtyReason::RustcChanged => s.dirty_because(unit, "the toolchain changed"),
-            DirtyReason::FeaturesChanged { .. } => {
-                s.dirty_because(unit, "the list of features changed")
-            }
+            DirtyReason::RustcChanged => "the toolchain changed".to_string(),
+            DirtyReason::FeaturesChanged { .. } => "the list of features changed".to_string(),
             DirtyReason::DeclaredFeaturesChanged { .. } => {
-                s.dirty_because(unit, "the list of declared features changed")
+                "the list of declared features changed".to_string()
             }
             DirtyReason::TargetConfigurationChanged => {
-                s.dirty_because(unit, "the target configuration changed")
-            }
-            DirtyReason::PathToSourceChanged => {
-                s.dirty_because(unit, "the path to the source changed")
+                "the target configuration changed".to_string()
             }
+            DirtyReason::PathToSourceChanged => "the path to the source changed".to_string(),
             DirtyReason::ProfileConfigurationChanged => {
-                s.dirty_because(unit, "the profile configuration changed")
-            }
-            DirtyReason::RustflagsChanged { .. } => s.dirty_because(unit, "the rustflags changed"),
-            DirtyReason::ConfigSettingsChanged => {
-                s.dirty_because(unit, "the config settings changed")
-            }
-            DirtyReason::CompileKindChanged => {
-                s.dirty_because(unit, "the rustc compile kind changed")
-            }
-            DirtyReason::LocalLengthsChanged => {
-                s.dirty_because(unit, "the local lengths changed")?;
-                s.note(
-                    "this could happen because of added/removed `cargo::rerun-if` instructions in the build script",
-                )?;
-
-                Ok(())
+                "the profile configuration changed".to_string()
             }
+            DirtyReason::RustflagsChanged { .. } => "the rustflags changed".to_string(),
+            DirtyReason::ConfigSettingsChanged => "the config settings changed".to_string(),
+            DirtyReason::CompileKindChanged => "the rustc compile kind changed".to_string(),
+            DirtyReason::LocalLengthsChanged => "the local lengths changed".to_string(),
             DirtyReason::PrecalculatedComponentsChanged { .. } => {
-                s.dirty_because(unit, "the precalculated components changed")
+                "the precalculated components changed".to_string()
             }
             DirtyReason::ChecksumUseChanged { old } => {
                 if *old {
-                    s.dirty_because(
-                        unit,
-                        "the prior compilation used checksum freshness and this one does not",
-                    )
+                    "the prior compilation used checksum freshness and this one does not"
+                        .to_string()
                 } else {
-                    s.dirty_because(unit, "checksum freshness requested, prior compilation did not use checksum freshness")
+                    "checksum freshness requested, prior compilation did not use checksum freshness"
+                        .to_string()
                 }
             }
             DirtyReason::DepInfoOutputChanged { .. } => {
-                s.dirty_because(unit, "the dependency info output changed")
+                "the dependency info output changed".to_string()
             }
             DirtyReason::RerunIfChangedOutputFileChanged { .. } => {
-                s.dirty_because(unit, "rerun-if-changed output file path changed")
+                "rerun-if-changed output file path changed".to_string()
             }
             DirtyReason::RerunIfChangedOutputPathsChanged { .. } => {
-                s.dirty_because(unit, "the rerun-if-changed instructions changed")
-            }
-            DirtyReason::EnvVarsChanged { .. } => {
-                s.dirty_because(unit, "the environment variables changed")
+                "the rerun-if-changed instructions changed".to_string()
             }
+            DirtyReason::EnvVarsChanged { .. } => "the environment variables changed".to_string(),
             DirtyReason::EnvVarChanged { name, .. } => {
-                s.dirty_because(unit, format_args!("the env variable {name} changed"))
+                format!("the env variable {name} changed")
             }
             DirtyReason::LocalFingerprintTypeChanged { .. } => {
-                s.dirty_because(unit, "the local fingerprint type changed")
+                "the local fingerprint type changed".to_string()
+            }
+            DirtyReason::NumberOfDependenciesChanged { old, new } => {
+                format!("number of dependencies changed ({old} => {new})")
             }
-            DirtyReason::NumberOfDependenciesChanged { old, new } => s.dirty_because(
-                unit,
-                format_args!("number of dependencies changed ({old} => {new})",),
-            ),
-            DirtyReason::UnitDependencyNameChanged { old, new } => s.dirty_because(
-                unit,
-                format_args!("name of dependency changed ({old} => {new})"),
-            ),
-            DirtyReason::UnitDependencyInfoChanged { .. } => {
-                s.dirty_because(unit, "dependency info changed")
+            DirtyReason::UnitDependencyNameChanged { old, new } => {
+                format!("name of dependency changed ({old} => {new})")
             }
+            DirtyReason::UnitDependencyInfoChanged { .. } => "dependency info changed".to_string(),
             DirtyReason::FsStatusOutdated(status) => match status {
-                FsStatus::Stale => s.dirty_because(unit, "stale, unknown reason"),
+                FsStatus::Stale => "stale, unknown reason".to_string(),
                 FsStatus::StaleItem(item) => match item {
                     StaleItem::MissingFile(missing_file) => {
                         let file = missing_file.strip_prefix(root).unwrap_or(&missing_file);
-                        s.dirty_because(
-                            unit,
-                            format_args!("the file `{}` is missing", file.display()),
-                        )
+                        format!("the file `{}` is missing", file.display())
                     }
                     StaleItem::UnableToReadFile(file) => {
                         let file = file.strip_prefix(root).unwrap_or(&file);
-                        s.dirty_because(
-                            unit,
-                            format_args!("the file `{}` could not be read", file.display()),
-                        )
+                        format!("the file `{}` could not be read", file.display())
                     }
                     StaleItem::FailedToReadMetadata(file) => {
                         let file = file.strip_prefix(root).unwrap_or(&file);
-                        s.dirty_because(
-                            unit,
-                            format_args!("couldn't read metadata for file `{}`", file.display()),
-                        )
+                        format!("couldn't read metadata for file `{}`", file.display())
                     }
                     StaleItem::ChangedFile {
                         stale,
@@ -252,10 +232,7 @@ impl DirtyReason {
                     } => {
                         let file = stale.strip_prefix(root).unwrap_or(&stale);
                         let after = Self::after(*reference_mtime, *stale_mtime, "last build");
-                        s.dirty_because(
-                            unit,
-                            format_args!("the file `{}` has changed ({after})", file.display()),
-                        )
+                        format!("the file `{}` has changed ({after})", file.display())
                     }
                     StaleItem::ChangedChecksum {
                         source,
@@ -263,12 +240,9 @@ impl DirtyReason {
                         new_checksum,
                     } => {
                         let file = source.strip_prefix(root).unwrap_or(&source);
-                        s.dirty_because(
-                            unit,
-                            format_args!(
-                                "the file `{}` has changed (checksum didn't match, {stored_checksum} != {new_checksum})",
-                                file.display(),
-                            ),
+                        format!(
+                            "the file `{}` has changed (checksum didn't match, {stored_checksum} != {new_checksum})",
+                            file.display(),
                         )
                     }
                     StaleItem::FileSizeChanged {
@@ -277,25 +251,18 @@ impl DirtyReason {
                         new_size,
                     } => {
                         let file = path.strip_prefix(root).unwrap_or(&path);
-                        s.dirty_because(
-                            unit,
-                            format_args!(
-                                "file size changed ({old_size} != {new_size}) for `{}`",
-                                file.display()
-                            ),
+                        format!(
+                            "file size changed ({old_size} != {new_size}) for `{}`",
+                            file.display()
                         )
                     }
                     StaleItem::MissingChecksum(path) => {
                         let file = path.strip_prefix(root).unwrap_or(&path);
-                        s.dirty_because(
-                            unit,
-                            format_args!("the checksum for file `{}` is missing", file.display()),
-                        )
+                        format!("the checksum for file `{}` is missing", file.display())
+                    }
+                    StaleItem::ChangedEnv { var, .. } => {
+                        format!("the environment variable {var} changed")
                     }
-                    StaleItem::ChangedEnv { var, .. } => s.dirty_because(
-                        unit,
-                        format_args!("the environment variable {var} changed"),
-                    ),
                 },
                 FsStatus::StaleDependency {
                     name,
@@ -304,13 +271,10 @@ impl DirtyReason {
                     ..
                 } => {
                     let after = Self::after(*max_mtime, *dep_mtime, "last build");
-                    s.dirty_because(
-                        unit,
-                        format_args!("the dependency {name} was rebuilt ({after})"),
-                    )
+                    format!("the dependency {name} was rebuilt ({after})")
                 }
                 FsStatus::StaleDepFingerprint { name } => {
-                    s.dirty_because(unit, format_args!("the dependency {name} was rebuilt"))
+                    format!("the dependency {name} was rebuilt")
                 }
                 FsStatus::UpToDate { .. } => {
                     unreachable!()
@@ -318,10 +282,10 @@ impl DirtyReason {
             },
             DirtyReason::NothingObvious => {
                 // See comment in fingerprint compare method.
-                s.dirty_because(unit, "the fingerprint comparison turned up nothing obvious")
+                "the fingerprint comparison turned up nothing obvious".to_string()
             }
-            DirtyReason::Forced => s.dirty_because(unit, "forced"),
-            DirtyReason::FreshBuild => s.dirty_because(unit, "fresh build"),
+            DirtyReason::Forced => "forced".to_string(),
+            DirtyReason::FreshBuild => "fresh build".to_string(),
         }
     }
 }
@@ -33,11 +33,14 @@
 //!      details. If any input files are missing, or are newer than the
 //!      dep-info, then the unit is dirty.
 //!
-//!  - Alternatively if you're using the unstable feature `checksum-freshness`
-//!    mtimes are ignored entirely in favor of comparing first the file size, and
-//!    then the checksum with a known prior value emitted by rustc. Only nightly
-//!    rustc will emit the needed metadata at the time of writing. This is dependent
-//!    on the unstable feature `-Z checksum-hash-algorithm`.
+//!  - Alternatively if checksum freshness is enabled, with `build.checksum-freshness`
+//!    or the unstable feature `checksum-freshness`, source file mtimes are
+//!    ignored in favor of comparing first the file size, and then the checksum
+//!    with a known prior value. Nightly rustc emits checksums with the unstable
+//!    feature `-Z checksum-hash-algorithm`, otherwise Cargo computes them itself
+//!    after the compilation. The mtime a file had when it was checksummed is
+//!    recorded as well, and as long as the file keeps both its size and mtime,
+//!    it isn't hashed again.
 //!
 //! Note: Fingerprinting is not a perfect solution. Filesystem mtime tracking
 //! is notoriously imprecise and problematic. Only a small part of the
@@ -269,7 +272,9 @@
 //! In the new-style, each `rerun-if` directive is translated to the
 //! corresponding [`LocalFingerprint`] variant. The [`RerunIfChanged`] variant
 //! compares the mtime of the given filenames against the mtime of the
-//! "output" file.
+//! "output" file. With checksum freshness, it instead compares them with the
+//! checksums Cargo wrote next to the "output" file after the build script ran
+//! (see [`rerun_if_changed_checksums_loc`]).
 //!
 //! Similar to normal units, the build script "output" file mtime is rewound
 //! to the time just before the build script is executed to handle mid-build
@@ -406,6 +411,7 @@ use super::{BuildContext, BuildRunner, FileFlavor, Job, Unit, Work};
 pub use self::dep_info::parse_dep_info;
 pub use self::dep_info::parse_rustc_dep_info;
 pub use self::dep_info::translate_dep_info;
+pub use self::dep_info::write_checksums;
 pub use self::dep_info::Checksum;
 pub use self::dirty_reason::DirtyReason;
 
@@ -758,9 +764,14 @@ enum LocalFingerprint {
     ///
     /// This is considered up-to-date if all of the `paths` are older than
     /// `output`, otherwise we need to recompile.
+    ///
+    /// If the `checksum` bool is true then the files in `paths` are instead
+    /// compared with the checksums stored next to `output`. Directories are
+    /// still compared by mtime.
     RerunIfChanged {
         output: PathBuf,
         paths: Vec<PathBuf>,
+        checksum: bool,
     },
 
     /// This represents a single `rerun-if-env-changed` annotation printed by a
@@ -907,7 +918,11 @@ impl LocalFingerprint {
 
             // We need to verify that no paths listed in `paths` are newer than
             // the `output` path itself, or the last time the build script ran.
-            LocalFingerprint::RerunIfChanged { output, paths } => Ok(find_stale_file(
+            LocalFingerprint::RerunIfChanged {
+                output,
+                paths,
+                checksum: false,
+            } => Ok(find_stale_file(
                 mtime_cache,
                 checksum_cache,
                 &build_root.join(output),
@@ -915,6 +930,31 @@ impl LocalFingerprint {
                 false,
             )),
 
+            // Or, with checksums, that they still have the checksums they had
+            // when the build script ran.
+            LocalFingerprint::RerunIfChanged {
+                output,
+                paths,
+                checksum: true,
+            } => {
+                let output = build_root.join(output);
+                let checksums = rerun_if_changed_checksums_loc(&output);
+                let Some(info) = parse_dep_info(pkg_root, build_root, &checksums)? else {
+                    return Ok(Some(StaleItem::MissingFile(checksums)));
+                };
+                Ok(find_stale_file(
+                    mtime_cache,
+                    checksum_cache,
+                    &output,
+                    paths.iter().map(|p| {
+                        let path = pkg_root.join(p);
+                        let checksum = info.files.get(&path).copied().flatten();
+                        (path, checksum)
+                    }),
+                    true,
+                ))
+            }
+
             // These have no dependencies on the filesystem, and their values
             // are included natively in the `Fingerprint` hash so nothing
             // tocheck for here.
@@ -1053,10 +1093,12 @@ impl Fingerprint {
                     LocalFingerprint::RerunIfChanged {
                         output: aout,
                         paths: apaths,
+                        checksum: checksum_a,
                     },
                     LocalFingerprint::RerunIfChanged {
                         output: bout,
                         paths: bpaths,
+                        checksum: checksum_b,
                     },
                 ) => {
                     if aout != bout {
@@ -1071,6 +1113,9 @@ impl Fingerprint {
                             new: apaths.clone(),
                         };
                     }
+                    if checksum_a != checksum_b {
+                        return DirtyReason::ChecksumUseChanged { old: *checksum_b };
+                    }
                 }
                 (
                     LocalFingerprint::RerunIfEnvChanged {
@@ -1508,7 +1553,7 @@ fn calculate_normal(
         let dep_info = dep_info.strip_prefix(&build_root).unwrap().to_path_buf();
         vec![LocalFingerprint::CheckDepInfo {
             dep_info,
-            checksum: build_runner.bcx.gctx.cli_unstable().checksum_freshness,
+            checksum: build_runner.bcx.build_config.checksum_freshness,
         }]
     };
 
@@ -1555,6 +1600,11 @@ fn calculate_normal(
     if let Some(allow_features) = &build_runner.bcx.gctx.cli_unstable().allow_features {
         allow_features.hash(&mut config);
     }
+    // Units built without the loaded crates in their dep-info can't be
+    // checked for unused dependencies.
+    if super::unused_deps::is_tracked(build_runner.bcx, unit) {
+        "binary-dep-depinfo".hash(&mut config);
+    }
     let compile_kind = unit.kind.fingerprint_hash();
     let mut declared_features = unit.pkg.summary().features().keys().collect::<Vec<_>>();
     declared_features.sort(); // to avoid useless rebuild if the user orders it's features
@@ -1721,6 +1771,7 @@ fn build_script_local_fingerprints(
     let pkg_root = unit.pkg.root().to_path_buf();
     let build_dir = build_root(build_runner);
     let env_config = Arc::clone(build_runner.bcx.gctx.env_config()?);
+    let checksum = build_runner.bcx.build_config.checksum_freshness;
     let calculate =
         move |deps: &BuildDeps, pkg_fingerprint: Option<&dyn Fn() -> CargoResult<String>>| {
             if deps.rerun_if_changed.is_empty() && deps.rerun_if_env_changed.is_empty() {
@@ -1755,6 +1806,7 @@ fn build_script_local_fingerprints(
                 &build_dir,
                 &pkg_root,
                 &env_config,
+                checksum,
             )))
         };
 
@@ -1791,6 +1843,7 @@ fn local_fingerprints_deps(
     build_root: &Path,
     pkg_root: &Path,
     env_config: &Arc<HashMap<String, OsString>>,
+    checksum: bool,
 ) -> Vec<LocalFingerprint> {
     debug!("new local fingerprints deps {:?}", pkg_root);
     let mut local = Vec::new();
@@ -1809,7 +1862,11 @@ fn local_fingerprints_deps(
             .iter()
             .map(|p| p.strip_prefix(pkg_root).unwrap_or(p).to_path_buf())
             .collect();
-        local.push(LocalFingerprint::RerunIfChanged { output, paths });
+        local.push(LocalFingerprint::RerunIfChanged {
+            output,
+            paths,
+            checksum,
+        });
     }
 
     local.extend(
@@ -1841,6 +1898,15 @@ fn write_fingerprint(loc: &Path, fingerprint: &Fingerprint) -> CargoResult<()> {
     Ok(())
 }
 
+/// Returns the hash of the [`Fingerprint`] of `unit` as known before the
+/// build starts.
+///
+/// Unlike the hash written to the fingerprint file, this doesn't yet reflect
+/// what build scripts of the unit's package emit when they run.
+pub fn prepare_time_hash(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<u64> {
+    Ok(calculate(build_runner, unit)?.hash_u64())
+}
+
 /// Prepare for work when a package starts to build
 pub fn prepare_init(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<()> {
     let new1 = build_runner.files().fingerprint_dir(unit);
@@ -1859,6 +1925,12 @@ pub fn dep_info_loc(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> Path
     build_runner.files().fingerprint_file_path(unit, "dep-")
 }
 
+/// Returns the location of the checksums of the `rerun-if-changed` paths of
+/// the build script whose output is at `script_output`.
+pub fn rerun_if_changed_checksums_loc(script_output: &Path) -> PathBuf {
+    script_output.with_file_name("rerun-if-changed-checksums")
+}
+
 /// Returns an absolute path that build directory.
 /// All paths are rewritten to be relative to this.
 fn build_root(build_runner: &BuildRunner<'_, '_>) -> PathBuf {
@@ -1956,7 +2028,7 @@ fn find_stale_file<I, P>(
     use_checksums: bool,
 ) -> Option<StaleItem>
 where
-    I: IntoIterator<Item = (P, Option<(u64, Checksum)>)>,
+    I: IntoIterator<Item = (P, Option<(u64, Checksum, Option<FileTime>)>)>,
     P: AsRef<Path>,
 {
     let Ok(reference_mtime) = paths::mtime(reference) else {
@@ -1984,18 +2056,25 @@ where
                 continue;
             }
         }
-        if use_checksums {
-            let Some((file_len, prior_checksum)) = prior_checksum else {
+        // Directories, only found in `rerun-if-changed` paths, have no
+        // checksum and are compared by mtime even when using checksums.
+        let prior_checksum = match prior_checksum {
+            Some(_) if use_checksums => prior_checksum,
+            None if use_checksums && !path.is_dir() => {
                 return Some(StaleItem::MissingChecksum(path.to_path_buf()));
-            };
+            }
+            _ => None,
+        };
+        if let Some((file_len, prior_checksum, prior_mtime)) = prior_checksum {
             let path_buf = path.to_path_buf();
 
             let path_checksum = match checksum_cache.entry(path_buf) {
                 Entry::Occupied(o) => *o.get(),
                 Entry::Vacant(v) => {
-                    let Ok(current_file_len) = fs::metadata(&path).map(|m| m.len()) else {
+                    let Ok(metadata) = fs::metadata(&path) else {
                         return Some(StaleItem::FailedToReadMetadata(path.to_path_buf()));
                     };
+                    let current_file_len = metadata.len();
                     if current_file_len != file_len {
                         return Some(StaleItem::FileSizeChanged {
                             path: path.to_path_buf(),
@@ -2003,6 +2082,13 @@ where
                             old_size: file_len,
                         });
                     }
+                    // A file with the size and mtime it had when it was
+                    // checksummed is assumed not to have changed since.
+                    let mtime = FileTime::from_last_modification_time(&metadata);
+                    if prior_mtime == Some(mtime) {
+                        v.insert(prior_checksum);
+                        continue;
+                    }
                     let Ok(file) = File::open(path) else {
                         return Some(StaleItem::MissingFile(path.to_path_buf()));
                     };
@@ -0,0 +1,271 @@
+//! A jobserver shared by all Cargo processes of the machine.
+//!
+//! Every build creates its own jobserver with `-j` tokens, so two builds
+//! running at the same time, like `cargo check` from an editor and
+//! `cargo test` from a terminal, together run twice as many jobs as there
+//! are CPUs. With `-Zglobal-jobserver`, builds additionally join a pool of
+//! tokens kept in a named fifo under `$CARGO_HOME/jobserver`, with one token
+//! per CPU, and each job after the first needs a token from both.
+//!
+//! ## The pool
+//!
+//! A token is a byte in the fifo. Acquiring one reads a byte, and releasing
+//! it writes the byte back.
+//!
+//! The content of a fifo is lost once no process has it open anymore, so
+//! the pool is filled again by whichever build finds itself alone. Members
+//! hold a shared lock on the `lock` file next to the fifo for as long as
+//! they are building. A build that manages to take an exclusive lock knows
+//! there is no other member, discards whatever may be left in the fifo, and
+//! writes a fresh set of tokens before switching to a shared lock. If a
+//! Cargo process is killed while holding tokens, those tokens are lost until
+//! the pool gets refilled this way.
+//!
+//! Switching from the exclusive to the shared lock can't be done atomically,
+//! so joining the pool is serialized by an exclusive lock on the `join` file.
+//! Otherwise another build could take the exclusive lock in between, find
+//! itself alone, and add a second set of tokens.
+//!
+//! ## Limitations
+//!
+//! * Only jobs that Cargo spawns are counted. Each build still runs its first
+//!   job without a token, and a rustc using several threads shares tokens of
+//!   its own build's jobserver only.
+//! * A build that inherited a jobserver from its environment, like a Cargo
+//!   run by a build script or by `make`, doesn't join the pool since its
+//!   parent already coordinates it.
+//! * Named fifos are only available on Unix. Elsewhere the flag is ignored
+//!   with a warning.
+
+use std::sync::Arc;
+
+use crate::util::context::GlobalContext;
+use crate::CargoResult;
+
+pub use imp::{GlobalJobserver, GlobalToken};
+
+/// Joins the pool if `-Zglobal-jobserver` is used and this build doesn't
+/// inherit a jobserver from its environment.
+pub fn join(gctx: &GlobalContext) -> CargoResult<Option<Arc<GlobalJobserver>>> {
+    if !gctx.cli_unstable().global_jobserver || gctx.jobserver_from_env().is_some() {
+        return Ok(None);
+    }
+    GlobalJobserver::join(gctx)
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::ffi::CString;
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, Read, Write};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread::available_parallelism;
+
+    use anyhow::Context as _;
+    use tracing::debug;
+
+    use crate::util::context::GlobalContext;
+    use crate::util::FileLock;
+    use crate::CargoResult;
+
+    /// The directory in `$CARGO_HOME` holding the pool.
+    const DIR: &str = "jobserver";
+    /// The named fifo holding the tokens.
+    const FIFO: &str = "fifo";
+    /// The file whose lock tells who is using the pool.
+    const LOCK: &str = "lock";
+    /// The file whose lock serializes joining the pool.
+    const JOIN_LOCK: &str = "join";
+    /// The byte making up a token.
+    const TOKEN: u8 = b'|';
+    /// How long to wait for a token before checking whether the build is over,
+    /// in milliseconds.
+    const POLL_INTERVAL: i32 = 100;
+
+    /// A member of the machine-wide pool of tokens.
+    pub struct GlobalJobserver {
+        fifo: Arc<File>,
+        /// Set once the build is over, to stop waiting for tokens.
+        stopped: AtomicBool,
+        _lock: FileLock,
+    }
+
+    /// A token of the pool, given back when dropped.
+    pub struct GlobalToken {
+        fifo: Arc<File>,
+        byte: u8,
+    }
+
+    impl GlobalJobserver {
+        pub(super) fn join(gctx: &GlobalContext) -> CargoResult<Option<Arc<GlobalJobserver>>> {
+            let dir = gctx.home().join(DIR);
+            dir.create_dir()?;
+            let fifo_path = dir.as_path_unlocked().join(FIFO);
+            create_fifo(&fifo_path)
+                .with_context(|| format!("failed to create fifo `{}`", fifo_path.display()))?;
+            // Opening for both reading and writing never blocks, unlike
+            // opening for only one of them while nobody has the other end.
+            let fifo = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&fifo_path)
+                .with_context(|| format!("failed to open fifo `{}`", fifo_path.display()))?;
+            set_nonblocking(&fifo)?;
+
+            // Nobody else can take the exclusive lock while this is held, so
+            // nobody can fill the fifo between releasing it and taking the
+            // shared one.
+            let joining = dir.open_rw_exclusive_create(JOIN_LOCK, gctx, "global jobserver")?;
+            if let Some(exclusive) = dir.try_open_rw_exclusive_create(LOCK)? {
+                let tokens = available_parallelism().map_or(1, |n| n.get());
+                fill(&fifo, tokens)
+                    .with_context(|| format!("failed to fill fifo `{}`", fifo_path.display()))?;
+                debug!("filled the global jobserver with {tokens} tokens");
+                drop(exclusive);
+            }
+            let lock = dir.open_ro_shared_create(LOCK, gctx, "global jobserver")?;
+            drop(joining);
+
+            Ok(Some(Arc::new(GlobalJobserver {
+                fifo: Arc::new(fifo),
+                stopped: AtomicBool::new(false),
+                _lock: lock,
+            })))
+        }
+
+        /// Waits for a token of the pool.
+        ///
+        /// Returns an error if the build ended before one was available.
+        pub fn acquire(&self) -> io::Result<GlobalToken> {
+            let mut buf = [0];
+            loop {
+                match (&*self.fifo).read(&mut buf) {
+                    Ok(1) => {
+                        return Ok(GlobalToken {
+                            fifo: Arc::clone(&self.fifo),
+                            byte: buf[0],
+                        })
+                    }
+                    Ok(_) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "the global jobserver fifo was closed",
+                        ))
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        if self.stopped.load(Ordering::SeqCst) {
+                            return Err(io::Error::new(
+                                io::ErrorKind::Other,
+                                "the build ended while waiting for a token",
+                            ));
+                        }
+                        wait_readable(&self.fifo)?;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+
+        /// Stops waiting for tokens, as the build is over.
+        pub fn stop(&self) {
+            self.stopped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    impl Drop for GlobalToken {
+        fn drop(&mut self) {
+            if let Err(e) = (&*self.fifo).write_all(&[self.byte]) {
+                debug!("failed to release global jobserver token: {e}");
+            }
+        }
+    }
+
+    fn create_fifo(path: &Path) -> io::Result<()> {
+        let path = CString::new(path.as_os_str().as_bytes())?;
+        if unsafe { libc::mkfifo(path.as_ptr(), 0o600) } == 0 {
+            return Ok(());
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() == io::ErrorKind::AlreadyExists {
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+
+    fn set_nonblocking(fifo: &File) -> io::Result<()> {
+        let fd = fifo.as_raw_fd();
+        unsafe {
+            let flags = libc::fcntl(fd, libc::F_GETFL);
+            if flags == -1 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces whatever is left in the fifo with `tokens` tokens.
+    fn fill(fifo: &File, tokens: usize) -> io::Result<()> {
+        let mut buf = [0; 64];
+        loop {
+            match (&*fifo).read(&mut buf) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        (&*fifo).write_all(&vec![TOKEN; tokens])
+    }
+
+    /// Waits until the fifo has something to read, or for [`POLL_INTERVAL`].
+    fn wait_readable(fifo: &File) -> io::Result<()> {
+        let mut fd = libc::pollfd {
+            fd: fifo.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        if unsafe { libc::poll(&mut fd, 1, POLL_INTERVAL) } == -1 {
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::io;
+    use std::sync::Arc;
+
+    use crate::util::context::GlobalContext;
+    use crate::CargoResult;
+
+    pub enum GlobalJobserver {}
+
+    pub enum GlobalToken {}
+
+    impl GlobalJobserver {
+        pub(super) fn join(gctx: &GlobalContext) -> CargoResult<Option<Arc<GlobalJobserver>>> {
+            gctx.shell()
+                .warn("`-Zglobal-jobserver` is only supported on Unix, ignoring it")?;
+            Ok(None)
+        }
+
+        pub fn acquire(&self) -> io::Result<GlobalToken> {
+            match *self {}
+        }
+
+        pub fn stop(&self) {
+            match *self {}
+        }
+    }
+}
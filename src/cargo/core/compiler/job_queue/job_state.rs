@@ -72,6 +72,12 @@ impl<'a, 'gctx> JobState<'a, 'gctx> {
         self.messages.push(Message::Run(self.id, cmd.to_string()));
     }
 
+    /// Signals that the outputs of this job were restored from the shared
+    /// build cache instead of running a command.
+    pub fn restored(&self, description: String) {
+        self.messages.push(Message::Restored(self.id, description));
+    }
+
     pub fn build_plan(
         &self,
         module_name: String,
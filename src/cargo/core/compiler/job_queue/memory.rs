@@ -0,0 +1,158 @@
+//! Limits on the memory used by the jobs of a build.
+//!
+//! Jobserver tokens bound the number of jobs running at the same time, but
+//! not how much memory they use. Some jobs need a lot more memory than
+//! others, notably linking a binary with LTO, so a build with many of them
+//! can run out of memory even with a sensible `-j`.
+//!
+//! With `-Zmemory-limit` and `build.memory-limit`, each job is given an
+//! estimate of the memory it needs, and a job only starts if the estimates
+//! of the running jobs and its own stay under the limit. With
+//! `build.sample-free-memory`, a job also only starts if the system reports
+//! enough available memory for it.
+//!
+//! The estimates are rough guesses depending on what kind of work a job does,
+//! see [`estimate`]. A job always starts when no other job is running, so
+//! that a build makes progress even if the limit is lower than the estimate
+//! of a job. Jobs wait in priority order, so a job that doesn't fit keeps
+//! the ones after it from starting, instead of being starved by smaller ones.
+
+use std::collections::HashMap;
+
+use tracing::debug;
+
+use super::{Job, JobId};
+use crate::core::compiler::lto::Lto;
+use crate::core::compiler::{BuildRunner, CrateType, Unit};
+use crate::util::CargoResult;
+
+const MIB: u64 = 1024 * 1024;
+const GIB: u64 = 1024 * MIB;
+
+/// Estimate for a job running rustc or rustdoc without linking.
+const COMPILE: u64 = 512 * MIB;
+/// Estimate for a job running a build script.
+const BUILD_SCRIPT: u64 = 512 * MIB;
+/// Estimate for a job running rustc and linking, like for binaries, tests
+/// and dynamic libraries.
+const LINK: u64 = GIB;
+/// Estimate for a job running rustc with thin LTO.
+const THIN_LTO: u64 = 2 * GIB;
+/// Estimate for a job running rustc with fat LTO.
+const FAT_LTO: u64 = 4 * GIB;
+
+/// Tracks the memory expected to be used by the running jobs.
+pub struct MemoryBudget {
+    /// The value of `build.memory-limit`.
+    limit: Option<u64>,
+    /// The value of `build.sample-free-memory`.
+    sample_free: bool,
+    /// The estimates of the running jobs, except fresh ones.
+    in_use: HashMap<JobId, u64>,
+}
+
+impl MemoryBudget {
+    pub fn new(build_runner: &BuildRunner<'_, '_>) -> CargoResult<MemoryBudget> {
+        let build_config = build_runner.bcx.build_config;
+        let mut sample_free = build_config.sample_free_memory;
+        if sample_free && available_memory().is_none() {
+            build_runner.bcx.gctx.shell().warn(
+                "`build.sample-free-memory` is not supported on this platform, ignoring it",
+            )?;
+            sample_free = false;
+        }
+        Ok(MemoryBudget {
+            limit: build_config.memory_limit,
+            sample_free,
+            in_use: HashMap::new(),
+        })
+    }
+
+    /// Whether memory is taken into account at all.
+    pub fn is_enabled(&self) -> bool {
+        self.limit.is_some() || self.sample_free
+    }
+
+    /// Returns whether a job needing `memory` can start now.
+    pub fn fits(&self, memory: u64) -> bool {
+        if !self.is_enabled() || memory == 0 || self.in_use.is_empty() {
+            return true;
+        }
+        let in_use = self.in_use.values().sum::<u64>();
+        if let Some(limit) = self.limit {
+            if in_use + memory > limit {
+                debug!("waiting for memory, {in_use} of {limit} bytes in use, {memory} needed");
+                return false;
+            }
+        }
+        if self.sample_free {
+            if let Some(available) = available_memory() {
+                if memory > available {
+                    debug!("waiting for memory, {available} bytes available, {memory} needed");
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Records that the job `id` started, with an estimate of `memory`.
+    pub fn start(&mut self, id: JobId, memory: u64) {
+        if memory > 0 {
+            self.in_use.insert(id, memory);
+        }
+    }
+
+    /// Records that the job `id` finished.
+    pub fn finish(&mut self, id: JobId) {
+        self.in_use.remove(&id);
+    }
+}
+
+/// Estimates the memory needed by the `job` of `unit`.
+///
+/// Fresh jobs don't spawn any process, and need nothing.
+pub fn estimate(build_runner: &BuildRunner<'_, '_>, unit: &Unit, job: &Job) -> u64 {
+    if job.freshness().is_fresh() {
+        return 0;
+    }
+    if unit.mode.is_run_custom_build() {
+        return BUILD_SCRIPT;
+    }
+    if !unit.mode.generates_executable() {
+        // Checking, documenting, and running doc tests.
+        return COMPILE;
+    }
+    match build_runner.lto.get(unit) {
+        Some(Lto::Run(Some(kind))) if kind == "thin" => return THIN_LTO,
+        Some(Lto::Run(_)) => return FAT_LTO,
+        _ => {}
+    }
+    let links = unit.mode.is_rustc_test()
+        || unit
+            .target
+            .rustc_crate_types()
+            .iter()
+            .any(CrateType::requires_upstream_objects);
+    if links {
+        LINK
+    } else {
+        COMPILE
+    }
+}
+
+/// Returns the memory available on the system, in bytes, if known.
+#[cfg(target_os = "linux")]
+fn available_memory() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?;
+    let kib = line.trim().strip_suffix("kB")?.trim().parse::<u64>().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn available_memory() -> Option<u64> {
+    None
+}
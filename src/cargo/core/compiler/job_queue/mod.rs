@@ -70,6 +70,10 @@
 //! data and perform a PGO-like optimization to prioritize jobs, making a build
 //! fully pipelined.
 //!
+//! Besides tokens, jobs may also wait for memory when `build.memory-limit`
+//! or `build.sample-free-memory` is configured with `-Zmemory-limit`. See the
+//! [`memory`] module.
+//!
 //! ## Message queue
 //!
 //! Each spawned thread running a process uses the message queue [`Queue`] to
@@ -110,8 +114,10 @@
 //! [`push`]: Queue::push
 //! [`push_bounded`]: Queue::push_bounded
 
+mod global_jobserver;
 mod job;
 mod job_state;
+mod memory;
 
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
@@ -128,9 +134,12 @@ use jobserver::{Acquired, HelperThread};
 use semver::Version;
 use tracing::{debug, trace};
 
+use self::global_jobserver::GlobalToken;
 pub use self::job::Freshness::{self, Dirty, Fresh};
 pub use self::job::{Job, Work};
 pub use self::job_state::JobState;
+use self::memory::MemoryBudget;
+use super::build_report::BuildReport;
 use super::build_runner::OutputFile;
 use super::custom_build::Severity;
 use super::timings::Timings;
@@ -191,17 +200,26 @@ struct DrainState<'gctx> {
     /// Note that the length of this may be zero, but we will still spawn work,
     /// as we share the implicit token given to this Cargo process with a
     /// single rustc process.
-    tokens: Vec<Acquired>,
+    ///
+    /// With `-Zglobal-jobserver`, each token comes with a token of the
+    /// machine-wide jobserver.
+    tokens: Vec<(Acquired, Option<GlobalToken>)>,
 
     /// The list of jobs that we have not yet started executing, but have
     /// retrieved from the `queue`. We eagerly pull jobs off the main queue to
     /// allow us to request jobserver tokens pretty early.
     pending_queue: Vec<(Unit, Job, usize)>,
+    /// The memory expected to be used by running jobs, limiting which ones
+    /// may start with `build.memory-limit`.
+    memory: MemoryBudget,
     print: DiagnosticPrinter<'gctx>,
 
     /// How many jobs we've finished
     finished: usize,
     per_package_future_incompat_reports: Vec<FutureIncompatReportPackage>,
+    /// Facts about the build, saved for `cargo report` with
+    /// `-Zunstable-options`.
+    build_report: Option<BuildReport>,
 }
 
 /// Count of warnings, used to print a summary after the job succeeds
@@ -344,6 +362,7 @@ enum Artifact {
 
 enum Message {
     Run(JobId, String),
+    Restored(JobId, String),
     BuildPlanMsg(String, ProcessBuilder, Arc<Vec<OutputFile>>),
     Stdout(String),
     Stderr(String),
@@ -371,7 +390,7 @@ enum Message {
     },
 
     FixDiagnostic(diagnostic_server::Message),
-    Token(io::Result<Acquired>),
+    Token(io::Result<(Acquired, Option<GlobalToken>)>),
     Finish(JobId, Artifact, CargoResult<()>),
     FutureIncompatReport(JobId, Vec<FutureBreakageItem>),
 }
@@ -385,6 +404,14 @@ impl<'gctx> JobQueue<'gctx> {
         }
     }
 
+    /// Units whose jobs aren't fresh, and thus will write their outputs.
+    pub fn dirty_units(&self) -> impl Iterator<Item = &Unit> {
+        self.queue
+            .iter()
+            .filter(|(_, job)| job.freshness().is_dirty())
+            .map(|(unit, _)| unit)
+    }
+
     pub fn enqueue(
         &mut self,
         build_runner: &BuildRunner<'_, 'gctx>,
@@ -499,20 +526,34 @@ impl<'gctx> JobQueue<'gctx> {
             timings: self.timings,
             tokens: Vec::new(),
             pending_queue: Vec::new(),
+            memory: MemoryBudget::new(build_runner)?,
             print: DiagnosticPrinter::new(
                 build_runner.bcx.gctx,
                 &build_runner.bcx.rustc().workspace_wrapper,
             ),
             finished: 0,
             per_package_future_incompat_reports: Vec::new(),
+            build_report: build_runner
+                .bcx
+                .gctx
+                .cli_unstable()
+                .unstable_options
+                .then(BuildReport::new),
         };
 
-        // Create a helper thread for acquiring jobserver tokens
+        // Create a helper thread for acquiring jobserver tokens, which also
+        // waits for a token of the machine-wide jobserver if there is one.
+        let global_jobserver = global_jobserver::join(build_runner.bcx.gctx)?;
         let messages = state.messages.clone();
+        let global = global_jobserver.clone();
         let helper = build_runner
             .jobserver
             .clone()
             .into_helper_thread(move |token| {
+                let token = token.and_then(|token| {
+                    let global_token = global.as_ref().map(|g| g.acquire()).transpose()?;
+                    Ok((token, global_token))
+                });
                 messages.push(Message::Token(token));
             })
             .context("failed to create helper thread for jobserver management")?;
@@ -532,7 +573,13 @@ impl<'gctx> JobQueue<'gctx> {
             .map(move |srv| srv.start(move |msg| messages.push(Message::FixDiagnostic(msg))));
 
         thread::scope(move |scope| {
-            match state.drain_the_queue(build_runner, plan, scope, &helper) {
+            let error = state.drain_the_queue(build_runner, plan, scope, &helper);
+            // The helper thread may be waiting for a global token, which it
+            // must stop doing before it can be joined when dropped.
+            if let Some(global) = &global_jobserver {
+                global.stop();
+            }
+            match error {
                 Some(err) => Err(err),
                 None => Ok(()),
             }
@@ -573,6 +620,11 @@ impl<'gctx> DrainState<'gctx> {
         // remove items from its end to schedule the highest priority items
         // sooner.
         while self.has_extra_tokens() && !self.pending_queue.is_empty() {
+            let (unit, job, _) = self.pending_queue.last().unwrap();
+            let memory = memory::estimate(build_runner, unit, job);
+            if !self.memory.fits(memory) {
+                break;
+            }
             let (unit, job, _) = self.pending_queue.pop().unwrap();
             *self.counts.get_mut(&unit.pkg.package_id()).unwrap() -= 1;
             if !build_runner.bcx.build_config.build_plan {
@@ -586,8 +638,12 @@ impl<'gctx> DrainState<'gctx> {
                     &unit,
                     job.freshness(),
                 )?;
+                if let Some(build_report) = &mut self.build_report {
+                    build_report.unit_freshness(&unit, job.freshness(), build_runner.bcx.ws.root());
+                }
             }
-            self.run(&unit, job, build_runner, scope);
+            let id = self.run(&unit, job, build_runner, scope);
+            self.memory.start(id, memory);
         }
 
         Ok(())
@@ -611,6 +667,19 @@ impl<'gctx> DrainState<'gctx> {
                     .gctx
                     .shell()
                     .verbose(|c| c.status("Running", &cmd))?;
+                if self.active[&id].mode.is_run_custom_build() {
+                    if let Some(build_report) = &mut self.build_report {
+                        build_report.build_script_started(id);
+                    }
+                }
+                self.timings.unit_start(id, self.active[&id].clone());
+            }
+            Message::Restored(id, description) => {
+                build_runner
+                    .bcx
+                    .gctx
+                    .shell()
+                    .verbose(|c| c.status("Restored", &description))?;
                 self.timings.unit_start(id, self.active[&id].clone());
             }
             Message::BuildPlanMsg(module_name, cmd, filenames) => {
@@ -668,6 +737,7 @@ impl<'gctx> DrainState<'gctx> {
                             id,
                             &build_runner.bcx.rustc().workspace_wrapper,
                         );
+                        self.memory.finish(id);
                         self.active.remove(&id).unwrap()
                     }
                     // ... otherwise if it hasn't finished we leave it
@@ -725,7 +795,11 @@ impl<'gctx> DrainState<'gctx> {
         if events.is_empty() {
             loop {
                 self.tick_progress();
-                self.tokens.truncate(self.active.len() - 1);
+                // Jobs waiting for memory keep the tokens they were given, as
+                // those won't be requested again.
+                if !self.memory.is_enabled() || self.pending_queue.is_empty() {
+                    self.tokens.truncate(self.active.len() - 1);
+                }
                 match self.messages.pop(Duration::from_millis(500)) {
                     Some(message) => {
                         events.push(message);
@@ -815,6 +889,11 @@ impl<'gctx> DrainState<'gctx> {
         if let Err(e) = self.timings.finished(build_runner, &errors.to_error()) {
             self.handle_error(&mut build_runner.bcx.gctx.shell(), &mut errors, e);
         }
+        if let Some(build_report) = &mut self.build_report {
+            if !build_runner.bcx.build_config.build_plan {
+                build_report.save(build_runner.bcx.ws, errors.count == 0);
+            }
+        }
         if build_runner.bcx.build_config.emit_json() {
             let mut shell = build_runner.bcx.gctx.shell();
             let msg = machine_message::BuildFinished {
@@ -937,7 +1016,7 @@ impl<'gctx> DrainState<'gctx> {
         job: Job,
         build_runner: &BuildRunner<'_, '_>,
         scope: &'s Scope<'s, '_>,
-    ) {
+    ) -> JobId {
         let id = JobId(self.next_id);
         self.next_id = self.next_id.checked_add(1).unwrap();
 
@@ -966,6 +1045,7 @@ impl<'gctx> DrainState<'gctx> {
                 scope.spawn(move || doit(None));
             }
         }
+        id
     }
 
     fn emit_log_messages(
@@ -1116,6 +1196,9 @@ impl<'gctx> DrainState<'gctx> {
                 build_runner,
                 unit.show_warnings(build_runner.bcx.gctx),
             )?;
+            if let Some(build_report) = &mut self.build_report {
+                build_report.build_script_finished(id, unit, build_runner);
+            }
         }
         let unlocked = self.queue.finish(unit, &artifact);
         match artifact {
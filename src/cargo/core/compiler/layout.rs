@@ -19,7 +19,8 @@
 //!     debug/  # or release/
 //!
 //!         # File used to lock the directory to prevent multiple cargo processes
-//!         # from using it at the same time.
+//!         # from using it at the same time. Holds the PID of the process
+//!         # locking it exclusively.
 //!         .cargo-lock
 //!
 //!         # Hidden directory that holds all of the fingerprint files for all
@@ -28,6 +29,9 @@
 //!             # Each package is in a separate directory.
 //!             # Note that different target kinds have different filename prefixes.
 //!             $pkgname-$META/
+//!                 # File used to lock the unit with `-Zfine-grain-locking`,
+//!                 # in which case the directory lock above is shared.
+//!                 .cargo-lock
 //!                 # Set of source filenames for this package.
 //!                 dep-lib-$targetname
 //!                 # Timestamp when this package was last built.
@@ -91,6 +95,10 @@
 //!     # Output from rustdoc
 //!     doc/
 //!
+//!     # File used to lock `doc/` with `-Zfine-grain-locking`, as rustdoc
+//!     # output of different units is shared there.
+//!     .cargo-doc-lock
+//!
 //!     # Used by `cargo package` and `cargo publish` to build a `.crate` file.
 //!     package/
 //!
@@ -103,7 +111,7 @@
 
 use crate::core::compiler::CompileTarget;
 use crate::core::Workspace;
-use crate::util::{CargoResult, FileLock};
+use crate::util::{CargoResult, FileLock, Filesystem};
 use cargo_util::paths;
 use std::path::{Path, PathBuf};
 
@@ -136,7 +144,9 @@ pub struct Layout {
     tmp: PathBuf,
     /// The lockfile for a build (`.cargo-lock`). Will be unlocked when this
     /// struct is `drop`ped.
-    _lock: FileLock,
+    ///
+    /// Will be `None` for a layout created with [`Layout::new_unlocked`].
+    _lock: Option<FileLock>,
     /// Same as `_lock` but for the build directory.
     ///
     /// Will be `None` when the build-dir and target-dir are the same path as we cannot
@@ -156,6 +166,32 @@ impl Layout {
         target: Option<CompileTarget>,
         dest: &str,
     ) -> CargoResult<Layout> {
+        Layout::new_with_lock(ws, target, dest, false)
+    }
+
+    /// Same as [`Layout::new`], but only takes a shared lock on the build
+    /// directory.
+    ///
+    /// This is for `-Zfine-grain-locking`, where the caller is responsible for
+    /// locking each unit it builds, so that builds of different units in the
+    /// same directory can run at the same time. Commands taking an exclusive
+    /// lock with [`Layout::new`], like `cargo clean`, still wait for all of
+    /// them.
+    pub fn new_shared(
+        ws: &Workspace<'_>,
+        target: Option<CompileTarget>,
+        dest: &str,
+    ) -> CargoResult<Layout> {
+        Layout::new_with_lock(ws, target, dest, true)
+    }
+
+    fn new_with_lock(
+        ws: &Workspace<'_>,
+        target: Option<CompileTarget>,
+        dest: &str,
+        shared: bool,
+    ) -> CargoResult<Layout> {
+        let mut layout = Layout::new_unlocked(ws, target, dest);
         let mut root = ws.target_dir();
         let mut build_root = ws.build_dir();
         if let Some(target) = target {
@@ -177,28 +213,34 @@ impl Layout {
         // actual destination (sub)subdirectory.
         paths::create_dir_all(dest.as_path_unlocked())?;
 
-        // For now we don't do any more finer-grained locking on the artifact
-        // directory, so just lock the entire thing for the duration of this
-        // compile.
-        let lock = dest.open_rw_exclusive_create(".cargo-lock", ws.gctx(), "build directory")?;
+        // Unless the caller locks units on its own, lock the entire directory
+        // for the duration of this compile.
+        layout._lock = Some(lock_dir(&dest, ws, shared)?);
 
-        let build_lock = if root != build_root {
-            Some(build_dest.open_rw_exclusive_create(
-                ".cargo-lock",
-                ws.gctx(),
-                "build directory",
-            )?)
-        } else {
-            None
-        };
-        let root = root.into_path_unlocked();
-        let build_root = build_root.into_path_unlocked();
-        let dest = dest.into_path_unlocked();
-        let build_dest = build_dest.as_path_unlocked();
+        if root != build_root {
+            layout._build_lock = Some(lock_dir(&build_dest, ws, shared)?);
+        }
+        Ok(layout)
+    }
+
+    /// Calculate the paths for build output, without creating or locking any
+    /// directory.
+    ///
+    /// This is for reporting where outputs would go, the layout must not be
+    /// used to build.
+    pub fn new_unlocked(ws: &Workspace<'_>, target: Option<CompileTarget>, dest: &str) -> Layout {
+        let mut root = ws.target_dir().into_path_unlocked();
+        let mut build_root = ws.build_dir().into_path_unlocked();
+        if let Some(target) = target {
+            root.push(target.short_name());
+            build_root.push(target.short_name());
+        }
+        let build_dest = build_root.join(dest);
+        let dest = root.join(dest);
         let deps = build_dest.join("deps");
         let artifact = deps.join("artifact");
 
-        Ok(Layout {
+        Layout {
             deps,
             build: build_dest.join("build"),
             artifact,
@@ -210,9 +252,9 @@ impl Layout {
             tmp: build_root.join("tmp"),
             root,
             dest,
-            _lock: lock,
-            _build_lock: build_lock,
-        })
+            _lock: None,
+            _build_lock: None,
+        }
     }
 
     /// Makes sure all directories stored in the Layout exist on the filesystem.
@@ -273,3 +315,17 @@ impl Layout {
         Ok(&self.tmp)
     }
 }
+
+/// Locks a build directory through its `.cargo-lock` file.
+///
+/// An exclusive lock records the PID of this process, so that others waiting
+/// for it can tell who holds it.
+fn lock_dir(dir: &Filesystem, ws: &Workspace<'_>, shared: bool) -> CargoResult<FileLock> {
+    if shared {
+        dir.open_ro_shared_create(".cargo-lock", ws.gctx(), "build directory")
+    } else {
+        let mut lock = dir.open_rw_exclusive_create(".cargo-lock", ws.gctx(), "build directory")?;
+        lock.record_holder()?;
+        Ok(lock)
+    }
+}
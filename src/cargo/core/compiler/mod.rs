@@ -35,6 +35,7 @@ pub mod artifact;
 mod build_config;
 pub(crate) mod build_context;
 mod build_plan;
+pub mod build_report;
 pub(crate) mod build_runner;
 mod compilation;
 mod compile_kind;
@@ -49,11 +50,13 @@ mod lto;
 mod output_depinfo;
 mod output_sbom;
 pub mod rustdoc;
+pub(crate) mod shared_cache;
 pub mod standard_lib;
 mod timings;
 mod unit;
 pub mod unit_dependencies;
 pub mod unit_graph;
+mod unused_deps;
 
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
@@ -73,8 +76,9 @@ pub use self::build_config::{BuildConfig, CompileMode, MessageFormat, TimingOutp
 pub use self::build_context::{
     BuildContext, FileFlavor, FileType, RustDocFingerprint, RustcTargetData, TargetInfo,
 };
+pub use self::build_plan::replay_build_plan;
 use self::build_plan::BuildPlan;
-pub use self::build_runner::{BuildRunner, Metadata, UnitHash};
+pub use self::build_runner::{BuildRunner, Metadata, UnitHash, UnitLockContended};
 pub use self::compilation::{Compilation, Doctest, UnitOutput};
 pub use self::compile_kind::{CompileKind, CompileKindFallback, CompileTarget};
 pub use self::crate_type::CrateType;
@@ -87,6 +91,7 @@ pub(crate) use self::layout::Layout;
 pub use self::lto::Lto;
 use self::output_depinfo::output_depinfo;
 use self::output_sbom::build_sbom;
+pub use self::timings::{TimingReport, UnitTimingReport};
 use self::unit_graph::UnitDep;
 use crate::core::compiler::future_incompat::FutureIncompatReport;
 pub use crate::core::compiler::unit::{Unit, UnitInterner};
@@ -199,22 +204,14 @@ fn compile<'gctx>(
             let work = if unit.mode.is_doc() || unit.mode.is_doc_scrape() {
                 rustdoc(build_runner, unit)?
             } else {
-                rustc(build_runner, unit, exec)?
+                let work = rustc(build_runner, unit, exec)?;
+                shared_cache::prepare(build_runner, unit, work)?
             };
             work.then(link_targets(build_runner, unit, false)?)
         } else {
             // We always replay the output cache,
             // since it might contain future-incompat-report messages
-            let show_diagnostics = unit.show_warnings(bcx.gctx)
-                && build_runner.bcx.gctx.warning_handling()? != WarningHandling::Allow;
-            let work = replay_output_cache(
-                unit.pkg.package_id(),
-                PathBuf::from(unit.pkg.manifest_path()),
-                &unit.target,
-                build_runner.files().message_cache_path(unit),
-                build_runner.bcx.build_config.message_format,
-                show_diagnostics,
-            );
+            let work = replay_unit_output_cache(build_runner, unit)?;
             // Need to link targets on both the dirty and fresh.
             work.then(link_targets(build_runner, unit, true)?)
         });
@@ -235,6 +232,23 @@ fn compile<'gctx>(
     Ok(())
 }
 
+/// Creates a unit of work that replays the cached compiler output of `unit`.
+fn replay_unit_output_cache(
+    build_runner: &mut BuildRunner<'_, '_>,
+    unit: &Unit,
+) -> CargoResult<Work> {
+    let show_diagnostics = unit.show_warnings(build_runner.bcx.gctx)
+        && build_runner.bcx.gctx.warning_handling()? != WarningHandling::Allow;
+    Ok(replay_output_cache(
+        unit.pkg.package_id(),
+        PathBuf::from(unit.pkg.manifest_path()),
+        &unit.target,
+        build_runner.files().message_cache_path(unit),
+        build_runner.bcx.build_config.message_format,
+        show_diagnostics,
+    ))
+}
+
 /// Generates the warning message used when fallible doc-scrape units fail,
 /// either for rustdoc or rustc.
 fn make_failed_scrape_diagnostic(
@@ -308,6 +322,7 @@ fn rustc(
     let fingerprint_dir = build_runner.files().fingerprint_dir(unit);
     let script_metadata = build_runner.find_build_script_metadata(unit);
     let is_local = unit.is_local();
+    let checksum_freshness = build_runner.bcx.build_config.checksum_freshness;
     let artifact = unit.artifact;
     let sbom_files = build_runner.sbom_output_files(unit)?;
     let sbom = build_sbom(build_runner, unit)?;
@@ -470,6 +485,8 @@ fn rustc(
                 // Do not track source files in the fingerprint for registry dependencies.
                 is_local,
                 &env_config,
+                checksum_freshness,
+                timestamp,
             )
             .with_context(|| {
                 internal(format!(
@@ -711,7 +728,7 @@ fn prepare_rustc(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> CargoResult
         base.args(args);
     }
     base.args(&unit.rustflags);
-    if gctx.cli_unstable().binary_dep_depinfo {
+    if gctx.cli_unstable().binary_dep_depinfo || unused_deps::is_tracked(build_runner.bcx, unit) {
         base.arg("-Z").arg("binary-dep-depinfo");
     }
     if build_runner.bcx.gctx.cli_unstable().checksum_freshness {
@@ -817,6 +834,23 @@ fn prepare_rustdoc(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> CargoResu
     Ok(rustdoc)
 }
 
+/// The command compiling `unit` with `rustc`, or `rustdoc` for documentation,
+/// as far as it is known before building, like in a build plan.
+///
+/// Returns `None` for units that aren't compiled, like running a build script.
+pub fn unit_command(
+    build_runner: &BuildRunner<'_, '_>,
+    unit: &Unit,
+) -> CargoResult<Option<ProcessBuilder>> {
+    if unit.mode.is_run_custom_build() || unit.mode.is_doc_test() {
+        Ok(None)
+    } else if unit.mode.is_doc() || unit.mode.is_doc_scrape() {
+        prepare_rustdoc(build_runner, unit).map(Some)
+    } else {
+        prepare_rustc(build_runner, unit).map(Some)
+    }
+}
+
 /// Creates a unit of work invoking `rustdoc` for documenting the `unit`.
 fn rustdoc(build_runner: &mut BuildRunner<'_, '_>, unit: &Unit) -> CargoResult<Work> {
     let mut rustdoc = prepare_rustdoc(build_runner, unit)?;
@@ -1026,6 +1060,7 @@ fn build_base_args(
         strip,
         rustflags: profile_rustflags,
         trim_paths,
+        link_args,
         ..
     } = unit.profile.clone();
     let test = unit.mode.is_any_test();
@@ -1111,6 +1146,12 @@ fn build_base_args(
     cmd.args(unit.pkg.manifest().lint_rustflags());
     cmd.args(&profile_rustflags);
 
+    if !unit.mode.is_check() && unit.requires_upstream_objects() {
+        for arg in &link_args {
+            cmd.arg("-C").arg(format!("link-arg={arg}"));
+        }
+    }
+
     // `-C overflow-checks` is implied by the setting of `-C debug-assertions`,
     // so we only need to provide `-C overflow-checks` if it differs from
     // the value of `-C debug-assertions` we would provide.
@@ -1185,7 +1226,7 @@ fn build_base_args(
         "linker=",
         build_runner
             .compilation
-            .target_linker(unit.kind)
+            .unit_linker(unit)
             .as_ref()
             .map(|s| s.as_ref()),
     );
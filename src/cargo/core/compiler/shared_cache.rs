@@ -0,0 +1,492 @@
+//! An opt-in cache of compiled units shared between workspaces.
+//!
+//! Every workspace normally compiles its dependencies from scratch into its
+//! own build directory, even when another workspace on the same machine has
+//! already built the exact same unit. With `-Zshared-build-cache`, Cargo
+//! stores the outputs of units of non-path packages in
+//! `$CARGO_HOME/build-cache` after compiling them, and restores them from
+//! there instead of invoking `rustc` when an identical unit is needed again.
+//!
+//! ## Cache keys
+//!
+//! An entry is keyed by a hash of:
+//!
+//! * The unit's [`Fingerprint`] as known before the build starts. This covers
+//!   the compiler, the profile, features, flags, and the fingerprints of all
+//!   dependencies.
+//! * [`Metadata::unit_id`], since output filenames embed it and must be the
+//!   same in the build directory the entry gets restored to.
+//! * The `cfg`s, environment variables and link directives emitted by the
+//!   package's build script, which are only known once the script has run.
+//!   Library search paths are left out since they point into the build
+//!   directory.
+//!
+//! Unless a build script emits absolute paths in one of the hashed
+//! directives, nothing in the key depends on the location of the build
+//! directory, so the same unit built in two workspaces gets the same key.
+//!
+//! ## Entries
+//!
+//! An entry is a directory named after its key. It holds the unit's output
+//! files, its fingerprint dep-info file, and its cached compiler messages,
+//! each at its path relative to the build directory. Restoring an entry
+//! copies those files back to the same relative paths.
+//!
+//! Entries are written to a temporary directory first and then renamed into
+//! place, so concurrent builds never see a partially written entry.
+//!
+//! The cache is strictly an optimization. A failure to restore an entry falls
+//! back to compiling the unit, and a failure to store one is only logged.
+//!
+//! ## Remote caches
+//!
+//! A team can share entries through a remote cache configured with
+//! `build-cache.remote`. When an entry is missing locally, it is fetched from
+//! `GET <remote>/v1/entries/<key>`, and after compiling a unit its new entry
+//! is uploaded with `PUT <remote>/v1/entries/<key>`. In both directions the
+//! body is a gzip-compressed tarball of the entry directory, and a fetched
+//! entry is unpacked into the local cache before being restored from there.
+//!
+//! The remote is only consulted when the network is allowed. Like the local
+//! cache it never fails the build: a `404 Not Found` or any other error while
+//! fetching is a miss, and a failed upload leaves the remote as it was. If
+//! anything went wrong with the remote, a warning with the first error is
+//! printed once the build finishes.
+//!
+//! All units share a single connection to the remote, so transfers happen one
+//! at a time.
+//!
+//! ## Garbage collection
+//!
+//! Entries restored or stored during a build are recorded in the
+//! [`GlobalCacheTracker`] when the build finishes, which allows
+//! `cargo clean gc` to evict entries by age or by total size.
+//!
+//! [`Fingerprint`]: super::fingerprint::Fingerprint
+//! [`Metadata::unit_id`]: super::Metadata::unit_id
+//! [`GlobalCacheTracker`]: crate::core::global_cache_tracker::GlobalCacheTracker
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{bail, Context as _};
+use cargo_util::paths;
+use curl::easy::{Easy, List};
+use filetime::FileTime;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tracing::debug;
+use url::Url;
+
+use super::{fingerprint, BuildOutput, BuildRunner, CompileMode, Unit, UnitHash, Work};
+use crate::core::global_cache_tracker::BuildCacheEntry;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::errors::CargoResult;
+use crate::util::interning::InternedString;
+use crate::util::network::http::http_handle;
+use crate::util::{self, StableHasher};
+use crate::GlobalContext;
+
+/// Handle to the shared build cache, see the [module-level docs](self).
+pub struct SharedCache {
+    /// Path to `$CARGO_HOME/build-cache`.
+    root: PathBuf,
+    /// Connection to the remote cache from `build-cache.remote`, if there is
+    /// one and the network is allowed.
+    remote: Option<Mutex<Remote>>,
+    /// The number of failed requests to the remote cache during this build,
+    /// and the first error.
+    remote_errors: Mutex<(usize, Option<anyhow::Error>)>,
+    /// Keys of the entries restored or stored during this build, along with
+    /// their size in bytes.
+    used: Mutex<HashMap<String, u64>>,
+}
+
+impl SharedCache {
+    /// Returns a handle to the shared build cache if it is enabled.
+    pub fn new(gctx: &GlobalContext) -> CargoResult<Option<Arc<SharedCache>>> {
+        if !gctx.cli_unstable().shared_build_cache {
+            return Ok(None);
+        }
+        let remote = match gctx.get_string("build-cache.remote")? {
+            Some(remote) => {
+                let mut url = Url::parse(&remote.val)
+                    .ok()
+                    .filter(|url| matches!(url.scheme(), "http" | "https"))
+                    .with_context(|| {
+                        format!(
+                            "`build-cache.remote` must be an http or https URL, got `{}`\n\
+                             (defined in {})",
+                            remote.val, remote.definition
+                        )
+                    })?;
+                if !url.path().ends_with('/') {
+                    url.set_path(&format!("{}/", url.path()));
+                }
+                Some(url)
+            }
+            None => None,
+        };
+        let remote = match remote {
+            Some(url) if gctx.network_allowed() => match http_handle(gctx) {
+                Ok(handle) => Some(Mutex::new(Remote { url, handle })),
+                Err(e) => {
+                    crate::display_warning_with_error(
+                        &format!("the remote build cache `{url}` is not used"),
+                        &e,
+                        &mut gctx.shell(),
+                    );
+                    None
+                }
+            },
+            _ => None,
+        };
+        Ok(Some(Arc::new(SharedCache {
+            root: gctx.shared_build_cache_path().into_path_unlocked(),
+            remote,
+            remote_errors: Mutex::new((0, None)),
+            used: Mutex::new(HashMap::new()),
+        })))
+    }
+
+    /// Copies the files of the entry `key` into `build_root`.
+    ///
+    /// Returns `false` if there is no such entry.
+    fn restore(&self, key: &str, build_root: &Path) -> CargoResult<bool> {
+        let entry_dir = self.root.join(key);
+        if !entry_dir.is_dir() {
+            return Ok(false);
+        }
+        // The restored files must not look older than the outputs of the
+        // units depending on them, or those would be considered stale.
+        let now = FileTime::now();
+        let mut size = 0;
+        for entry in walkdir::WalkDir::new(&entry_dir) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let rel = entry.path().strip_prefix(&entry_dir)?;
+            let dst = build_root.join(rel);
+            if let Some(parent) = dst.parent() {
+                paths::create_dir_all(parent)?;
+            }
+            size += paths::copy(entry.path(), &dst)?;
+            filetime::set_file_mtime(&dst, now)
+                .with_context(|| format!("failed to set mtime of `{}`", dst.display()))?;
+        }
+        self.used.lock().unwrap().insert(key.to_string(), size);
+        Ok(true)
+    }
+
+    /// Stores `files`, which must be located in `build_root`, as the entry
+    /// `key`.
+    fn store(&self, key: &str, build_root: &Path, files: &[PathBuf]) -> CargoResult<()> {
+        if self.root.join(key).exists() {
+            return Ok(());
+        }
+        let tmp = self.staging_dir(key)?;
+        let mut size = 0;
+        for file in files {
+            let rel = file.strip_prefix(build_root).with_context(|| {
+                format!("`{}` is outside of the build directory", file.display())
+            })?;
+            let dst = tmp.join(rel);
+            if let Some(parent) = dst.parent() {
+                paths::create_dir_all(parent)?;
+            }
+            size += paths::copy(file, &dst)?;
+        }
+        self.commit(&tmp, key)?;
+        self.used.lock().unwrap().insert(key.to_string(), size);
+        Ok(())
+    }
+
+    /// Fetches the entry `key` from the remote cache into the local one.
+    ///
+    /// Returns `false` if the remote cache doesn't have it.
+    fn download(&self, remote: &mut Remote, key: &str) -> CargoResult<bool> {
+        let Some(archive) = remote.get(key)? else {
+            return Ok(false);
+        };
+        let tmp = self.staging_dir(key)?;
+        tar::Archive::new(GzDecoder::new(&archive[..]))
+            .unpack(&tmp)
+            .with_context(|| format!("failed to unpack entry `{key}` from {}", remote.url))?;
+        self.commit(&tmp, key)?;
+        Ok(true)
+    }
+
+    /// Uploads the local entry `key` to the remote cache.
+    fn upload(&self, remote: &mut Remote, key: &str) -> CargoResult<()> {
+        let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        archive.append_dir_all(".", self.root.join(key))?;
+        let archive = archive.into_inner()?.finish()?;
+        remote.put(key, &archive)
+    }
+
+    /// Creates an empty directory to write the entry `key` into before
+    /// [committing](Self::commit) it.
+    fn staging_dir(&self, key: &str) -> CargoResult<PathBuf> {
+        let tmp = self.root.join(format!(".tmp-{key}-{}", std::process::id()));
+        if tmp.exists() {
+            paths::remove_dir_all(&tmp)?;
+        }
+        paths::create_dir_all(&tmp)?;
+        Ok(tmp)
+    }
+
+    /// Moves the staged entry at `tmp` into place as the entry `key`.
+    fn commit(&self, tmp: &Path, key: &str) -> CargoResult<()> {
+        let entry_dir = self.root.join(key);
+        if let Err(e) = std::fs::rename(tmp, &entry_dir) {
+            let _ = paths::remove_dir_all(tmp);
+            // Another build may have stored the same entry in the meantime.
+            if entry_dir.exists() {
+                return Ok(());
+            }
+            return Err(anyhow::Error::from(e)
+                .context(format!("failed to rename `{}` into place", tmp.display())));
+        }
+        Ok(())
+    }
+
+    /// Remembers a failed request to the remote cache, to be reported by
+    /// [`SharedCache::report_remote_errors`].
+    fn remote_error(&self, error: anyhow::Error) {
+        tracing::warn!("remote build cache error: {error:?}");
+        let mut errors = self.remote_errors.lock().unwrap();
+        errors.0 += 1;
+        errors.1.get_or_insert(error);
+    }
+
+    /// Warns about the failed requests to the remote cache during this build,
+    /// if there were any.
+    pub fn report_remote_errors(&self, gctx: &GlobalContext) {
+        let (count, first) = std::mem::take(&mut *self.remote_errors.lock().unwrap());
+        let (Some(first), Some(remote)) = (first, &self.remote) else {
+            return;
+        };
+        let url = remote.lock().unwrap().url.clone();
+        let requests = if count == 1 { "request" } else { "requests" };
+        crate::display_warning_with_error(
+            &format!(
+                "{count} {requests} to the remote build cache `{url}` failed, \
+                 the affected units were compiled locally or not uploaded"
+            ),
+            &first,
+            &mut gctx.shell(),
+        );
+    }
+
+    /// Records the entries used during this build as last used now.
+    ///
+    /// Errors are reported as warnings, since the build itself is not
+    /// affected by them.
+    pub fn save_last_use(&self, gctx: &GlobalContext) {
+        let used = std::mem::take(&mut *self.used.lock().unwrap());
+        if used.is_empty() {
+            return;
+        }
+        // The build only holds a shared lock, but the tracking database
+        // requires an exclusive one.
+        let _lock = match gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive) {
+            Ok(lock) => lock,
+            Err(e) => {
+                tracing::warn!("failed to lock package cache to save build cache use: {e:?}");
+                return;
+            }
+        };
+        let mut deferred = match gctx.deferred_global_last_use() {
+            Ok(deferred) => deferred,
+            Err(e) => {
+                tracing::warn!("failed to save build cache use: {e:?}");
+                return;
+            }
+        };
+        for (key, size) in used {
+            deferred.mark_build_cache_entry_used(BuildCacheEntry {
+                key: InternedString::new(&key),
+                size,
+            });
+        }
+        deferred.save_no_error(gctx);
+    }
+}
+
+/// Connection to a remote build cache, see the
+/// [module-level docs](self#remote-caches).
+struct Remote {
+    url: Url,
+    handle: Easy,
+}
+
+impl Remote {
+    fn entry_url(&self, key: &str) -> CargoResult<Url> {
+        Ok(self.url.join(&format!("v1/entries/{key}"))?)
+    }
+
+    /// Fetches the archive of the entry `key`, or `None` if the remote
+    /// doesn't have it.
+    fn get(&mut self, key: &str) -> CargoResult<Option<Vec<u8>>> {
+        let url = self.entry_url(key)?;
+        self.handle.get(true)?;
+        self.handle.url(url.as_str())?;
+        let mut body = Vec::new();
+        {
+            let mut transfer = self.handle.transfer();
+            transfer.write_function(|data| {
+                body.extend_from_slice(data);
+                Ok(data.len())
+            })?;
+            transfer
+                .perform()
+                .with_context(|| format!("failed to download `{url}`"))?;
+        }
+        match self.handle.response_code()? {
+            200 => Ok(Some(body)),
+            404 => Ok(None),
+            code => bail!("failed to download `{url}`, got status {code}"),
+        }
+    }
+
+    /// Uploads `archive` as the archive of the entry `key`.
+    fn put(&mut self, key: &str, mut archive: &[u8]) -> CargoResult<()> {
+        let url = self.entry_url(key)?;
+        self.handle.put(true)?;
+        self.handle.upload(true)?;
+        self.handle.url(url.as_str())?;
+        self.handle.in_filesize(archive.len() as u64)?;
+        let mut headers = List::new();
+        headers.append("Content-Type: application/gzip")?;
+        // Don't wait for a `100 Continue` the server may never send.
+        headers.append("Expect:")?;
+        self.handle.http_headers(headers)?;
+        {
+            let mut transfer = self.handle.transfer();
+            transfer.read_function(|buf| Ok(archive.read(buf).unwrap_or(0)))?;
+            transfer
+                .perform()
+                .with_context(|| format!("failed to upload `{url}`"))?;
+        }
+        match self.handle.response_code()? {
+            200..=299 => Ok(()),
+            code => bail!("failed to upload `{url}`, got status {code}"),
+        }
+    }
+}
+
+/// Returns whether the outputs of `unit` can be shared between workspaces.
+fn is_cacheable(build_runner: &BuildRunner<'_, '_>, unit: &Unit) -> bool {
+    // Sources of path packages may change at any time, and their fingerprints
+    // contain mtimes specific to this workspace.
+    !unit.is_local()
+        && !unit.is_std
+        && !unit.artifact.is_true()
+        && unit.target.is_lib()
+        && matches!(
+            unit.mode,
+            CompileMode::Build | CompileMode::Check { test: false }
+        )
+        && !build_runner.bcx.build_config.build_plan
+}
+
+/// Wraps `compile`, the work that compiles `unit`, so that the unit is
+/// restored from the shared build cache when possible, and stored in it
+/// otherwise.
+///
+/// `compile` is returned unchanged if the cache is disabled or `unit` can't
+/// be cached.
+pub fn prepare(
+    build_runner: &mut BuildRunner<'_, '_>,
+    unit: &Unit,
+    compile: Work,
+) -> CargoResult<Work> {
+    let Some(cache) = build_runner.shared_cache.clone() else {
+        return Ok(compile);
+    };
+    if !is_cacheable(build_runner, unit) {
+        return Ok(compile);
+    }
+
+    let fingerprint = fingerprint::prepare_time_hash(build_runner, unit)?;
+    let unit_id = build_runner.files().metadata(unit).unit_id();
+    let script_metadata = build_runner.find_build_script_metadata(unit);
+    let build_script_outputs = Arc::clone(&build_runner.build_script_outputs);
+    let build_root = build_runner.bcx.ws.build_dir().into_path_unlocked();
+    let mut files: Vec<_> = build_runner
+        .outputs(unit)?
+        .iter()
+        .map(|output| output.path.clone())
+        .collect();
+    files.push(fingerprint::dep_info_loc(build_runner, unit));
+    files.push(build_runner.files().message_cache_path(unit));
+    let replay = super::replay_unit_output_cache(build_runner, unit)?;
+    let name = unit.pkg.to_string();
+
+    Ok(Work::new(move |state| {
+        let key = {
+            let outputs = build_script_outputs.lock().unwrap();
+            cache_key(
+                fingerprint,
+                unit_id,
+                script_metadata.and_then(|meta| outputs.get(meta)),
+            )
+        };
+        let mut restored = cache.restore(&key, &build_root);
+        if let (Ok(false), Some(remote)) = (&restored, &cache.remote) {
+            restored = match cache.download(&mut remote.lock().unwrap(), &key) {
+                Ok(true) => cache.restore(&key, &build_root),
+                Ok(false) => Ok(false),
+                Err(e) => {
+                    cache.remote_error(e);
+                    Ok(false)
+                }
+            };
+        }
+        match restored {
+            Ok(true) => {
+                state.restored(name);
+                return replay.call(state);
+            }
+            Ok(false) => debug!("shared build cache miss for {name} ({key})"),
+            Err(e) => debug!("failed to restore {name} from shared build cache: {e:?}"),
+        }
+
+        compile.call(state)?;
+
+        // Not every output is produced on every platform, and the message
+        // cache only exists if the compiler emitted anything.
+        let files: Vec<_> = files.into_iter().filter(|f| f.is_file()).collect();
+        if let Err(e) = cache.store(&key, &build_root, &files) {
+            tracing::warn!("failed to store {name} in shared build cache: {e:?}");
+            return Ok(());
+        }
+        if let Some(remote) = &cache.remote {
+            if let Err(e) = cache.upload(&mut remote.lock().unwrap(), &key) {
+                cache.remote_error(e.context(format!("failed to upload {name}")));
+            }
+        }
+        Ok(())
+    }))
+}
+
+/// Computes the key of the entry for a unit, see the
+/// [module-level docs](self#cache-keys).
+fn cache_key(fingerprint: u64, unit_id: UnitHash, script_output: Option<&BuildOutput>) -> String {
+    let mut hasher = StableHasher::new();
+    fingerprint.hash(&mut hasher);
+    unit_id.hash(&mut hasher);
+    if let Some(output) = script_output {
+        output.cfgs.hash(&mut hasher);
+        output.check_cfgs.hash(&mut hasher);
+        output.env.hash(&mut hasher);
+        output.library_links.hash(&mut hasher);
+        output.linker_args.hash(&mut hasher);
+    }
+    util::to_hex(Hasher::finish(&hasher))
+}
@@ -189,12 +189,13 @@ fn generate_roots(
             let kind = **kind;
             let list = ret.entry(kind).or_insert_with(Vec::new);
             let unit_for = UnitFor::new_normal(kind);
-            let profile = profiles.get_profile(
+            let profile = profiles.get_profile_for_platform(
                 pkg.package_id(),
                 /*is_member*/ false,
                 /*is_local*/ false,
                 unit_for,
                 kind,
+                target_data,
             );
             list.push(interner.intern(
                 pkg,
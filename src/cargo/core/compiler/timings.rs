@@ -5,7 +5,7 @@
 use super::{CompileMode, Unit};
 use crate::core::compiler::job_queue::JobId;
 use crate::core::compiler::{BuildContext, BuildRunner, TimingOutput};
-use crate::core::PackageId;
+use crate::core::{PackageId, PackageIdSpec};
 use crate::util::cpu::State;
 use crate::util::machine_message::{self, Message};
 use crate::util::style;
@@ -14,6 +14,7 @@ use anyhow::Context as _;
 use cargo_util::paths;
 use std::collections::HashMap;
 use std::io::{BufWriter, Write};
+use std::path::Path;
 use std::thread::available_parallelism;
 use std::time::{Duration, Instant};
 
@@ -30,7 +31,8 @@ pub struct Timings<'gctx> {
     enabled: bool,
     /// If true, saves an HTML report to disk.
     report_html: bool,
-    /// If true, emits JSON information with timing information.
+    /// If true, emits JSON information with timing information, and saves it
+    /// as a [`TimingReport`] to disk.
     report_json: bool,
     /// When Cargo started.
     start: Instant,
@@ -95,6 +97,99 @@ struct Concurrency {
     inactive: usize,
 }
 
+/// The timing information of a build, saved as JSON by `--timings=json`.
+///
+/// Read back by `cargo report timings`. Fields are only ever added to it, a
+/// change to the meaning of a field bumps [`TimingReport::VERSION`].
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct TimingReport {
+    /// The version of the format, [`TimingReport::VERSION`].
+    pub version: u32,
+    /// When the build started, in RFC 3339 format.
+    pub start: String,
+    /// Total time of the build in seconds.
+    pub duration: f64,
+    pub profile: String,
+    pub jobs: u32,
+    /// The number of CPUs on the system, if known.
+    pub ncpu: Option<usize>,
+    /// The first line of `rustc -vV`.
+    pub rustc: String,
+    pub host: String,
+    pub requested_targets: Vec<String>,
+    pub fresh_units: u32,
+    pub dirty_units: u32,
+    /// The error that stopped the build, if any.
+    pub error: Option<String>,
+    /// The units that were built, in the order they started.
+    pub units: Vec<UnitTimingReport>,
+    pub concurrency: Vec<ConcurrencySample>,
+    pub cpu_usage: Vec<CpuUsageSample>,
+}
+
+/// How long an individual unit took to build, see [`TimingReport`].
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct UnitTimingReport {
+    pub package_id: PackageIdSpec,
+    pub name: String,
+    pub version: String,
+    /// The target and what was done with it, like `bin "foo" (check)`.
+    pub target: String,
+    /// The platform the unit was built for.
+    pub kind: String,
+    pub features: Vec<String>,
+    /// When the unit started, as an offset in seconds from the start of the
+    /// build.
+    pub start: f64,
+    /// Total time to build the unit in seconds.
+    pub duration: f64,
+    /// When the `.rmeta` file was generated, as an offset in seconds from
+    /// `start`.
+    pub rmeta_time: Option<f64>,
+    /// Indices into [`TimingReport::units`] of the units that could start
+    /// once this one finished.
+    pub unlocked_units: Vec<usize>,
+    /// Same as `unlocked_units`, but for the `.rmeta` file.
+    pub unlocked_rmeta_units: Vec<usize>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ConcurrencySample {
+    /// Time as an offset in seconds from the start of the build.
+    pub t: f64,
+    pub active: usize,
+    pub waiting: usize,
+    pub inactive: usize,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CpuUsageSample {
+    /// Time as an offset in seconds from the start of the build.
+    pub t: f64,
+    /// Percentage of the CPUs of the system in use.
+    pub usage: f64,
+}
+
+impl TimingReport {
+    pub const VERSION: u32 = 1;
+
+    /// Loads a report saved by `--timings=json`.
+    pub fn load(path: &Path) -> CargoResult<TimingReport> {
+        let contents = paths::read(path)?;
+        let report: TimingReport = serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse timing report `{}`", path.display()))?;
+        if report.version != TimingReport::VERSION {
+            anyhow::bail!(
+                "timing report `{}` has version {}, but only version {} is supported",
+                path.display(),
+                report.version,
+                TimingReport::VERSION
+            );
+        }
+        Ok(report)
+    }
+}
+
 impl<'gctx> Timings<'gctx> {
     pub fn new(bcx: &BuildContext<'_, 'gctx>, root_units: &[Unit]) -> Timings<'gctx> {
         let has_report = |what| bcx.build_config.timing_outputs.contains(&what);
@@ -301,6 +396,10 @@ impl<'gctx> Timings<'gctx> {
             self.report_html(build_runner, error)
                 .context("failed to save timing report")?;
         }
+        if self.report_json {
+            self.report_json(build_runner, error)
+                .context("failed to save timing report")?;
+        }
         Ok(())
     }
 
@@ -347,15 +446,107 @@ impl<'gctx> Timings<'gctx> {
         let unstamped_filename = timings_path.join("cargo-timing.html");
         paths::link_or_copy(&filename, &unstamped_filename)?;
 
+        self.report_saved(&filename)
+    }
+
+    /// Save the JSON [`TimingReport`] to disk.
+    fn report_json(
+        &self,
+        build_runner: &BuildRunner<'_, '_>,
+        error: &Option<anyhow::Error>,
+    ) -> CargoResult<()> {
+        let bcx = build_runner.bcx;
+        let timestamp = self.start_str.replace(&['-', ':'][..], "");
+        let timings_path = build_runner.files().host_root().join("cargo-timings");
+        paths::create_dir_all(&timings_path)?;
+        let filename = timings_path.join(format!("cargo-timing-{}.json", timestamp));
+
+        let unit_map = self.unit_map();
+        let units = self
+            .unit_times
+            .iter()
+            .map(|ut| UnitTimingReport {
+                package_id: ut.unit.pkg.package_id().to_spec(),
+                name: ut.unit.pkg.name().to_string(),
+                version: ut.unit.pkg.version().to_string(),
+                target: if ut.target.is_empty() {
+                    ut.unit.target.description_named()
+                } else {
+                    ut.target.trim_start().to_string()
+                },
+                kind: bcx.target_data.short_name(&ut.unit.kind).to_string(),
+                features: ut.unit.features.iter().map(|f| f.to_string()).collect(),
+                start: round(ut.start),
+                duration: round(ut.duration),
+                rmeta_time: ut.rmeta_time.map(round),
+                unlocked_units: unlocked_indices(&unit_map, &ut.unlocked_units),
+                unlocked_rmeta_units: unlocked_indices(&unit_map, &ut.unlocked_rmeta_units),
+            })
+            .collect();
+        let report = TimingReport {
+            version: TimingReport::VERSION,
+            start: self.start_str.clone(),
+            duration: round(self.start.elapsed().as_secs_f64()),
+            profile: self.profile.clone(),
+            jobs: bcx.jobs(),
+            ncpu: available_parallelism().ok().map(|x| x.get()),
+            rustc: rustc_version(bcx).to_string(),
+            host: bcx.rustc().host.to_string(),
+            requested_targets: bcx
+                .build_config
+                .requested_kinds
+                .iter()
+                .map(|kind| bcx.target_data.short_name(kind).to_string())
+                .collect(),
+            fresh_units: self.total_fresh,
+            dirty_units: self.total_dirty,
+            error: error.as_ref().map(|e| e.to_string()),
+            units,
+            concurrency: self
+                .concurrency
+                .iter()
+                .map(|c| ConcurrencySample {
+                    t: round(c.t),
+                    active: c.active,
+                    waiting: c.waiting,
+                    inactive: c.inactive,
+                })
+                .collect(),
+            cpu_usage: self
+                .cpu_usage
+                .iter()
+                .map(|&(t, usage)| CpuUsageSample {
+                    t: round(t),
+                    usage: round(usage),
+                })
+                .collect(),
+        };
+        paths::write(&filename, serde_json::to_string_pretty(&report)?)?;
+
+        let unstamped_filename = timings_path.join("cargo-timing.json");
+        paths::link_or_copy(&filename, &unstamped_filename)?;
+
+        self.report_saved(&filename)
+    }
+
+    fn report_saved(&self, filename: &Path) -> CargoResult<()> {
         let mut shell = self.gctx.shell();
-        let timing_path = std::env::current_dir().unwrap_or_default().join(&filename);
+        let timing_path = std::env::current_dir().unwrap_or_default().join(filename);
         let link = shell.err_file_hyperlink(&timing_path);
         let msg = format!("report saved to {link}{}{link:#}", timing_path.display(),);
         shell.status_with_color("Timing", msg, &style::NOTE)?;
-
         Ok(())
     }
 
+    /// Maps units to their index in `unit_times`.
+    fn unit_map(&self) -> HashMap<Unit, usize> {
+        self.unit_times
+            .iter()
+            .enumerate()
+            .map(|(i, ut)| (ut.unit.clone(), i))
+            .collect()
+    }
+
     /// Render the summary table.
     fn write_summary_table(
         &self,
@@ -439,12 +630,7 @@ impl<'gctx> Timings<'gctx> {
     /// in a `<script>` HTML element to draw graphs.
     fn write_js_data(&self, f: &mut impl Write) -> CargoResult<()> {
         // Create a map to link indices of unlocked units.
-        let unit_map: HashMap<Unit, usize> = self
-            .unit_times
-            .iter()
-            .enumerate()
-            .map(|(i, ut)| (ut.unit.clone(), i))
-            .collect();
+        let unit_map = self.unit_map();
         #[derive(serde::Serialize)]
         struct UnitData {
             i: usize,
@@ -458,7 +644,6 @@ impl<'gctx> Timings<'gctx> {
             unlocked_units: Vec<usize>,
             unlocked_rmeta_units: Vec<usize>,
         }
-        let round = |x: f64| (x * 100.0).round() / 100.0;
         let unit_data: Vec<UnitData> = self
             .unit_times
             .iter()
@@ -471,19 +656,8 @@ impl<'gctx> Timings<'gctx> {
                 }
                 .to_string();
 
-                // These filter on the unlocked units because not all unlocked
-                // units are actually "built". For example, Doctest mode units
-                // don't actually generate artifacts.
-                let unlocked_units: Vec<usize> = ut
-                    .unlocked_units
-                    .iter()
-                    .filter_map(|unit| unit_map.get(unit).copied())
-                    .collect();
-                let unlocked_rmeta_units: Vec<usize> = ut
-                    .unlocked_rmeta_units
-                    .iter()
-                    .filter_map(|unit| unit_map.get(unit).copied())
-                    .collect();
+                let unlocked_units = unlocked_indices(&unit_map, &ut.unlocked_units);
+                let unlocked_rmeta_units = unlocked_indices(&unit_map, &ut.unlocked_rmeta_units);
                 UnitData {
                     i,
                     name: ut.unit.pkg.name().to_string(),
@@ -581,13 +755,33 @@ impl UnitTime {
     }
 }
 
-fn render_rustc_info(bcx: &BuildContext<'_, '_>) -> String {
-    let version = bcx
-        .rustc()
+/// Rounds seconds to hundredths, which is precise enough for timings.
+fn round(x: f64) -> f64 {
+    (x * 100.0).round() / 100.0
+}
+
+/// The indices in `unit_map` of `unlocked`.
+///
+/// These filter on the unlocked units because not all unlocked units are
+/// actually "built". For example, Doctest mode units don't actually generate
+/// artifacts.
+fn unlocked_indices(unit_map: &HashMap<Unit, usize>, unlocked: &[Unit]) -> Vec<usize> {
+    unlocked
+        .iter()
+        .filter_map(|unit| unit_map.get(unit).copied())
+        .collect()
+}
+
+fn rustc_version<'a>(bcx: &'a BuildContext<'_, '_>) -> &'a str {
+    bcx.rustc()
         .verbose_version
         .lines()
         .next()
-        .expect("rustc version");
+        .expect("rustc version")
+}
+
+fn render_rustc_info(bcx: &BuildContext<'_, '_>) -> String {
+    let version = rustc_version(bcx);
     let requested_target = bcx
         .build_config
         .requested_kinds
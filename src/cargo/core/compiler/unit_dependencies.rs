@@ -811,12 +811,13 @@ fn new_unit_dep(
     artifact: Option<&Artifact>,
 ) -> CargoResult<UnitDep> {
     let is_local = pkg.package_id().source_id().is_path() && !state.is_std;
-    let profile = state.profiles.get_profile(
+    let profile = state.profiles.get_profile_for_platform(
         pkg.package_id(),
         state.ws.is_member(pkg),
         is_local,
         unit_for,
         kind,
+        state.target_data,
     );
     new_unit_dep_with_profile(
         state, parent, pkg, target, unit_for, kind, mode, profile, artifact,
@@ -0,0 +1,120 @@
+//! Detection of unused dependencies for the `unused_dependencies` lint.
+//!
+//! rustc only loads the crates passed with `--extern` that the crate refers
+//! to, and with `-Zbinary-dep-depinfo` it lists the files of those crates in
+//! its dep-info file. After a build, a dependency that none of the units of a
+//! package loaded is unused.
+//!
+//! The dep-info files are read from the fingerprint directory, so units that
+//! are fresh are checked as well.
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::fingerprint::{dep_info_loc, parse_dep_info};
+use super::{BuildContext, BuildRunner, Unit};
+use crate::core::dependency::DepKind;
+use crate::util::errors::AlreadyPrintedError;
+use crate::util::lints;
+use crate::CargoResult;
+
+/// Whether the dependencies of `unit` are checked for the
+/// `unused_dependencies` lint, which needs rustc to list the crates it loaded.
+pub fn is_tracked(bcx: &BuildContext<'_, '_>, unit: &Unit) -> bool {
+    bcx.gctx.cli_unstable().cargo_lints
+        && !unit.mode.is_doc()
+        && !unit.mode.is_doc_test()
+        && !unit.mode.is_doc_scrape()
+        && !unit.mode.is_run_custom_build()
+        && bcx.ws.is_member(&unit.pkg)
+        && lints::unused_dependencies_enabled(&unit.pkg)
+}
+
+/// Reports the dependencies of workspace members that no unit built for the
+/// package has used.
+pub fn report(build_runner: &mut BuildRunner<'_, '_>) -> CargoResult<()> {
+    let bcx = build_runner.bcx;
+    let mut units_by_pkg = BTreeMap::new();
+    for unit in bcx.unit_graph.keys() {
+        if is_tracked(bcx, unit) {
+            units_by_pkg
+                .entry(unit.pkg.package_id())
+                .or_insert_with(Vec::new)
+                .push(unit.clone());
+        }
+    }
+
+    let build_root = bcx.ws.build_dir().into_path_unlocked();
+    let mut error_count = 0;
+    for units in units_by_pkg.values() {
+        let pkg = &units[0].pkg;
+        // Indexes into `pkg.dependencies()`, of the dependencies passed to
+        // rustc and of those rustc loaded.
+        let mut passed = HashSet::new();
+        let mut used = HashSet::new();
+        for unit in units {
+            let dep_info = dep_info_loc(build_runner, unit);
+            let loaded = parse_dep_info(pkg.root(), &build_root, &dep_info)?.map(|info| {
+                info.files
+                    .into_keys()
+                    .filter_map(|path| path.file_name().map(|name| name.to_owned()))
+                    .collect::<HashSet<_>>()
+            });
+            let kinds: &[DepKind] = if unit.target.is_custom_build() {
+                &[DepKind::Build]
+            } else {
+                &[DepKind::Normal, DepKind::Development]
+            };
+            for unit_dep in &bcx.unit_graph[unit] {
+                let dep_unit = &unit_dep.unit;
+                if dep_unit.pkg.package_id() == pkg.package_id()
+                    || dep_unit.is_std
+                    || dep_unit.mode.is_run_custom_build()
+                    || !dep_unit.target.is_lib()
+                {
+                    continue;
+                }
+                let name_in_toml = unit_dep.dep_name.unwrap_or(dep_unit.pkg.name());
+                let is_used = match &loaded {
+                    Some(loaded) => build_runner.outputs(dep_unit)?.iter().any(|output| {
+                        output
+                            .path
+                            .file_name()
+                            .is_some_and(|name| loaded.contains(name))
+                    }),
+                    // Without dep-info, there's no telling.
+                    None => true,
+                };
+                for (i, dep) in pkg.dependencies().iter().enumerate() {
+                    if dep.name_in_toml() == name_in_toml && kinds.contains(&dep.kind()) {
+                        passed.insert(i);
+                        if is_used {
+                            used.insert(i);
+                        }
+                    }
+                }
+            }
+        }
+
+        let unused: Vec<_> = pkg
+            .dependencies()
+            .iter()
+            .enumerate()
+            .filter(|(i, dep)| {
+                // Dev-dependencies may only be used by doctests, which aren't
+                // built here.
+                dep.kind() != DepKind::Development && passed.contains(i) && !used.contains(i)
+            })
+            .map(|(_, dep)| dep)
+            .collect();
+        lints::check_unused_dependencies(pkg, &unused, &mut error_count, bcx.gctx)?;
+    }
+
+    if error_count > 0 {
+        Err(AlreadyPrintedError::new(anyhow::anyhow!(
+            "encountered {error_count} errors(s) while running lints"
+        ))
+        .into())
+    } else {
+        Ok(())
+    }
+}
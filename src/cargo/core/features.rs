@@ -519,6 +519,15 @@ features! {
 
     /// Allow paths that resolve relatively to a base specified in the config.
     (unstable, path_bases, "", "reference/unstable.html#path-bases"),
+
+    /// Allow overriding profile settings for a target platform.
+    (unstable, profile_target_overrides, "", "reference/unstable.html#profile-target-overrides"),
+
+    /// Allow setting environment variables for rustc and build scripts in a profile.
+    (unstable, profile_env, "", "reference/unstable.html#profile-env-option"),
+
+    /// Allow specifying the linker and linker arguments in a profile.
+    (unstable, profile_linker, "", "reference/unstable.html#profile-linker-and-link-args-options"),
 }
 
 /// Status and metadata for a single unstable feature.
@@ -773,12 +782,15 @@ unstable_cli_options!(
     dual_proc_macros: bool = ("Build proc-macros for both the host and the target"),
     feature_unification: bool = ("Enable new feature unification modes in workspaces"),
     features: Option<Vec<String>>,
+    fine_grain_locking: bool = ("Lock build directories per unit so that independent builds can run at the same time"),
     gc: bool = ("Track cache usage and \"garbage collect\" unused files"),
     #[serde(deserialize_with = "deserialize_git_features")]
     git: Option<GitFeatures> = ("Enable support for shallow git fetch operations"),
     #[serde(deserialize_with = "deserialize_gitoxide_features")]
     gitoxide: Option<GitoxideFeatures> = ("Use gitoxide for the given git interactions, or all of them if no argument is given"),
+    global_jobserver: bool = ("Share a machine-wide jobserver between concurrent Cargo builds"),
     host_config: bool = ("Enable the `[host]` section in the .cargo/config.toml file"),
+    memory_limit: bool = ("Enable the `memory-limit` and `sample-free-memory` options in build config in .cargo/config.toml file"),
     minimal_versions: bool = ("Resolve minimal dependency versions instead of maximum"),
     msrv_policy: bool = ("Enable rust-version aware policy within cargo"),
     mtime_on_use: bool = ("Configure Cargo to update the mtime of used files"),
@@ -786,7 +798,10 @@ unstable_cli_options!(
     no_index_update: bool = ("Do not update the registry index even if the cache is outdated"),
     package_workspace: bool = ("Handle intra-workspace dependencies when packaging"),
     panic_abort_tests: bool = ("Enable support to run tests with -Cpanic=abort"),
+    profile_env: bool = ("Enable the `env` option in profiles in .cargo/config.toml file"),
+    profile_linker: bool = ("Enable the `linker` and `link-args` options in profiles in .cargo/config.toml file"),
     profile_rustflags: bool = ("Enable the `rustflags` option in profiles in .cargo/config.toml file"),
+    profile_target_overrides: bool = ("Enable the `target` overrides in profiles in .cargo/config.toml file"),
     public_dependency: bool = ("Respect a dependency's `public` field in Cargo.toml to control public/private dependencies"),
     publish_timeout: bool = ("Enable the `publish.timeout` key in .cargo/config.toml file"),
     root_dir: Option<PathBuf> = ("Set the root directory relative to which paths are printed (defaults to workspace root)"),
@@ -795,6 +810,7 @@ unstable_cli_options!(
     sbom: bool = ("Enable the `sbom` option in build config in .cargo/config.toml file"),
     script: bool = ("Enable support for single-file, `.rs` packages"),
     separate_nightlies: bool,
+    shared_build_cache: bool = ("Reuse compiled units of non-path packages across workspaces"),
     skip_rustdoc_fingerprint: bool,
     target_applies_to_host: bool = ("Enable the `target-applies-to-host` key in the .cargo/config.toml file"),
     trim_paths: bool = ("Enable the `trim-paths` option in profiles"),
@@ -1290,6 +1306,7 @@ impl CliUnstable {
                 )?
             }
             "host-config" => self.host_config = parse_empty(k, v)?,
+            "memory-limit" => self.memory_limit = parse_empty(k, v)?,
             "next-lockfile-bump" => self.next_lockfile_bump = parse_empty(k, v)?,
             "minimal-versions" => self.minimal_versions = parse_empty(k, v)?,
             "msrv-policy" => self.msrv_policy = parse_empty(k, v)?,
@@ -1299,7 +1316,10 @@ impl CliUnstable {
             "package-workspace" => self.package_workspace= parse_empty(k, v)?,
             "panic-abort-tests" => self.panic_abort_tests = parse_empty(k, v)?,
             "public-dependency" => self.public_dependency = parse_empty(k, v)?,
+            "profile-env" => self.profile_env = parse_empty(k, v)?,
+            "profile-linker" => self.profile_linker = parse_empty(k, v)?,
             "profile-rustflags" => self.profile_rustflags = parse_empty(k, v)?,
+            "profile-target-overrides" => self.profile_target_overrides = parse_empty(k, v)?,
             "trim-paths" => self.trim_paths = parse_empty(k, v)?,
             "publish-timeout" => self.publish_timeout = parse_empty(k, v)?,
             "root-dir" => self.root_dir = v.map(|v| v.into()),
@@ -1308,6 +1328,9 @@ impl CliUnstable {
             "sbom" => self.sbom = parse_empty(k, v)?,
             "separate-nightlies" => self.separate_nightlies = parse_empty(k, v)?,
             "checksum-freshness" => self.checksum_freshness = parse_empty(k, v)?,
+            "global-jobserver" => self.global_jobserver = parse_empty(k, v)?,
+            "fine-grain-locking" => self.fine_grain_locking = parse_empty(k, v)?,
+            "shared-build-cache" => self.shared_build_cache = parse_empty(k, v)?,
             "skip-rustdoc-fingerprint" => self.skip_rustdoc_fingerprint = parse_empty(k, v)?,
             "script" => self.script = parse_empty(k, v)?,
             "target-applies-to-host" => self.target_applies_to_host = parse_empty(k, v)?,
@@ -1324,35 +1347,36 @@ impl CliUnstable {
 
     /// Generates an error if `-Z unstable-options` was not used for a new,
     /// unstable command-line flag.
-    pub fn fail_if_stable_opt(&self, flag: &str, issue: u32) -> CargoResult<()> {
+    ///
+    /// The `issue` is the tracking issue of the flag, if there is one.
+    pub fn fail_if_stable_opt(&self, flag: &str, issue: impl Into<Option<u32>>) -> CargoResult<()> {
         self.fail_if_stable_opt_custom_z(flag, issue, "unstable-options", self.unstable_options)
     }
 
     pub fn fail_if_stable_opt_custom_z(
         &self,
         flag: &str,
-        issue: u32,
+        issue: impl Into<Option<u32>>,
         z_name: &str,
         enabled: bool,
     ) -> CargoResult<()> {
         if !enabled {
-            let see = format!(
-                "See https://github.com/rust-lang/cargo/issues/{issue} for more \
-                 information about the `{flag}` flag."
-            );
+            let see = match issue.into() {
+                Some(issue) => format!(
+                    "\nSee https://github.com/rust-lang/cargo/issues/{issue} for more \
+                     information about the `{flag}` flag."
+                ),
+                None => String::new(),
+            };
             // NOTE: a `config` isn't available here, check the channel directly
             let channel = channel();
             if channel == "nightly" || channel == "dev" {
-                bail!(
-                    "the `{flag}` flag is unstable, pass `-Z {z_name}` to enable it\n\
-                     {see}"
-                );
+                bail!("the `{flag}` flag is unstable, pass `-Z {z_name}` to enable it{see}");
             } else {
                 bail!(
                     "the `{flag}` flag is unstable, and only available on the nightly channel \
                      of Cargo, but this is the `{channel}` channel\n\
-                     {SEE_CHANNELS}\n\
-                     {see}"
+                     {SEE_CHANNELS}{see}"
                 );
             }
         }
@@ -1361,34 +1385,37 @@ impl CliUnstable {
 
     /// Generates an error if `-Z unstable-options` was not used for a new,
     /// unstable subcommand.
+    ///
+    /// The `issue` is the tracking issue of the subcommand, if there is one.
     pub fn fail_if_stable_command(
         &self,
         gctx: &GlobalContext,
         command: &str,
-        issue: u32,
+        issue: impl Into<Option<u32>>,
         z_name: &str,
         enabled: bool,
     ) -> CargoResult<()> {
         if enabled {
             return Ok(());
         }
-        let see = format!(
-            "See https://github.com/rust-lang/cargo/issues/{} for more \
-            information about the `cargo {}` command.",
-            issue, command
-        );
+        let see = match issue.into() {
+            Some(issue) => format!(
+                "\nSee https://github.com/rust-lang/cargo/issues/{} for more \
+                information about the `cargo {}` command.",
+                issue, command
+            ),
+            None => String::new(),
+        };
         if gctx.nightly_features_allowed {
             bail!(
                 "the `cargo {command}` command is unstable, pass `-Z {z_name}` \
-                 to enable it\n\
-                 {see}",
+                 to enable it{see}",
             );
         } else {
             bail!(
                 "the `cargo {}` command is unstable, and only available on the \
                  nightly channel of Cargo, but this is the `{}` channel\n\
-                 {}\n\
-                 {}",
+                 {}{}",
                 command,
                 channel(),
                 SEE_CHANNELS,
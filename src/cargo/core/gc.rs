@@ -112,6 +112,8 @@ struct AutoConfig {
     max_git_co_age: Option<String>,
     /// Any git clone older than this duration will be deleted from the git cache.
     max_git_db_age: Option<String>,
+    /// Any shared build cache entry older than this duration will be deleted.
+    max_build_cache_age: Option<String>,
 }
 
 /// Options to use for garbage collection.
@@ -135,6 +137,10 @@ pub struct GcOpts {
     pub max_git_size: Option<u64>,
     /// The `--max-download-size` CLI option.
     pub max_download_size: Option<u64>,
+    /// The `--max-build-cache-age` CLI option.
+    pub max_build_cache_age: Option<Duration>,
+    /// The `--max-build-cache-size` CLI option.
+    pub max_build_cache_size: Option<u64>,
 }
 
 impl GcOpts {
@@ -159,6 +165,11 @@ impl GcOpts {
             || self.max_download_size.is_some()
     }
 
+    /// Returns whether any shared build cache cleaning options are set.
+    pub fn is_build_cache_opt_set(&self) -> bool {
+        self.max_build_cache_age.is_some() || self.max_build_cache_size.is_some()
+    }
+
     /// Updates the `GcOpts` to incorporate the specified max download age.
     ///
     /// "Download" means any cached data that can be re-downloaded.
@@ -176,10 +187,14 @@ impl GcOpts {
         let auto_config = gctx
             .get::<Option<AutoConfig>>("gc.auto")?
             .unwrap_or_default();
-        self.update_for_auto_gc_config(&auto_config)
+        self.update_for_auto_gc_config(&auto_config, gctx.cli_unstable().shared_build_cache)
     }
 
-    fn update_for_auto_gc_config(&mut self, auto_config: &AutoConfig) -> CargoResult<()> {
+    fn update_for_auto_gc_config(
+        &mut self,
+        auto_config: &AutoConfig,
+        shared_build_cache: bool,
+    ) -> CargoResult<()> {
         self.max_src_age = newer_time_span_for_config(
             self.max_src_age,
             "gc.auto.max-src-age",
@@ -220,6 +235,18 @@ impl GcOpts {
                 .as_deref()
                 .unwrap_or(DEFAULT_MAX_AGE_DOWNLOADED),
         )?;
+        // Cleaning the shared build cache measures each of its entries, so it
+        // is only done for those who may be using it.
+        if shared_build_cache || auto_config.max_build_cache_age.is_some() {
+            self.max_build_cache_age = newer_time_span_for_config(
+                self.max_build_cache_age,
+                "gc.auto.max-build-cache-age",
+                auto_config
+                    .max_build_cache_age
+                    .as_deref()
+                    .unwrap_or(DEFAULT_MAX_AGE_EXTRACTED),
+            )?;
+        }
         Ok(())
     }
 }
@@ -278,7 +305,8 @@ impl<'a, 'gctx> Gc<'a, 'gctx> {
             return Ok(());
         }
         let mut gc_opts = GcOpts::default();
-        gc_opts.update_for_auto_gc_config(&auto_config)?;
+        gc_opts
+            .update_for_auto_gc_config(&auto_config, self.gctx.cli_unstable().shared_build_cache)?;
         self.gc(clean_ctx, &gc_opts)?;
         if !clean_ctx.dry_run {
             self.global_cache_tracker.set_last_auto_gc()?;
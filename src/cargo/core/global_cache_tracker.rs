@@ -209,6 +209,17 @@ pub struct GitCheckout {
     pub size: Option<u64>,
 }
 
+/// The key for a shared build cache entry stored in the database.
+///
+/// See [`crate::core::compiler::shared_cache`] for what an entry contains.
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct BuildCacheEntry {
+    /// The directory name of the entry, which is the hex-encoded cache key.
+    pub key: InternedString,
+    /// Total size of the entry directory in bytes.
+    pub size: u64,
+}
+
 /// Filesystem paths in the global cache.
 ///
 /// Accessing these assumes a lock has already been acquired.
@@ -223,6 +234,8 @@ struct BasePaths {
     crate_dir: PathBuf,
     /// Root path to the `src` directories.
     src: PathBuf,
+    /// Root path to the shared build cache entries.
+    build_cache: PathBuf,
 }
 
 /// Migrations which initialize the database, and can be used to evolve it over time.
@@ -303,6 +316,15 @@ fn migrations() -> Vec<Migration> {
             )?;
             Ok(())
         }),
+        // Entries of the shared build cache, keyed by the directory name of
+        // the entry.
+        basic_migration(
+            "CREATE TABLE build_cache (
+                name TEXT PRIMARY KEY NOT NULL,
+                size INTEGER NOT NULL,
+                timestamp INTEGER NOT NULL
+             )",
+        ),
     ]
 }
 
@@ -512,6 +534,23 @@ impl GlobalCacheTracker {
         Ok(rows)
     }
 
+    /// Returns all shared build cache entry timestamps.
+    pub fn build_cache_all(&self) -> CargoResult<Vec<(BuildCacheEntry, Timestamp)>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT name, size, timestamp FROM build_cache")?;
+        let rows = stmt
+            .query_map([], |row| {
+                let key = row.get_unwrap(0);
+                let size = row.get_unwrap(1);
+                let timestamp = row.get_unwrap(2);
+                let kind = BuildCacheEntry { key, size };
+                Ok((kind, timestamp))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
     /// Returns whether or not an auto GC should be performed, compared to the
     /// last time it was recorded in the database.
     pub fn should_run_auto_gc(&mut self, frequency: Duration) -> CargoResult<bool> {
@@ -559,6 +598,7 @@ impl GlobalCacheTracker {
             git_co: gctx.git_checkouts_path().into_path_unlocked(),
             crate_dir: gctx.registry_cache_path().into_path_unlocked(),
             src: gctx.registry_source_path().into_path_unlocked(),
+            build_cache: gctx.shared_build_cache_path().into_path_unlocked(),
         };
         let now = now();
         trace!(target: "gc", "cleaning {gc_opts:?}");
@@ -577,6 +617,10 @@ impl GlobalCacheTracker {
             )
             .context("failed to sync tracking database")?
         }
+        if gc_opts.is_build_cache_opt_set() {
+            Self::sync_build_cache_with_files(&tx, now, &base.build_cache)
+                .context("failed to sync tracking database")?
+        }
         if let Some(max_age) = gc_opts.max_index_age {
             let max_age = now - max_age.as_secs();
             Self::get_registry_index_to_clean(&tx, max_age, &base, &mut delete_paths)?;
@@ -609,6 +653,15 @@ impl GlobalCacheTracker {
             let max_age = now - max_age.as_secs();
             Self::get_git_co_items_to_clean(&tx, max_age, &base.git_co, &mut delete_paths)?;
         }
+        if let Some(max_age) = gc_opts.max_build_cache_age {
+            let max_age = now - max_age.as_secs();
+            Self::get_build_cache_items_to_clean_age(
+                &tx,
+                max_age,
+                &base.build_cache,
+                &mut delete_paths,
+            )?;
+        }
         // Size collection must happen after date collection so that dates
         // have precedence, since size constraints are a more blunt
         // instrument.
@@ -646,6 +699,14 @@ impl GlobalCacheTracker {
         if let Some(max_size) = gc_opts.max_download_size {
             Self::get_registry_items_to_clean_size_both(&tx, max_size, &base, &mut delete_paths)?;
         }
+        if let Some(max_size) = gc_opts.max_build_cache_size {
+            Self::get_build_cache_items_to_clean_size(
+                &tx,
+                max_size,
+                &base.build_cache,
+                &mut delete_paths,
+            )?;
+        }
 
         clean_ctx.remove_paths(&delete_paths)?;
 
@@ -807,6 +868,44 @@ impl GlobalCacheTracker {
         Ok(())
     }
 
+    /// Synchronizes the `build_cache` table to match the entries on disk.
+    ///
+    /// Entries missing on disk are removed from the database, and entries
+    /// that are on disk but not tracked (such as when saving the last-use
+    /// data failed) are added with their current size.
+    #[tracing::instrument(skip(conn, now, base_path))]
+    fn sync_build_cache_with_files(
+        conn: &Connection,
+        now: Timestamp,
+        base_path: &Path,
+    ) -> CargoResult<()> {
+        trace!(target: "gc", "syncing build cache entries");
+        let mut select_stmt = conn.prepare_cached("SELECT rowid, name FROM build_cache")?;
+        let mut delete_stmt = conn.prepare_cached("DELETE FROM build_cache WHERE rowid = ?1")?;
+        let mut rows = select_stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let rowid: i64 = row.get_unwrap(0);
+            let name: String = row.get_unwrap(1);
+            if !base_path.join(name).exists() {
+                delete_stmt.execute([rowid])?;
+            }
+        }
+
+        let mut insert_stmt = conn.prepare_cached(
+            "INSERT INTO build_cache (name, size, timestamp)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT DO NOTHING",
+        )?;
+        // Entries are staged in dot-prefixed temporary directories before
+        // being renamed into place, so those are not tracked.
+        let names = Self::list_dir_names(base_path)?;
+        for name in names.iter().filter(|name| !name.starts_with('.')) {
+            let size = cargo_util::du(&base_path.join(name), &[])?;
+            insert_stmt.execute(params![name, size, now])?;
+        }
+        Ok(())
+    }
+
     /// For parent tables, add any entries that are on disk but aren't tracked in the db.
     #[tracing::instrument(skip(conn, now, base_path))]
     fn update_parent_for_missing_from_db(
@@ -1277,6 +1376,63 @@ impl GlobalCacheTracker {
         Ok(())
     }
 
+    /// Adds paths to delete from `build_cache` whose last use is older than
+    /// the given timestamp.
+    fn get_build_cache_items_to_clean_age(
+        conn: &Connection,
+        max_age: Timestamp,
+        base_path: &Path,
+        delete_paths: &mut Vec<PathBuf>,
+    ) -> CargoResult<()> {
+        debug!(target: "gc", "cleaning build cache since {max_age:?}");
+        let mut stmt = conn.prepare_cached(
+            "DELETE FROM build_cache WHERE timestamp < ?1
+                RETURNING name",
+        )?;
+        let mut rows = stmt.query([max_age])?;
+        while let Some(row) = rows.next()? {
+            let name: String = row.get_unwrap(0);
+            delete_paths.push(base_path.join(&name));
+        }
+        Ok(())
+    }
+
+    /// Adds paths to delete from `build_cache` in order to keep the total
+    /// size under the given max size.
+    fn get_build_cache_items_to_clean_size(
+        conn: &Connection,
+        max_size: u64,
+        base_path: &Path,
+        delete_paths: &mut Vec<PathBuf>,
+    ) -> CargoResult<()> {
+        debug!(target: "gc", "cleaning build cache till under {max_size:?}");
+        let total_size: u64 = conn.query_row(
+            "SELECT coalesce(SUM(size), 0) FROM build_cache",
+            [],
+            |row| row.get(0),
+        )?;
+        if total_size <= max_size {
+            return Ok(());
+        }
+        // See `get_registry_items_to_clean_size` for an explanation of this
+        // statement.
+        let mut stmt = conn.prepare_cached(
+            "DELETE FROM build_cache WHERE rowid IN \
+                (SELECT x.rowid FROM \
+                    (SELECT rowid, size, SUM(size) OVER \
+                        (ORDER BY timestamp, name ROWS UNBOUNDED PRECEDING) AS running_amount \
+                        FROM build_cache) x \
+                    WHERE x.running_amount - x.size < ?1) \
+                RETURNING name;",
+        )?;
+        let mut rows = stmt.query([total_size - max_size])?;
+        while let Some(row) = rows.next()? {
+            let name: String = row.get_unwrap(0);
+            delete_paths.push(base_path.join(&name));
+        }
+        Ok(())
+    }
+
     /// Adds paths to delete from `registry_index` whose last use is older
     /// than the given timestamp.
     fn get_registry_index_to_clean(
@@ -1442,6 +1598,8 @@ pub struct DeferredGlobalLastUse {
     git_db_timestamps: HashMap<GitDb, Timestamp>,
     /// New git checkout entries to insert.
     git_checkout_timestamps: HashMap<GitCheckout, Timestamp>,
+    /// New shared build cache entries to insert.
+    build_cache_timestamps: HashMap<BuildCacheEntry, Timestamp>,
     /// This is used so that a warning about failing to update the database is
     /// only displayed once.
     save_err_has_warned: bool,
@@ -1460,6 +1618,7 @@ impl DeferredGlobalLastUse {
             registry_src_timestamps: HashMap::new(),
             git_db_timestamps: HashMap::new(),
             git_checkout_timestamps: HashMap::new(),
+            build_cache_timestamps: HashMap::new(),
             save_err_has_warned: false,
             now: now(),
         }
@@ -1471,6 +1630,7 @@ impl DeferredGlobalLastUse {
             && self.registry_src_timestamps.is_empty()
             && self.git_db_timestamps.is_empty()
             && self.git_checkout_timestamps.is_empty()
+            && self.build_cache_timestamps.is_empty()
     }
 
     fn clear(&mut self) {
@@ -1479,6 +1639,7 @@ impl DeferredGlobalLastUse {
         self.registry_src_timestamps.clear();
         self.git_db_timestamps.clear();
         self.git_checkout_timestamps.clear();
+        self.build_cache_timestamps.clear();
     }
 
     /// Indicates the given [`RegistryIndex`] has been used right now.
@@ -1507,6 +1668,11 @@ impl DeferredGlobalLastUse {
         self.mark_git_checkout_used_stamp(git_checkout, None);
     }
 
+    /// Indicates the given [`BuildCacheEntry`] has been used right now.
+    pub fn mark_build_cache_entry_used(&mut self, entry: BuildCacheEntry) {
+        self.build_cache_timestamps.insert(entry, self.now);
+    }
+
     /// Indicates the given [`RegistryIndex`] has been used with the given
     /// time (or "now" if `None`).
     pub fn mark_registry_index_used_stamp(
@@ -1587,6 +1753,7 @@ impl DeferredGlobalLastUse {
         self.insert_registry_crate_from_cache(&tx)?;
         self.insert_registry_src_from_cache(&tx)?;
         self.insert_git_checkout_from_cache(&tx)?;
+        self.insert_build_cache_from_cache(&tx)?;
         tx.commit()?;
         trace!(target: "gc", "last-use save complete");
         Ok(())
@@ -1725,6 +1892,28 @@ impl DeferredGlobalLastUse {
         Ok(())
     }
 
+    /// Flushes all of the `build_cache_timestamps` to the database,
+    /// clearing `build_cache_timestamps`.
+    fn insert_build_cache_from_cache(&mut self, conn: &Connection) -> CargoResult<()> {
+        let build_cache_timestamps = std::mem::take(&mut self.build_cache_timestamps);
+        for (entry, timestamp) in build_cache_timestamps {
+            trace!(target: "gc", "insert build cache entry {entry:?} {timestamp}");
+            let mut stmt = conn.prepare_cached(
+                "INSERT INTO build_cache (name, size, timestamp)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT DO UPDATE SET timestamp=excluded.timestamp
+                    WHERE timestamp < ?4",
+            )?;
+            stmt.execute(params![
+                entry.key,
+                entry.size,
+                timestamp,
+                timestamp - UPDATE_RESOLUTION
+            ])?;
+        }
+        Ok(())
+    }
+
     /// Returns the numeric ID of the registry, either fetching from the local
     /// cache, or getting it from the database.
     ///
@@ -21,7 +21,7 @@
 //! The precedence is explained in [`ProfileMaker`].
 //! The algorithm happens within [`ProfileMaker::get_profile`].
 
-use crate::core::compiler::{CompileKind, CompileTarget, Unit};
+use crate::core::compiler::{CompileKind, CompileTarget, RustcTargetData, Unit};
 use crate::core::dependency::Artifact;
 use crate::core::resolver::features::FeaturesFor;
 use crate::core::Feature;
@@ -32,6 +32,7 @@ use crate::util::interning::InternedString;
 use crate::util::toml::validate_profile;
 use crate::util::{closest_msg, context, CargoResult, GlobalContext};
 use anyhow::{bail, Context as _};
+use cargo_platform::{Cfg, Platform};
 use cargo_util_schemas::manifest::TomlTrimPaths;
 use cargo_util_schemas::manifest::TomlTrimPathsValue;
 use cargo_util_schemas::manifest::{
@@ -268,6 +269,9 @@ impl Profiles {
     /// Retrieves the profile for a target.
     /// `is_member` is whether or not this package is a member of the
     /// workspace.
+    ///
+    /// This doesn't apply `[profile.*.target.*]` overrides, see
+    /// [`Profiles::get_profile_for_platform`].
     pub fn get_profile(
         &self,
         pkg_id: PackageId,
@@ -275,9 +279,40 @@ impl Profiles {
         is_local: bool,
         unit_for: UnitFor,
         kind: CompileKind,
+    ) -> Profile {
+        self.get_profile_inner(pkg_id, is_member, is_local, unit_for, kind, None)
+    }
+
+    /// Like [`Profiles::get_profile`], but also applies the
+    /// `[profile.*.target.*]` overrides matching the platform of `kind`.
+    pub fn get_profile_for_platform(
+        &self,
+        pkg_id: PackageId,
+        is_member: bool,
+        is_local: bool,
+        unit_for: UnitFor,
+        kind: CompileKind,
+        target_data: &RustcTargetData<'_>,
+    ) -> Profile {
+        // Units for the host use `build-override` instead of the overrides
+        // for a target.
+        let platform = (!unit_for.is_for_host())
+            .then(|| (target_data.short_name(&kind), target_data.cfg(kind)));
+        self.get_profile_inner(pkg_id, is_member, is_local, unit_for, kind, platform)
+    }
+
+    fn get_profile_inner(
+        &self,
+        pkg_id: PackageId,
+        is_member: bool,
+        is_local: bool,
+        unit_for: UnitFor,
+        kind: CompileKind,
+        platform: Option<(&str, &[Cfg])>,
     ) -> Profile {
         let maker = self.get_profile_maker(&self.requested_profile).unwrap();
-        let mut profile = maker.get_profile(Some(pkg_id), is_member, unit_for.is_for_host());
+        let mut profile =
+            maker.get_profile(Some(pkg_id), is_member, unit_for.is_for_host(), platform);
 
         // Dealing with `panic=abort` and `panic=unwind` requires some special
         // treatment. Be sure to process all the various options here.
@@ -331,6 +366,7 @@ impl Profiles {
         result.debuginfo = for_unit_profile.debuginfo;
         result.opt_level = for_unit_profile.opt_level;
         result.trim_paths = for_unit_profile.trim_paths.clone();
+        result.env = for_unit_profile.env.clone();
         result
     }
 
@@ -340,7 +376,9 @@ impl Profiles {
     pub fn base_profile(&self) -> Profile {
         let profile_name = self.requested_profile;
         let maker = self.get_profile_maker(&profile_name).unwrap();
-        maker.get_profile(None, /*is_member*/ true, /*is_for_host*/ false)
+        maker.get_profile(
+            None, /*is_member*/ true, /*is_for_host*/ false, /*platform*/ None,
+        )
     }
 
     /// Gets the directory name for a profile, like `debug` or `release`.
@@ -402,6 +440,10 @@ impl Profiles {
 /// - `[profile.dev.package."*"]` -- this cannot apply to workspace members.
 /// - `[profile.dev.build-override]` -- this can only apply to `build.rs` scripts
 ///   and their dependencies.
+/// - `[profile.dev.target.<triple>]` -- this cannot apply to `build.rs` scripts
+///   and their dependencies.
+/// - `[profile.dev.target.'cfg(...)']` -- same as above, when several match,
+///   the later one in alphabetical order wins.
 /// - `[profile.dev]`
 /// - Default (hard-coded) values.
 #[derive(Debug, Clone)]
@@ -425,11 +467,15 @@ impl ProfileMaker {
     }
 
     /// Generates a new `Profile`.
+    ///
+    /// `platform` is the name and `cfg` values of the target platform, for
+    /// applying the overrides for it.
     fn get_profile(
         &self,
         pkg_id: Option<PackageId>,
         is_member: bool,
         is_for_host: bool,
+        platform: Option<(&str, &[Cfg])>,
     ) -> Profile {
         let mut profile = self.default.clone();
 
@@ -469,20 +515,39 @@ impl ProfileMaker {
         // profiles, such as `[profile.release.build-override]` or
         // `[profile.release.package.foo]`
         if let Some(toml) = &self.toml {
-            merge_toml_overrides(pkg_id, is_member, is_for_host, &mut profile, toml);
+            merge_toml_overrides(pkg_id, is_member, is_for_host, platform, &mut profile, toml);
         }
         profile
     }
 }
 
-/// Merge package and build overrides from the given TOML profile into the given `Profile`.
+/// Merge target, package and build overrides from the given TOML profile into
+/// the given `Profile`.
 fn merge_toml_overrides(
     pkg_id: Option<PackageId>,
     is_member: bool,
     is_for_host: bool,
+    platform: Option<(&str, &[Cfg])>,
     profile: &mut Profile,
     toml: &TomlProfile,
 ) {
+    if let (Some((name, cfg)), Some(targets)) = (platform, &toml.target) {
+        // `cfg()` expressions first, so the target triple itself wins.
+        let mut matches: Vec<_> = targets
+            .iter()
+            .filter_map(|(key, target_profile)| {
+                // `validate_profile` ensures these parse.
+                let platform = key.parse::<Platform>().ok()?;
+                platform
+                    .matches(name, cfg)
+                    .then(|| (matches!(platform, Platform::Name(_)), target_profile))
+            })
+            .collect();
+        matches.sort_by_key(|(is_name, _)| *is_name);
+        for (_, target_profile) in matches {
+            merge_profile(profile, target_profile);
+        }
+    }
     if is_for_host {
         if let Some(build_override) = &toml.build_override {
             merge_profile(profile, build_override);
@@ -572,6 +637,18 @@ fn merge_profile(profile: &mut Profile, toml: &TomlProfile) {
     if let Some(trim_paths) = &toml.trim_paths {
         profile.trim_paths = Some(trim_paths.clone());
     }
+    if let Some(env) = &toml.env {
+        profile.env.extend(
+            env.iter()
+                .map(|(k, v)| (InternedString::new(k), InternedString::new(v))),
+        );
+    }
+    if let Some(linker) = &toml.linker {
+        profile.linker = Some(InternedString::new(linker));
+    }
+    if let Some(args) = &toml.link_args {
+        profile.link_args = args.iter().map(InternedString::from).collect();
+    }
     profile.strip = match toml.strip {
         Some(StringOrBool::Bool(true)) => {
             Strip::Resolved(StripInner::Named(InternedString::new("symbols")))
@@ -625,6 +702,19 @@ pub struct Profile {
     // remove when `-Ztrim-paths` is stablized
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trim_paths: Option<TomlTrimPaths>,
+    /// Environment variables set for rustc and build scripts.
+    // remove when `profile-env` is stablized
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<InternedString, InternedString>,
+    // `None` means use the linker of the target configuration.
+    // remove when `profile-linker` is stablized
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linker: Option<InternedString>,
+    /// Arguments passed to the linker with `-C link-arg`, for crates that are
+    /// linked.
+    // remove when `profile-linker` is stablized
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub link_args: Vec<InternedString>,
 }
 
 impl Default for Profile {
@@ -646,6 +736,9 @@ impl Default for Profile {
             strip: Strip::Deferred(StripInner::None),
             rustflags: vec![],
             trim_paths: None,
+            env: BTreeMap::new(),
+            linker: None,
+            link_args: vec![],
         }
     }
 }
@@ -675,6 +768,9 @@ compact_debug! {
                 strip
                 rustflags
                 trim_paths
+                env
+                linker
+                link_args
             )]
         }
     }
@@ -743,7 +839,7 @@ impl Profile {
             self.rpath,
             (self.incremental, self.panic, self.strip),
             &self.rustflags,
-            &self.trim_paths,
+            (&self.trim_paths, &self.env, self.linker, &self.link_args),
         )
     }
 }
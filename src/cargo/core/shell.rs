@@ -21,6 +21,9 @@ pub struct Shell {
     /// Flag that indicates the current line needs to be cleared before
     /// printing. Used when a progress bar is currently displayed.
     needs_clear: bool,
+    /// Whether messages are discarded whatever the verbosity, see
+    /// [`Shell::set_muted`].
+    muted: bool,
     hostname: Option<String>,
 }
 
@@ -60,6 +63,7 @@ impl Shell {
             },
             verbosity: Verbosity::Verbose,
             needs_clear: false,
+            muted: false,
             hostname: None,
         }
     }
@@ -70,6 +74,7 @@ impl Shell {
             output: ShellOut::Write(AutoStream::never(out)), // strip all formatting on write
             verbosity: Verbosity::Verbose,
             needs_clear: false,
+            muted: false,
             hostname: None,
         }
     }
@@ -85,6 +90,7 @@ impl Shell {
     ) -> CargoResult<()> {
         match self.verbosity {
             Verbosity::Quiet => Ok(()),
+            _ if self.muted => Ok(()),
             _ => {
                 if self.needs_clear {
                     self.err_erase_line();
@@ -238,6 +244,14 @@ impl Shell {
         self.verbosity = verbosity;
     }
 
+    /// Sets whether status messages, warnings and notes are discarded.
+    ///
+    /// Unlike [`Verbosity::Quiet`], this leaves alone what else depends on
+    /// the verbosity, like showing the output of build scripts with `-vv`.
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
     /// Gets the verbosity of the shell.
     pub fn verbosity(&self) -> Verbosity {
         self.verbosity
@@ -42,7 +42,9 @@ use std::sync::Arc;
 use crate::core::compiler::unit_dependencies::build_unit_dependencies;
 use crate::core::compiler::unit_graph::{self, UnitDep, UnitGraph};
 use crate::core::compiler::{apply_env_config, standard_lib, CrateType, TargetInfo};
-use crate::core::compiler::{BuildConfig, BuildContext, BuildRunner, Compilation};
+use crate::core::compiler::{
+    BuildConfig, BuildContext, BuildRunner, Compilation, UnitLockContended,
+};
 use crate::core::compiler::{CompileKind, CompileMode, CompileTarget, RustcTargetData, Unit};
 use crate::core::compiler::{DefaultExecutor, Executor, UnitInterner};
 use crate::core::profiles::Profiles;
@@ -66,6 +68,10 @@ mod packages;
 
 pub use packages::Packages;
 
+mod shard;
+
+pub use shard::Shard;
+
 /// Contains information about how a package should be compiled.
 ///
 /// Note on distinction between `CompileOptions` and [`BuildConfig`]:
@@ -98,6 +104,8 @@ pub struct CompileOptions {
     /// Whether the build process should check the minimum Rust version
     /// defined in the cargo metadata for a crate.
     pub honor_rust_version: Option<bool>,
+    /// Only build the root units in this shard (`cargo test --shard`).
+    pub shard: Option<Shard>,
 }
 
 impl CompileOptions {
@@ -116,6 +124,7 @@ impl CompileOptions {
             target_rustc_crate_types: None,
             rustdoc_document_private_items: false,
             honor_rust_version: None,
+            shard: None,
         })
     }
 }
@@ -159,11 +168,28 @@ pub fn compile_ws<'a>(
         return Compilation::new(&bcx);
     }
     crate::core::gc::auto_gc(bcx.gctx);
-    let build_runner = BuildRunner::new(&bcx)?;
     if options.build_config.dry_run {
-        build_runner.dry_run()
-    } else {
-        build_runner.compile(exec)
+        return BuildRunner::new(&bcx)?.dry_run();
+    }
+    let mut attempts = 0;
+    loop {
+        // Starting over prints nothing new until the units are locked.
+        bcx.gctx.shell().set_muted(attempts > 0);
+        let build_runner = BuildRunner::new(&bcx);
+        bcx.gctx.shell().set_muted(false);
+        let mut build_runner = build_runner?;
+        build_runner.lock_retry = attempts > 0;
+        if attempts >= UnitLockContended::MAX_ATTEMPTS {
+            // Stop contending with other builds for the same units, and wait
+            // for the whole build directory instead.
+            build_runner.fine_grain_locking = false;
+        }
+        match build_runner.compile(exec) {
+            // With `-Zfine-grain-locking`, another build was still using a
+            // unit we were about to rebuild, so start over once it is done.
+            Err(e) if UnitLockContended::wait(&e, bcx.gctx, attempts)? => attempts += 1,
+            result => return result,
+        }
     }
 }
 
@@ -222,6 +248,7 @@ pub fn create_bcx<'a, 'gctx>(
         ref target_rustc_crate_types,
         rustdoc_document_private_items,
         honor_rust_version,
+        shard,
     } = *options;
     let gctx = ws.gctx();
 
@@ -390,6 +417,10 @@ pub fn create_bcx<'a, 'gctx>(
     };
     let mut units = generator.generate_root_units()?;
 
+    if let Some(shard) = shard {
+        shard.select(&mut units);
+    }
+
     if let Some(args) = target_rustc_crate_types {
         override_rustc_crate_types(&mut units, args, interner)?;
     }
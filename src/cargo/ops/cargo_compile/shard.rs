@@ -0,0 +1,52 @@
+//! See [`Shard`].
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use anyhow::bail;
+
+use crate::core::compiler::Unit;
+use crate::util::errors::CargoResult;
+
+/// One of several disjoint subsets of the root units of a build, as selected
+/// with `cargo test --shard N/M`.
+///
+/// Root units are sorted and then dealt out to the shards in turn. Building
+/// every shard from `1/M` to `M/M` therefore covers each root unit exactly
+/// once, and a shard always selects the same units as long as the set of root
+/// units doesn't change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shard {
+    /// The 1-based index of this shard.
+    index: usize,
+    /// The total number of shards.
+    total: usize,
+}
+
+impl Shard {
+    /// Removes all units from `units` that don't belong to this shard.
+    pub fn select(&self, units: &mut Vec<Unit>) {
+        let mut sorted = units.clone();
+        sorted.sort();
+        let selected: HashSet<_> = sorted
+            .into_iter()
+            .skip(self.index - 1)
+            .step_by(self.total)
+            .collect();
+        units.retain(|unit| selected.contains(unit));
+    }
+}
+
+impl FromStr for Shard {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> CargoResult<Shard> {
+        let shard = s
+            .split_once('/')
+            .and_then(|(index, total)| Some((index.parse().ok()?, total.parse().ok()?)));
+        match shard {
+            Some((index, total)) if 1 <= index && index <= total => Ok(Shard { index, total }),
+            _ => bail!("invalid shard `{s}`, expected `N/M` where N is between 1 and M"),
+        }
+    }
+}
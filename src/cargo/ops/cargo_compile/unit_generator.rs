@@ -158,12 +158,13 @@ impl<'a> UnitGenerator<'a, '_> {
                 } else {
                     UnitFor::new_normal(kind)
                 };
-                let profile = self.profiles.get_profile(
+                let profile = self.profiles.get_profile_for_platform(
                     pkg.package_id(),
                     self.ws.is_member(pkg),
                     is_local,
                     unit_for,
                     kind,
+                    self.target_data,
                 );
                 let kind = kind.for_target(target);
                 self.interner.intern(
@@ -3,10 +3,12 @@
 use crate::util::context::{ConfigKey, ConfigValue as CV, Definition, GlobalContext};
 use crate::util::errors::CargoResult;
 use crate::{drop_eprintln, drop_println};
-use anyhow::{bail, format_err, Error};
+use anyhow::{bail, format_err, Context as _, Error};
+use cargo_util::paths;
 use serde_json::json;
 use std::borrow::Cow;
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 pub enum ConfigFormat {
@@ -312,3 +314,185 @@ fn print_toml_unmerged(
     }
     Ok(())
 }
+
+/// The config file written by `cargo config set` and `cargo config unset`.
+pub enum ConfigLocation {
+    /// `.cargo/config.toml` in the current directory.
+    Project,
+    /// `config.toml` in `$CARGO_HOME`.
+    User,
+    /// A file given on the command line.
+    File(PathBuf),
+}
+
+impl ConfigLocation {
+    fn path(&self, gctx: &GlobalContext) -> CargoResult<PathBuf> {
+        let dir = match self {
+            ConfigLocation::Project => gctx.cwd().join(".cargo"),
+            ConfigLocation::User => gctx.home().as_path_unlocked().to_path_buf(),
+            ConfigLocation::File(path) => return Ok(gctx.cwd().join(path)),
+        };
+        // Prefer an existing `config` file like loading the config does.
+        Ok(gctx
+            .get_file_path(&dir, "config", false)?
+            .unwrap_or_else(|| dir.join("config.toml")))
+    }
+}
+
+/// Options for `cargo config set`.
+pub struct SetOptions<'a> {
+    pub key: &'a str,
+    /// A TOML value, or a string if it isn't valid TOML.
+    pub value: &'a str,
+    pub location: ConfigLocation,
+}
+
+pub fn set(gctx: &GlobalContext, opts: &SetOptions<'_>) -> CargoResult<()> {
+    let path = opts.location.path(gctx)?;
+    let keys = parse_key(opts.key)?;
+    let mut value = match toml_edit::Value::from_str(opts.value) {
+        Ok(value) => value,
+        Err(_) => toml_edit::Value::from(opts.value),
+    };
+    value.decor_mut().clear();
+
+    let mut doc = read_config_document(&path)?;
+    let (last, parents) = keys.split_last().unwrap();
+    let mut table = doc.as_table_mut() as &mut dyn toml_edit::TableLike;
+    for (i, key) in parents.iter().enumerate() {
+        let item = table.entry_format(key).or_insert_with(|| {
+            let mut new_table = toml_edit::Table::new();
+            new_table.set_implicit(true);
+            toml_edit::Item::Table(new_table)
+        });
+        table = item
+            .as_table_like_mut()
+            .ok_or_else(|| not_a_table(&keys[..=i], &path))?;
+    }
+    match table.get_mut(last.get()) {
+        Some(item) if item.is_table_like() => bail!(
+            "`{}` is a table in `{}`, and can't be set to a value",
+            opts.key,
+            path.display()
+        ),
+        Some(toml_edit::Item::Value(existing)) => {
+            // Keep the comments around the existing value.
+            let decor = existing.decor().clone();
+            *existing = value;
+            *existing.decor_mut() = decor;
+        }
+        _ => {
+            table.insert(last.get(), toml_edit::Item::Value(value));
+        }
+    }
+    write_config_document(&path, &doc)
+}
+
+/// Options for `cargo config unset`.
+pub struct UnsetOptions<'a> {
+    pub key: &'a str,
+    pub location: ConfigLocation,
+}
+
+pub fn unset(gctx: &GlobalContext, opts: &UnsetOptions<'_>) -> CargoResult<()> {
+    let path = opts.location.path(gctx)?;
+    let keys = parse_key(opts.key)?;
+    let not_set = || format_err!("`{}` is not set in `{}`", opts.key, path.display());
+    if !path.exists() {
+        return Err(not_set());
+    }
+
+    let mut doc = read_config_document(&path)?;
+    let (last, parents) = keys.split_last().unwrap();
+    let mut table = doc.as_table_mut() as &mut dyn toml_edit::TableLike;
+    for key in parents {
+        table = table
+            .get_mut(key.get())
+            .and_then(|item| item.as_table_like_mut())
+            .ok_or_else(not_set)?;
+    }
+    table.remove(last.get()).ok_or_else(not_set)?;
+    write_config_document(&path, &doc)
+}
+
+/// Prints the effective value of every config key, with where it is defined.
+pub fn list(gctx: &GlobalContext) -> CargoResult<()> {
+    let root = ConfigKey::new();
+    let Some(cv) = gctx.get_cv_with_env(&root)? else {
+        return Ok(());
+    };
+    let opts = GetOptions {
+        key: None,
+        format: ConfigFormat::Toml,
+        show_origin: true,
+        merged: true,
+    };
+    list_leaves(gctx, &opts, &root, &cv)?;
+    if let Some(env) = maybe_env(gctx, &root, &cv) {
+        print_toml_env(gctx, &env);
+    }
+    Ok(())
+}
+
+fn list_leaves(
+    gctx: &GlobalContext,
+    opts: &GetOptions<'_>,
+    key: &ConfigKey,
+    cv: &CV,
+) -> CargoResult<()> {
+    match cv {
+        CV::Table(table, _def) => {
+            let mut key_vals: Vec<_> = table.iter().collect();
+            key_vals.sort_by(|a, b| a.0.cmp(b.0));
+            for (table_key, val) in key_vals {
+                let mut subkey = key.clone();
+                subkey.push(table_key);
+                list_leaves(gctx, opts, &subkey, val)?;
+            }
+        }
+        _ => {
+            // Environment variables may override the value from the files.
+            let cv = gctx.get_cv_with_env(key)?.unwrap_or_else(|| cv.clone());
+            print_toml(gctx, opts, key, &cv);
+        }
+    }
+    Ok(())
+}
+
+/// Parses a config key like `target.'cfg(unix)'.runner` into its parts.
+fn parse_key(key: &str) -> CargoResult<Vec<toml_edit::Key>> {
+    let keys = toml_edit::Key::parse(key).with_context(|| format!("invalid config key `{key}`"))?;
+    if keys.is_empty() {
+        bail!("config key must not be empty");
+    }
+    Ok(keys)
+}
+
+fn read_config_document(path: &Path) -> CargoResult<toml_edit::DocumentMut> {
+    if !path.exists() {
+        return Ok(toml_edit::DocumentMut::new());
+    }
+    let contents = paths::read(path)?;
+    contents
+        .parse()
+        .with_context(|| format!("could not parse TOML configuration in `{}`", path.display()))
+}
+
+fn write_config_document(path: &Path, doc: &toml_edit::DocumentMut) -> CargoResult<()> {
+    if let Some(parent) = path.parent() {
+        paths::create_dir_all(parent)?;
+    }
+    paths::write(path, doc.to_string())
+}
+
+fn not_a_table(keys: &[toml_edit::Key], path: &Path) -> Error {
+    let key = keys
+        .iter()
+        .map(|key| key.display_repr())
+        .collect::<Vec<_>>();
+    format_err!(
+        "expected table for configuration key `{}` in `{}`, but found a value",
+        key.join("."),
+        path.display()
+    )
+}
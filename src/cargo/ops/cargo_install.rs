@@ -1,17 +1,22 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::task::Poll;
 use std::{env, fs};
 
 use crate::core::compiler::{CompileKind, DefaultExecutor, Executor, UnitOutput};
-use crate::core::{Dependency, Edition, Package, PackageId, SourceId, Target, Workspace};
+use crate::core::resolver::features::CliFeatures;
+use crate::core::{
+    Dependency, Edition, GitReference, Package, PackageId, SourceId, Target, Workspace,
+};
 use crate::ops::{common_for_install_and_uninstall::*, FilterRule};
 use crate::ops::{CompileFilter, Packages};
-use crate::sources::source::Source;
+use crate::sources::source::{QueryKind, Source};
 use crate::sources::{GitSource, PathSource, SourceConfigMap};
+use crate::util::cache_lock::CacheLockMode;
 use crate::util::context::FeatureUnification;
 use crate::util::errors::CargoResult;
-use crate::util::{Filesystem, GlobalContext, Rustc};
+use crate::util::{short_hash, Filesystem, GlobalContext, IntoUrl, Rustc, VersionExt};
 use crate::{drop_println, ops};
 
 use anyhow::{bail, Context as _};
@@ -19,6 +24,8 @@ use cargo_util::paths;
 use cargo_util_schemas::core::PartialVersion;
 use itertools::Itertools;
 use semver::VersionReq;
+use serde::Deserialize;
+use serde_untagged::UntaggedEnumVisitor;
 use tempfile::Builder as TempFileBuilder;
 
 struct Transaction {
@@ -759,19 +766,7 @@ pub fn install(
             }
         }
 
-        let mut summary = vec![];
-        if !succeeded.is_empty() {
-            summary.push(format!("Successfully installed {}!", succeeded.join(", ")));
-        }
-        if !failed.is_empty() {
-            summary.push(format!(
-                "Failed to install {} (see error(s) above).",
-                failed.join(", ")
-            ));
-        }
-        if !succeeded.is_empty() || !failed.is_empty() {
-            gctx.shell().status("Summary", summary.join(" "))?;
-        }
+        print_summary(gctx, "install", "installed", &succeeded, &[], &failed)?;
 
         (!succeeded.is_empty(), !failed.is_empty())
     };
@@ -798,6 +793,35 @@ pub fn install(
     Ok(())
 }
 
+/// Prints which of several packages were installed, or updated, and which
+/// failed to be.
+fn print_summary(
+    gctx: &GlobalContext,
+    verb: &str,
+    past: &str,
+    succeeded: &[&str],
+    unchanged: &[&str],
+    failed: &[&str],
+) -> CargoResult<()> {
+    let mut summary = vec![];
+    if !succeeded.is_empty() {
+        summary.push(format!("Successfully {past} {}!", succeeded.join(", ")));
+    }
+    if !unchanged.is_empty() {
+        summary.push(format!("Left {} unchanged.", unchanged.join(", ")));
+    }
+    if !failed.is_empty() {
+        summary.push(format!(
+            "Failed to {verb} {} (see error(s) above).",
+            failed.join(", ")
+        ));
+    }
+    if !summary.is_empty() {
+        gctx.shell().status("Summary", summary.join(" "))?;
+    }
+    Ok(())
+}
+
 fn is_installed(
     pkg: &Package,
     gctx: &GlobalContext,
@@ -882,8 +906,21 @@ fn make_ws_rustc_target<'gctx>(
 }
 
 /// Display a list of installed binaries.
-pub fn install_list(dst: Option<&str>, gctx: &GlobalContext) -> CargoResult<()> {
+///
+/// With `outdated`, only packages with a newer version available are listed,
+/// along with that version.
+pub fn install_list(dst: Option<&str>, gctx: &GlobalContext, outdated: bool) -> CargoResult<()> {
     let root = resolve_root(dst, gctx)?;
+    if outdated {
+        let map = SourceConfigMap::new(gctx)?;
+        for pkg in outdated_packages(gctx, &root, &map)? {
+            drop_println!(gctx, "{} -> {}:", pkg.installed, pkg.describe_latest());
+            for bin in &pkg.bins {
+                drop_println!(gctx, "    {}", bin);
+            }
+        }
+        return Ok(());
+    }
     let tracker = InstallTracker::load(gctx, &root)?;
     for (k, v) in tracker.all_installed_bins() {
         drop_println!(gctx, "{}:", k);
@@ -894,6 +931,390 @@ pub fn install_list(dst: Option<&str>, gctx: &GlobalContext) -> CargoResult<()>
     Ok(())
 }
 
+/// Reinstalls every installed package that has a newer version available,
+/// with the features, profile and target it was installed with.
+pub fn install_update_all(
+    gctx: &GlobalContext,
+    root: Option<&str>,
+    opts: &ops::CompileOptions,
+    dry_run: bool,
+) -> CargoResult<()> {
+    let root = resolve_root(root, gctx)?;
+    let map = SourceConfigMap::new(gctx)?;
+    let outdated = outdated_packages(gctx, &root, &map)?;
+    if outdated.is_empty() {
+        gctx.shell().note("all installed packages are up to date")?;
+        return Ok(());
+    }
+
+    let rustc = gctx.load_global_rustc(None)?;
+    let current_rust_version = if opts.honor_rust_version.unwrap_or(true) {
+        Some(rustc.version.clone().into())
+    } else {
+        None
+    };
+
+    let mut succeeded = vec![];
+    let mut unchanged = vec![];
+    let mut failed = vec![];
+    for pkg in &outdated {
+        let name = pkg.installed.name();
+        let result = (|| -> CargoResult<bool> {
+            let mut opts = opts.clone();
+            pkg.info.apply_to(&mut opts, &rustc.host)?;
+            let vers = pkg
+                .info
+                .version_req()
+                .map(|v| v.parse::<VersionReq>())
+                .transpose()?;
+            let installable_pkg = InstallablePackage::new(
+                gctx,
+                root.clone(),
+                map.clone(),
+                Some(name.as_str()),
+                pkg.latest.source_id().without_precise(),
+                false,
+                vers.as_ref(),
+                &opts,
+                false,
+                false,
+                true,
+                current_rust_version.as_ref(),
+                None,
+            )?;
+            match installable_pkg {
+                Some(installable_pkg) => installable_pkg.install_one(dry_run),
+                None => Ok(false),
+            }
+        })();
+        match result {
+            Ok(true) => succeeded.push(name.as_str()),
+            // Nothing was installed, like when the binaries of the latest
+            // version need features that aren't enabled.
+            Ok(false) => unchanged.push(name.as_str()),
+            Err(e) => {
+                crate::display_error(&e, &mut gctx.shell());
+                failed.push(name.as_str());
+            }
+        }
+    }
+
+    print_summary(gctx, "update", "updated", &succeeded, &unchanged, &failed)?;
+
+    if !failed.is_empty() {
+        bail!("some crates failed to update");
+    }
+    Ok(())
+}
+
+/// The file listing the tools of a workspace, next to its root manifest.
+const TOOLS_MANIFEST: &str = "tools.toml";
+
+/// Installs every tool listed in the [`TOOLS_MANIFEST`] of `ws`.
+///
+/// Tools are installed into `root`, or by default into a directory of the
+/// workspace under `$CARGO_HOME/tools`, so that each workspace gets the
+/// versions it asks for. That directory is outside of the target directory,
+/// so that `cargo clean` keeps the tools. Tools already installed there at
+/// the requested version are skipped.
+pub fn install_from_manifest(
+    ws: &Workspace<'_>,
+    root: Option<&str>,
+    opts: &ops::CompileOptions,
+    force: bool,
+    dry_run: bool,
+) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let path = ws.root().join(TOOLS_MANIFEST);
+    let tools = load_tools_manifest(&path, gctx)?;
+    if tools.is_empty() {
+        gctx.shell()
+            .note(format!("no tools listed in `{}`", path.display()))?;
+        return Ok(());
+    }
+    let root = match root {
+        Some(root) => Filesystem::new(PathBuf::from(root)),
+        None => {
+            let name = ws
+                .root()
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .unwrap_or_default();
+            let dir = format!("{name}-{}", short_hash(&ws.root()));
+            gctx.home().join("tools").join(dir)
+        }
+    };
+    let map = SourceConfigMap::new(gctx)?;
+
+    let current_rust_version = if opts.honor_rust_version.unwrap_or(true) {
+        let rustc = gctx.load_global_rustc(None)?;
+        Some(rustc.version.clone().into())
+    } else {
+        None
+    };
+
+    let mut succeeded = vec![];
+    let mut failed = vec![];
+    // Sources already updated, to avoid updating them once per tool.
+    let mut updated = HashSet::new();
+    for tool in &tools {
+        let result = (|| -> CargoResult<bool> {
+            let mut opts = opts.clone();
+            opts.cli_features = tool.features.clone();
+            let installable_pkg = InstallablePackage::new(
+                gctx,
+                root.clone(),
+                map.clone(),
+                Some(tool.name.as_str()),
+                tool.source_id,
+                false,
+                tool.vers.as_ref(),
+                &opts,
+                force,
+                false,
+                updated.insert(tool.source_id),
+                current_rust_version.as_ref(),
+                None,
+            )?;
+            match installable_pkg {
+                Some(installable_pkg) => installable_pkg.install_one(dry_run),
+                None => Ok(false),
+            }
+        })();
+        match result {
+            Ok(_) => succeeded.push(tool.name.as_str()),
+            Err(e) => {
+                crate::display_error(&e, &mut gctx.shell());
+                failed.push(tool.name.as_str());
+            }
+        }
+    }
+
+    print_summary(gctx, "install", "installed", &succeeded, &[], &failed)?;
+
+    if !failed.is_empty() {
+        bail!("some tools failed to install");
+    }
+    Ok(())
+}
+
+/// A tool listed in a [`TOOLS_MANIFEST`].
+struct Tool {
+    name: String,
+    source_id: SourceId,
+    vers: Option<VersionReq>,
+    features: CliFeatures,
+}
+
+/// The contents of a [`TOOLS_MANIFEST`].
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlToolsManifest {
+    #[serde(default)]
+    tools: BTreeMap<String, TomlTool>,
+}
+
+/// A tool, either as its version or as a table of details.
+enum TomlTool {
+    Simple(String),
+    Detailed(TomlDetailedTool),
+}
+
+impl<'de> Deserialize<'de> for TomlTool {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        UntaggedEnumVisitor::new()
+            .string(|version| Ok(TomlTool::Simple(version.to_owned())))
+            .map(|map| map.deserialize().map(TomlTool::Detailed))
+            .deserialize(deserializer)
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct TomlDetailedTool {
+    version: Option<String>,
+    registry: Option<String>,
+    git: Option<String>,
+    branch: Option<String>,
+    tag: Option<String>,
+    rev: Option<String>,
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(default)]
+    all_features: bool,
+    default_features: Option<bool>,
+}
+
+/// Reads the tools listed in the [`TOOLS_MANIFEST`] at `path`.
+fn load_tools_manifest(path: &Path, gctx: &GlobalContext) -> CargoResult<Vec<Tool>> {
+    let contents = paths::read(path)?;
+    let manifest: TomlToolsManifest = toml::from_str(&contents)
+        .with_context(|| format!("failed to parse `{}`", path.display()))?;
+    manifest
+        .tools
+        .into_iter()
+        .map(|(name, tool)| {
+            let tool = match tool {
+                TomlTool::Simple(version) => TomlDetailedTool {
+                    version: Some(version),
+                    ..Default::default()
+                },
+                TomlTool::Detailed(tool) => tool,
+            };
+            let source_id = tool_source_id(&tool, gctx)
+                .with_context(|| format!("invalid tool `{name}` in `{}`", path.display()))?;
+            let vers =
+                match tool.version.as_deref() {
+                    Some(version) => Some(parse_tool_version(version).with_context(|| {
+                        format!("invalid tool `{name}` in `{}`", path.display())
+                    })?),
+                    None if source_id.is_registry() => bail!(
+                        "tool `{name}` in `{}` must specify a `version`",
+                        path.display()
+                    ),
+                    None => None,
+                };
+            let features = CliFeatures::from_command_line(
+                &tool.features,
+                tool.all_features,
+                tool.default_features.unwrap_or(true),
+            )?;
+            Ok(Tool {
+                name,
+                source_id,
+                vers,
+                features,
+            })
+        })
+        .collect()
+}
+
+fn tool_source_id(tool: &TomlDetailedTool, gctx: &GlobalContext) -> CargoResult<SourceId> {
+    let Some(git) = &tool.git else {
+        if tool.branch.is_some() || tool.tag.is_some() || tool.rev.is_some() {
+            bail!("`branch`, `tag` and `rev` can only be used with `git`");
+        }
+        return match &tool.registry {
+            Some(registry) => SourceId::alt_registry(gctx, registry),
+            None => SourceId::crates_io(gctx),
+        };
+    };
+    if tool.registry.is_some() {
+        bail!("`git` and `registry` cannot be used together");
+    }
+    let reference = match (&tool.branch, &tool.tag, &tool.rev) {
+        (None, None, None) => GitReference::DefaultBranch,
+        (Some(branch), None, None) => GitReference::Branch(branch.clone()),
+        (None, Some(tag), None) => GitReference::Tag(tag.clone()),
+        (None, None, Some(rev)) => GitReference::Rev(rev.clone()),
+        _ => bail!("only one of `branch`, `tag` or `rev` can be used"),
+    };
+    SourceId::for_git(&git.into_url()?, reference)
+}
+
+/// Parses `x.y.z` as `=x.y.z`, like `cargo install --version` does, so
+/// that tools are pinned to the version they list.
+fn parse_tool_version(version: &str) -> CargoResult<VersionReq> {
+    if let Ok(version) = version.trim().parse::<semver::Version>() {
+        return Ok(version.to_exact_req());
+    }
+    version
+        .parse::<VersionReq>()
+        .with_context(|| format!("invalid version requirement `{version}`"))
+}
+
+/// An installed package with a newer version available in its source.
+struct OutdatedPackage {
+    installed: PackageId,
+    latest: PackageId,
+    bins: BTreeSet<String>,
+    info: InstallInfo,
+}
+
+impl OutdatedPackage {
+    /// Describes the newer version, along with its commit for git sources.
+    fn describe_latest(&self) -> String {
+        match self.latest.source_id().precise_git_fragment() {
+            Some(rev) if self.latest.source_id().is_git() => {
+                let len = rev.len().min(8);
+                format!("v{} (#{})", self.latest.version(), &rev[..len])
+            }
+            _ => format!("v{}", self.latest.version()),
+        }
+    }
+}
+
+/// Queries the source of every installed package for a newer version.
+///
+/// Packages installed from a path are skipped, as they have no other
+/// version to compare with. Packages installed with `--version` only look
+/// for newer versions matching it.
+fn outdated_packages(
+    gctx: &GlobalContext,
+    root: &Filesystem,
+    map: &SourceConfigMap<'_>,
+) -> CargoResult<Vec<OutdatedPackage>> {
+    // The tracker is locked for as long as it is loaded, and installing a
+    // package loads it again, so only keep a copy of what is needed.
+    let installs: Vec<_> = {
+        let tracker = InstallTracker::load(gctx, root)?;
+        tracker
+            .all_installs()
+            .filter(|(pkg_id, _)| !pkg_id.source_id().is_path())
+            .map(|(pkg_id, info)| {
+                let bins = tracker.installed_bins(*pkg_id).cloned().unwrap_or_default();
+                (*pkg_id, bins, info.clone())
+            })
+            .collect()
+    };
+
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+    let mut outdated = Vec::new();
+    for (installed, bins, info) in installs {
+        let source_id = installed.source_id();
+        let vers = match info.version_req() {
+            Some(vers) => Some(vers),
+            // Avoid pre-release versions unless explicitly asked for, like
+            // when installing from a registry.
+            None if source_id.is_registry() => Some("*"),
+            None => None,
+        };
+        let dep = Dependency::parse(installed.name(), vers, source_id.without_precise())?;
+        let mut source: Box<dyn Source + '_> = if source_id.is_git() {
+            Box::new(GitSource::new(source_id.without_precise(), gctx)?)
+        } else {
+            map.load(source_id, &HashSet::new())?
+        };
+        source.invalidate_cache();
+        let summaries = loop {
+            match source.query_vec(&dep, QueryKind::Exact)? {
+                Poll::Ready(summaries) => break summaries,
+                Poll::Pending => source.block_until_ready()?,
+            }
+        };
+        let Some(latest) = summaries.iter().map(|s| s.package_id()).max() else {
+            continue;
+        };
+        let is_outdated = if source_id.is_git() {
+            !latest.source_id().has_same_precise_as(source_id)
+        } else {
+            latest.version() > installed.version()
+        };
+        if is_outdated {
+            outdated.push(OutdatedPackage {
+                installed,
+                latest,
+                bins,
+                info,
+            });
+        }
+    }
+    Ok(outdated)
+}
+
 /// Removes executables that are no longer part of a package that was
 /// previously installed.
 fn remove_orphaned_bins(
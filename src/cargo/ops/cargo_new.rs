@@ -1,8 +1,9 @@
-use crate::core::{Edition, Shell, Workspace};
+use crate::core::{Edition, GitReference, Shell, Workspace};
+use crate::sources::git::GitRemote;
 use crate::util::errors::CargoResult;
 use crate::util::important_paths::find_root_manifest_for_wd;
 use crate::util::{existing_vcs_repo, FossilRepo, GitRepo, HgRepo, PijulRepo};
-use crate::util::{restricted_names, GlobalContext};
+use crate::util::{restricted_names, GlobalContext, IntoUrl};
 use anyhow::{anyhow, Context as _};
 use cargo_util::paths::{self, write_atomic};
 use cargo_util_schemas::manifest::PackageName;
@@ -60,6 +61,8 @@ pub struct NewOptions {
     pub name: Option<String>,
     pub edition: Option<String>,
     pub registry: Option<String>,
+    /// Path or git URL of a template to create the package from
+    pub template: Option<String>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -96,6 +99,8 @@ struct MkOptions<'a> {
     source_files: Vec<SourceFileInformation>,
     edition: Option<&'a str>,
     registry: Option<&'a str>,
+    /// Local directory of the template to copy, if any.
+    template: Option<&'a Path>,
 }
 
 impl NewOptions {
@@ -107,6 +112,7 @@ impl NewOptions {
         name: Option<String>,
         edition: Option<String>,
         registry: Option<String>,
+        template: Option<String>,
     ) -> CargoResult<NewOptions> {
         let auto_detect_kind = !bin && !lib;
 
@@ -124,6 +130,7 @@ impl NewOptions {
             name,
             edition,
             registry,
+            template,
         };
         Ok(opts)
     }
@@ -434,8 +441,15 @@ fn calculate_new_project_kind(
 pub fn new(opts: &NewOptions, gctx: &GlobalContext) -> CargoResult<()> {
     let path = &opts.path;
     let name = get_name(path, opts)?;
-    gctx.shell()
-        .status("Creating", format!("{} `{}` package", opts.kind, name))?;
+    match &opts.template {
+        Some(template) => gctx.shell().status(
+            "Creating",
+            format!("`{}` package from template `{}`", name, template),
+        )?,
+        None => gctx
+            .shell()
+            .status("Creating", format!("{} `{}` package", opts.kind, name))?,
+    }
 
     if path.exists() {
         anyhow::bail!(
@@ -450,13 +464,24 @@ pub fn new(opts: &NewOptions, gctx: &GlobalContext) -> CargoResult<()> {
 
     check_name(name, opts.name.is_none(), is_bin, &mut gctx.shell())?;
 
+    let template = opts
+        .template
+        .as_deref()
+        .map(|template| Template::fetch(template, gctx))
+        .transpose()?;
+    let source_files = if template.is_some() {
+        Vec::new()
+    } else {
+        vec![plan_new_source_file(opts.kind.is_bin())]
+    };
     let mkopts = MkOptions {
         version_control: opts.version_control,
         path,
         name,
-        source_files: vec![plan_new_source_file(opts.kind.is_bin())],
+        source_files,
         edition: opts.edition.as_deref(),
         registry: opts.registry.as_deref(),
+        template: template.as_ref().map(Template::path),
     };
 
     mk(gctx, &mkopts).with_context(|| {
@@ -480,8 +505,14 @@ pub fn init(opts: &NewOptions, gctx: &GlobalContext) -> CargoResult<NewProjectKi
     let mut src_paths_types = vec![];
     detect_source_paths_and_types(path, name, &mut src_paths_types)?;
     let kind = calculate_new_project_kind(opts.kind, opts.auto_detect_kind, &src_paths_types);
-    gctx.shell()
-        .status("Creating", format!("{} package", opts.kind))?;
+    match &opts.template {
+        Some(template) => gctx
+            .shell()
+            .status("Creating", format!("package from template `{}`", template))?,
+        None => gctx
+            .shell()
+            .status("Creating", format!("{} package", opts.kind))?,
+    }
 
     if path.join("Cargo.toml").exists() {
         anyhow::bail!("`cargo init` cannot be run on existing Cargo packages")
@@ -490,7 +521,11 @@ pub fn init(opts: &NewOptions, gctx: &GlobalContext) -> CargoResult<NewProjectKi
 
     let has_bin = kind.is_bin();
 
-    if src_paths_types.is_empty() {
+    if opts.template.is_some() {
+        // The template's `Cargo.toml` describes the targets, and existing
+        // files are kept as they are.
+        src_paths_types.clear();
+    } else if src_paths_types.is_empty() {
         src_paths_types.push(plan_new_source_file(has_bin));
     } else if src_paths_types.len() == 1 && !src_paths_types.iter().any(|x| x.bin == has_bin) {
         // we've found the only file and it's not the type user wants. Change the type and warn
@@ -553,6 +588,11 @@ pub fn init(opts: &NewOptions, gctx: &GlobalContext) -> CargoResult<NewProjectKi
         }
     }
 
+    let template = opts
+        .template
+        .as_deref()
+        .map(|template| Template::fetch(template, gctx))
+        .transpose()?;
     let mkopts = MkOptions {
         version_control,
         path,
@@ -560,6 +600,7 @@ pub fn init(opts: &NewOptions, gctx: &GlobalContext) -> CargoResult<NewProjectKi
         source_files: src_paths_types,
         edition: opts.edition.as_deref(),
         registry: opts.registry.as_deref(),
+        template: template.as_ref().map(Template::path),
     };
 
     mk(gctx, &mkopts).with_context(|| {
@@ -759,50 +800,36 @@ fn mk(gctx: &GlobalContext, opts: &MkOptions<'_>) -> CargoResult<()> {
     });
 
     init_vcs(path, vcs, gctx)?;
-    write_ignore_file(path, &ignore, vcs)?;
-
-    // Create `Cargo.toml` file with necessary `[lib]` and `[[bin]]` sections, if needed.
-    let mut manifest = toml_edit::DocumentMut::new();
-    manifest["package"] = toml_edit::Item::Table(toml_edit::Table::new());
-    manifest["package"]["name"] = toml_edit::value(name);
-    manifest["package"]["version"] = toml_edit::value("0.1.0");
     let edition = match opts.edition {
         Some(edition) => edition.to_string(),
         None => Edition::LATEST_STABLE.to_string(),
     };
-    manifest["package"]["edition"] = toml_edit::value(edition);
-    if let Some(registry) = opts.registry {
-        let mut array = toml_edit::Array::default();
-        array.push(registry);
-        manifest["package"]["publish"] = toml_edit::value(array);
+    if let Some(template) = opts.template {
+        copy_template(template, path, name, &edition)?;
     }
-    let dep_table = toml_edit::Table::default();
-    manifest["dependencies"] = toml_edit::Item::Table(dep_table);
+    write_ignore_file(path, &ignore, vcs)?;
 
-    // Calculate what `[lib]` and `[[bin]]`s we need to append to `Cargo.toml`.
-    for i in &opts.source_files {
-        if i.bin {
-            if i.relative_path != "src/main.rs" {
-                let mut bin = toml_edit::Table::new();
-                bin["name"] = toml_edit::value(name);
-                bin["path"] = toml_edit::value(i.relative_path.clone());
-                manifest["bin"]
-                    .or_insert(toml_edit::Item::ArrayOfTables(
-                        toml_edit::ArrayOfTables::new(),
-                    ))
-                    .as_array_of_tables_mut()
-                    .expect("bin is an array of tables")
-                    .push(bin);
-            }
-        } else if i.relative_path != "src/lib.rs" {
-            let mut lib = toml_edit::Table::new();
-            lib["path"] = toml_edit::value(i.relative_path.clone());
-            manifest["lib"] = toml_edit::Item::Table(lib);
+    let manifest_path = paths::normalize_path(&path.join("Cargo.toml"));
+    let mut manifest = if opts.template.is_some() {
+        // The template's `Cargo.toml` is used as is, apart from the edition
+        // if one was given.
+        let mut manifest = paths::read(&manifest_path)?
+            .parse::<toml_edit::DocumentMut>()
+            .context("failed to parse the `Cargo.toml` of the template")?;
+        if let Some(edition) = opts.edition {
+            manifest["package"]["edition"] = toml_edit::value(edition);
         }
-    }
+        manifest
+    } else {
+        new_manifest(opts, &edition)
+    };
 
-    let manifest_path = paths::normalize_path(&path.join("Cargo.toml"));
-    if let Ok(root_manifest_path) = find_root_manifest_for_wd(&manifest_path) {
+    // Look for a workspace above the package, as a template already put a
+    // `Cargo.toml` next to the package's own one.
+    let package_dir = manifest_path.parent().unwrap();
+    if let Ok(root_manifest_path) =
+        find_root_manifest_for_wd(package_dir.parent().unwrap_or(package_dir))
+    {
         let root_manifest = paths::read(&root_manifest_path)?;
         // Sometimes the root manifest is not a valid manifest, so we only try to parse it if it is.
         // This should not block the creation of the new project. It is only a best effort to
@@ -812,26 +839,29 @@ fn mk(gctx: &GlobalContext, opts: &MkOptions<'_>) -> CargoResult<()> {
             let can_be_a_member = can_be_workspace_member(&display_path, &workspace_document)?;
             // Only try to inherit the workspace stuff if the new package can be a member of the workspace.
             if can_be_a_member {
-                if let Some(workspace_package_keys) = workspace_document
-                    .get("workspace")
-                    .and_then(|workspace| workspace.get("package"))
-                    .and_then(|package| package.as_table())
-                {
-                    update_manifest_with_inherited_workspace_package_keys(
-                        opts,
-                        &mut manifest,
-                        workspace_package_keys,
-                    )
-                }
-                // Try to inherit the workspace lints key if it exists.
-                if workspace_document
-                    .get("workspace")
-                    .and_then(|workspace| workspace.get("lints"))
-                    .is_some()
-                {
-                    let mut table = toml_edit::Table::new();
-                    table["workspace"] = toml_edit::value(true);
-                    manifest["lints"] = toml_edit::Item::Table(table);
+                // The manifest of a template is used as its author wrote it.
+                if opts.template.is_none() {
+                    if let Some(workspace_package_keys) = workspace_document
+                        .get("workspace")
+                        .and_then(|workspace| workspace.get("package"))
+                        .and_then(|package| package.as_table())
+                    {
+                        update_manifest_with_inherited_workspace_package_keys(
+                            opts,
+                            &mut manifest,
+                            workspace_package_keys,
+                        )
+                    }
+                    // Try to inherit the workspace lints key if it exists.
+                    if workspace_document
+                        .get("workspace")
+                        .and_then(|workspace| workspace.get("lints"))
+                        .is_some()
+                    {
+                        let mut table = toml_edit::Table::new();
+                        table["workspace"] = toml_edit::value(true);
+                        manifest["lints"] = toml_edit::Item::Table(table);
+                    }
                 }
 
                 // Try to add the new package to the workspace members.
@@ -921,6 +951,145 @@ mod tests {
     Ok(())
 }
 
+/// Creates the default `Cargo.toml`, with the `[lib]` and `[[bin]]` sections
+/// needed by the source files.
+fn new_manifest(opts: &MkOptions<'_>, edition: &str) -> toml_edit::DocumentMut {
+    let name = opts.name;
+    let mut manifest = toml_edit::DocumentMut::new();
+    manifest["package"] = toml_edit::Item::Table(toml_edit::Table::new());
+    manifest["package"]["name"] = toml_edit::value(name);
+    manifest["package"]["version"] = toml_edit::value("0.1.0");
+    manifest["package"]["edition"] = toml_edit::value(edition);
+    if let Some(registry) = opts.registry {
+        let mut array = toml_edit::Array::default();
+        array.push(registry);
+        manifest["package"]["publish"] = toml_edit::value(array);
+    }
+    let dep_table = toml_edit::Table::default();
+    manifest["dependencies"] = toml_edit::Item::Table(dep_table);
+
+    // Calculate what `[lib]` and `[[bin]]`s we need to append to `Cargo.toml`.
+    for i in &opts.source_files {
+        if i.bin {
+            if i.relative_path != "src/main.rs" {
+                let mut bin = toml_edit::Table::new();
+                bin["name"] = toml_edit::value(name);
+                bin["path"] = toml_edit::value(i.relative_path.clone());
+                manifest["bin"]
+                    .or_insert(toml_edit::Item::ArrayOfTables(
+                        toml_edit::ArrayOfTables::new(),
+                    ))
+                    .as_array_of_tables_mut()
+                    .expect("bin is an array of tables")
+                    .push(bin);
+            }
+        } else if i.relative_path != "src/lib.rs" {
+            let mut lib = toml_edit::Table::new();
+            lib["path"] = toml_edit::value(i.relative_path.clone());
+            manifest["lib"] = toml_edit::Item::Table(lib);
+        }
+    }
+    manifest
+}
+
+/// A template to create a package from, with `--template`.
+enum Template {
+    /// A local directory.
+    Path(PathBuf),
+    /// A checkout of a git repository in a temporary directory, removed once
+    /// the package is created.
+    Git {
+        checkout: PathBuf,
+        _tmp: tempfile::TempDir,
+    },
+}
+
+impl Template {
+    /// Finds the template at `template`, which is either a local directory or
+    /// the URL of a git repository to clone.
+    fn fetch(template: &str, gctx: &GlobalContext) -> CargoResult<Template> {
+        let path = gctx.cwd().join(template);
+        if path.is_dir() {
+            return Template::Path(path).validate(template);
+        }
+        let url = match template.into_url() {
+            Ok(url) if !path.exists() => url,
+            _ => anyhow::bail!("template `{}` is not a directory or a git URL", template),
+        };
+        gctx.shell()
+            .status("Updating", format!("git repository `{}`", url))?;
+        let tmp = tempfile::Builder::new()
+            .prefix("cargo-template")
+            .tempdir()?;
+        let checkout = tmp.path().join("checkout");
+        let remote = GitRemote::new(&url);
+        let (db, rev) = remote
+            .checkout(
+                &tmp.path().join("db"),
+                None,
+                &GitReference::DefaultBranch,
+                gctx,
+            )
+            .with_context(|| format!("failed to fetch template `{}`", template))?;
+        db.copy_to(rev, &checkout, gctx)?;
+        Template::Git {
+            checkout,
+            _tmp: tmp,
+        }
+        .validate(template)
+    }
+
+    fn validate(self, template: &str) -> CargoResult<Template> {
+        if !self.path().join("Cargo.toml").is_file() {
+            anyhow::bail!("template `{}` does not contain a `Cargo.toml`", template);
+        }
+        Ok(self)
+    }
+
+    /// The directory holding the files of the template.
+    fn path(&self) -> &Path {
+        match self {
+            Template::Path(path) => path,
+            Template::Git { checkout, .. } => checkout,
+        }
+    }
+}
+
+/// Copies the files of the `template` directory into `path`, except for
+/// those already there, replacing `{{name}}` and `{{edition}}` in their names
+/// and contents.
+fn copy_template(template: &Path, path: &Path, name: &str, edition: &str) -> CargoResult<()> {
+    let substitute = |s: &str| s.replace("{{name}}", name).replace("{{edition}}", edition);
+    let entries = walkdir::WalkDir::new(template)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| entry.depth() > 1 || entry.file_name() != ".git");
+    for entry in entries {
+        let entry =
+            entry.with_context(|| format!("failed to read template `{}`", template.display()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(template)?;
+        let dst = path.join(substitute(&relative.to_string_lossy()));
+        if dst.exists() {
+            continue;
+        }
+        if let Some(parent) = dst.parent() {
+            paths::create_dir_all(parent)?;
+        }
+        paths::copy(entry.path(), &dst)?;
+        // Files that aren't UTF-8, like images, are copied as they are.
+        if let Ok(contents) = std::str::from_utf8(&paths::read_bytes(&dst)?) {
+            let substituted = substitute(contents);
+            if substituted != contents {
+                paths::write(&dst, substituted)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 // Update the manifest with the inherited workspace package keys.
 // If the option is not set, the key is removed from the manifest.
 // If the option is set, keep the value from the manifest.
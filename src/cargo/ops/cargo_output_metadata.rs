@@ -1,24 +1,34 @@
 use crate::core::compiler::artifact::match_artifacts_kind_with_targets;
-use crate::core::compiler::{CompileKind, CompileKindFallback, RustcTargetData};
+use crate::core::compiler::{
+    self, BuildRunner, CompileKind, CompileKindFallback, CompileMode, FileFlavor, RustcTargetData,
+    UnitInterner,
+};
 use crate::core::dependency::DepKind;
 use crate::core::package::SerializedPackage;
+use crate::core::profiles::Profile;
 use crate::core::resolver::{features::CliFeatures, HasDevUnits, Resolve};
-use crate::core::{Package, PackageId, PackageIdSpec, Workspace};
-use crate::ops::{self, Packages};
+use crate::core::{Package, PackageId, PackageIdSpec, Target, Workspace};
+use crate::ops::{self, CompileOptions, Packages};
 use crate::util::interning::InternedString;
 use crate::util::CargoResult;
 use cargo_platform::Platform;
 use serde::Serialize;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 const VERSION: u32 = 1;
 
+/// The version of the `units` output, which changes independently of
+/// [`VERSION`] while it is unstable.
+const UNITS_VERSION: u32 = 1;
+
 pub struct OutputMetadataOptions {
     pub cli_features: CliFeatures,
     pub no_deps: bool,
     pub version: u32,
     pub filter_platforms: Vec<String>,
+    /// If set, also outputs the units of a build with these options.
+    pub units: Option<CompileOptions>,
 }
 
 /// Loads the manifest, resolves the dependencies of the package to the concrete
@@ -42,6 +52,11 @@ pub fn output_metadata(ws: &Workspace<'_>, opt: &OutputMetadataOptions) -> Cargo
         let (packages, resolve) = build_resolve_graph(ws, opt)?;
         (packages, Some(resolve))
     };
+    let units = opt
+        .units
+        .as_ref()
+        .map(|compile_opts| build_units(ws, compile_opts))
+        .transpose()?;
 
     Ok(ExportInfo {
         packages,
@@ -55,6 +70,7 @@ pub fn output_metadata(ws: &Workspace<'_>, opt: &OutputMetadataOptions) -> Cargo
         version: VERSION,
         workspace_root: ws.root().to_path_buf(),
         metadata: ws.custom_metadata().cloned(),
+        units,
     })
 }
 
@@ -71,6 +87,62 @@ pub struct ExportInfo {
     version: u32,
     workspace_root: PathBuf,
     metadata: Option<toml::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    units: Option<MetadataUnits>,
+}
+
+/// The units of a build, along with how they are compiled.
+///
+/// This mirrors the output of `--unit-graph`, with additional details.
+#[derive(Serialize)]
+struct MetadataUnits {
+    version: u32,
+    units: Vec<MetadataUnit>,
+    roots: Vec<usize>,
+}
+
+#[derive(Serialize)]
+struct MetadataUnit {
+    pkg_id: PackageIdSpec,
+    target: Target,
+    profile: Profile,
+    platform: CompileKind,
+    mode: CompileMode,
+    features: Vec<InternedString>,
+    /// Flags from the environment and config, see `TargetInfo::rustflags`.
+    ///
+    /// Flags of the profile are part of `profile`.
+    rustflags: Vec<String>,
+    /// Environment variables set for the unit from the config and the
+    /// profile, in addition to the ones Cargo always sets.
+    env: BTreeMap<String, String>,
+    outputs: Vec<MetadataUnitOutput>,
+    /// The `OUT_DIR` of a build script, set for the unit running it.
+    out_dir: Option<PathBuf>,
+    /// The `rustc` or `rustdoc` command compiling the unit, without the
+    /// flags from build scripts, see `compiler::unit_command`.
+    command: Option<MetadataUnitCommand>,
+    dependencies: Vec<MetadataUnitDep>,
+}
+
+#[derive(Serialize)]
+struct MetadataUnitCommand {
+    program: String,
+    args: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct MetadataUnitOutput {
+    path: PathBuf,
+    /// Where the file is uplifted to, like `target/debug`.
+    hardlink: Option<PathBuf>,
+    flavor: &'static str,
+}
+
+#[derive(Serialize)]
+struct MetadataUnitDep {
+    index: usize,
+    extern_crate_name: InternedString,
 }
 
 #[derive(Serialize)]
@@ -363,3 +435,93 @@ fn build_resolve_graph_r(
 
     Ok(())
 }
+
+/// Computes the units of a build with `compile_opts`, without building them.
+fn build_units(ws: &Workspace<'_>, compile_opts: &CompileOptions) -> CargoResult<MetadataUnits> {
+    let interner = UnitInterner::new();
+    let bcx = ops::create_bcx(ws, compile_opts, &interner)?;
+    let mut build_runner = BuildRunner::new(&bcx)?;
+    build_runner.prepare_units_unlocked()?;
+
+    let mut units: Vec<_> = bcx.unit_graph.iter().collect();
+    units.sort_unstable();
+    let indices: HashMap<_, _> = units
+        .iter()
+        .enumerate()
+        .map(|(i, (unit, _))| (*unit, i))
+        .collect();
+    let roots = bcx.roots.iter().map(|root| indices[root]).collect();
+
+    let env_config = ws.gctx().env_config()?;
+    let mut metadata_units = Vec::new();
+    for (unit, unit_deps) in units {
+        let mut env: BTreeMap<_, _> = env_config
+            .iter()
+            .map(|(k, v)| (k.clone(), v.to_string_lossy().into_owned()))
+            .collect();
+        env.extend(
+            unit.profile
+                .env
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        let outputs = build_runner
+            .outputs(unit)?
+            .iter()
+            .map(|output| MetadataUnitOutput {
+                path: output.path.clone(),
+                hardlink: output.hardlink.clone(),
+                flavor: match output.flavor {
+                    FileFlavor::Normal => "normal",
+                    FileFlavor::Auxiliary => "auxiliary",
+                    FileFlavor::Linkable => "linkable",
+                    FileFlavor::Rmeta => "rmeta",
+                    FileFlavor::DebugInfo => "debuginfo",
+                    FileFlavor::Sbom => "sbom",
+                },
+            })
+            .collect();
+        let out_dir = unit
+            .mode
+            .is_run_custom_build()
+            .then(|| build_runner.files().build_script_out_dir(unit));
+        let command = compiler::unit_command(&build_runner, unit)?.map(|cmd| MetadataUnitCommand {
+            program: cmd.get_program().to_string_lossy().into_owned(),
+            args: cmd
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+        });
+        let dependencies = unit_deps
+            .iter()
+            .map(|unit_dep| MetadataUnitDep {
+                index: indices[&unit_dep.unit],
+                extern_crate_name: unit_dep.extern_crate_name,
+            })
+            .collect();
+        metadata_units.push(MetadataUnit {
+            pkg_id: unit.pkg.package_id().to_spec(),
+            target: unit.target.clone(),
+            profile: unit.profile.clone(),
+            platform: unit.kind,
+            mode: unit.mode,
+            features: unit.features.clone(),
+            rustflags: if unit.mode.is_doc() {
+                unit.rustdocflags.to_vec()
+            } else {
+                unit.rustflags.to_vec()
+            },
+            env,
+            outputs,
+            out_dir,
+            command,
+            dependencies,
+        });
+    }
+
+    Ok(MetadataUnits {
+        version: UNITS_VERSION,
+        units: metadata_units,
+        roots,
+    })
+}
@@ -115,6 +115,7 @@ pub fn run_verify(
             target_rustc_crate_types: None,
             rustdoc_document_private_items: false,
             honor_rust_version: None,
+            shard: None,
         },
         &exec,
     )?;
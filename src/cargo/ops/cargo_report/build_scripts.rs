@@ -0,0 +1,44 @@
+//! Implementation of `cargo report build-scripts`.
+
+use crate::core::compiler::build_report::BuildReport;
+use crate::core::Workspace;
+use crate::drop_println;
+use crate::util::CargoResult;
+
+/// Shows what the build scripts of the last build of `ws` did, slowest
+/// first.
+pub fn report_build_scripts(ws: &Workspace<'_>) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let mut report = BuildReport::load(ws)?;
+    if report.build_scripts.is_empty() {
+        drop_println!(gctx, "the last build had no build scripts");
+        return Ok(());
+    }
+    report.build_scripts.sort_by(|a, b| {
+        b.duration
+            .unwrap_or(-1.0)
+            .total_cmp(&a.duration.unwrap_or(-1.0))
+    });
+    for script in &report.build_scripts {
+        match script.duration {
+            Some(duration) => drop_println!(gctx, "{}: ran in {duration:.2}s", script.package),
+            None => drop_println!(gctx, "{}: fresh", script.package),
+        }
+        if script.rerun_if_changed.is_empty() && script.rerun_if_env_changed.is_empty() {
+            drop_println!(
+                gctx,
+                "  no rerun-if instructions, reruns when any file of the package changes"
+            );
+        }
+        for path in &script.rerun_if_changed {
+            drop_println!(gctx, "  rerun-if-changed: {}", path.display());
+        }
+        for var in &script.rerun_if_env_changed {
+            drop_println!(gctx, "  rerun-if-env-changed: {var}");
+        }
+        for cfg in &script.cfgs {
+            drop_println!(gctx, "  cfg: {cfg}");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,14 @@
+//! Implementation of the reports of `cargo report`.
+//!
+//! The `future-incompatibilities` report lives with the rest of the
+//! future-incompat machinery in `core::compiler::future_incompat`. The
+//! reports about the last build read what `core::compiler::build_report`
+//! saved.
+
+mod build_scripts;
+mod rebuild_reasons;
+mod timings;
+
+pub use self::build_scripts::report_build_scripts;
+pub use self::rebuild_reasons::report_rebuild_reasons;
+pub use self::timings::{compare_timings, report_timings, CompareTimingsOptions};
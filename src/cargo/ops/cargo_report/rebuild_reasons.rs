@@ -0,0 +1,29 @@
+//! Implementation of `cargo report rebuild-reasons`.
+
+use crate::core::compiler::build_report::BuildReport;
+use crate::core::Workspace;
+use crate::drop_println;
+use crate::util::CargoResult;
+
+/// Shows why each unit rebuilt by the last build of `ws` was dirty.
+pub fn report_rebuild_reasons(ws: &Workspace<'_>) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let report = BuildReport::load(ws)?;
+    if !report.success {
+        drop_println!(gctx, "the last build failed");
+    }
+    let total = report.fresh_units as usize + report.dirty_units.len();
+    if report.dirty_units.is_empty() {
+        drop_println!(gctx, "all {total} units were fresh in the last build");
+        return Ok(());
+    }
+    drop_println!(
+        gctx,
+        "{} of {total} units were rebuilt in the last build:",
+        report.dirty_units.len()
+    );
+    for unit in &report.dirty_units {
+        drop_println!(gctx, "  {}: {}", unit.unit, unit.reason);
+    }
+    Ok(())
+}
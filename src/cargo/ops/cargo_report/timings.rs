@@ -0,0 +1,159 @@
+//! Implementation of `cargo report timings`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::core::compiler::{TimingReport, UnitTimingReport};
+use crate::drop_println;
+use crate::util::{CargoResult, GlobalContext};
+
+/// The number of units listed by [`report_timings`].
+const SLOWEST_UNITS: usize = 10;
+
+/// Changes smaller than this, in seconds, are noise rather than regressions.
+const MIN_CHANGE: f64 = 0.1;
+
+pub struct CompareTimingsOptions<'a> {
+    /// The report of the run to compare against.
+    pub old: &'a Path,
+    /// The report of the run that may have regressed.
+    pub new: &'a Path,
+    /// How much slower, in percent, a unit must build to be reported.
+    pub threshold: f64,
+}
+
+/// Shows a summary of the timing report at `path`, with its slowest units.
+pub fn report_timings(gctx: &GlobalContext, path: &Path) -> CargoResult<()> {
+    let report = TimingReport::load(path)?;
+    drop_println!(
+        gctx,
+        "build started at {} took {:.2}s",
+        report.start,
+        report.duration
+    );
+    drop_println!(
+        gctx,
+        "profile `{}`, {} units ({} fresh, {} dirty), jobs={}",
+        report.profile,
+        report.fresh_units + report.dirty_units,
+        report.fresh_units,
+        report.dirty_units,
+        report.jobs
+    );
+    if let Some(error) = &report.error {
+        drop_println!(gctx, "the build failed: {error}");
+    }
+
+    let mut units: Vec<_> = report.units.iter().collect();
+    units.sort_by(|a, b| b.duration.total_cmp(&a.duration));
+    if !units.is_empty() {
+        drop_println!(gctx, "slowest units:");
+    }
+    for unit in units.into_iter().take(SLOWEST_UNITS) {
+        drop_println!(gctx, "  {:>8.2}s  {}", unit.duration, describe(unit));
+    }
+    Ok(())
+}
+
+/// Compares two timing reports, listing the units that got slower.
+pub fn compare_timings(gctx: &GlobalContext, opts: &CompareTimingsOptions<'_>) -> CargoResult<()> {
+    let old = TimingReport::load(opts.old)?;
+    let new = TimingReport::load(opts.new)?;
+    drop_println!(
+        gctx,
+        "total time: {:.2}s -> {:.2}s ({})",
+        old.duration,
+        new.duration,
+        percent_change(old.duration, new.duration)
+    );
+
+    let old_units = durations(&old);
+    let new_units = durations(&new);
+
+    let mut regressed: Vec<_> = new_units
+        .iter()
+        .filter_map(|(key, &(unit, new_duration))| {
+            let &(_, old_duration) = old_units.get(key)?;
+            let change = new_duration - old_duration;
+            let regressed = change >= MIN_CHANGE
+                && (old_duration == 0.0 || change / old_duration * 100.0 > opts.threshold);
+            regressed.then_some((unit, old_duration, new_duration))
+        })
+        .collect();
+    regressed.sort_by(|a, b| (b.2 - b.1).total_cmp(&(a.2 - a.1)));
+    if regressed.is_empty() {
+        drop_println!(gctx, "no units regressed by more than {}%", opts.threshold);
+    } else {
+        drop_println!(
+            gctx,
+            "{} regressed by more than {}%:",
+            units(regressed.len()),
+            opts.threshold
+        );
+    }
+    for (unit, old_duration, new_duration) in regressed {
+        drop_println!(
+            gctx,
+            "  {:>+8.2}s ({})  {} ({:.2}s -> {:.2}s)",
+            new_duration - old_duration,
+            percent_change(old_duration, new_duration),
+            describe(unit),
+            old_duration,
+            new_duration
+        );
+    }
+
+    let mut added: Vec<_> = new_units
+        .iter()
+        .filter(|(key, _)| !old_units.contains_key(key))
+        .map(|(_, &(unit, duration))| (unit, duration))
+        .collect();
+    added.sort_by(|a, b| b.1.total_cmp(&a.1));
+    if !added.is_empty() {
+        drop_println!(gctx, "{} only in the new report:", units(added.len()));
+    }
+    for (unit, duration) in added {
+        drop_println!(gctx, "  {:>8.2}s  {}", duration, describe(unit));
+    }
+    Ok(())
+}
+
+/// Identifies a unit across reports. The package ID isn't part of it, as it
+/// contains the path to the workspace, which may differ between runs.
+type UnitKey<'a> = (&'a str, &'a str, &'a str, &'a str);
+
+/// The total build time of the units of `report`, adding up those that can't
+/// be told apart.
+fn durations(report: &TimingReport) -> HashMap<UnitKey<'_>, (&UnitTimingReport, f64)> {
+    let mut durations = HashMap::new();
+    for unit in &report.units {
+        let key = (
+            unit.name.as_str(),
+            unit.version.as_str(),
+            unit.target.as_str(),
+            unit.kind.as_str(),
+        );
+        durations.entry(key).or_insert((unit, 0.0)).1 += unit.duration;
+    }
+    durations
+}
+
+fn describe(unit: &UnitTimingReport) -> String {
+    format!("{} v{} {}", unit.name, unit.version, unit.target)
+}
+
+fn percent_change(old: f64, new: f64) -> String {
+    if old == 0.0 {
+        "new".to_string()
+    } else {
+        format!("{:+.1}%", (new - old) / old * 100.0)
+    }
+}
+
+fn units(n: usize) -> String {
+    if n == 1 {
+        "1 unit".to_string()
+    } else {
+        format!("{n} units")
+    }
+}
@@ -11,11 +11,28 @@ use cargo_util::{ProcessBuilder, ProcessError};
 use std::ffi::OsString;
 use std::fmt::Write;
 use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+use self::report::TestReporter;
+
+mod report;
 
 pub struct TestOptions {
     pub compile_opts: ops::CompileOptions,
     pub no_run: bool,
     pub no_fail_fast: bool,
+    /// The number of test executables to run in parallel (`--test-jobs`).
+    ///
+    /// If set, the output of each executable is buffered and printed once it
+    /// exits, followed by a summary once all of them have run. This implies
+    /// `no_fail_fast`.
+    pub test_jobs: Option<u32>,
+    /// Where to write a JUnit XML report of the tests that ran (`--junit`).
+    pub junit: Option<PathBuf>,
 }
 
 /// The kind of test.
@@ -29,6 +46,16 @@ enum TestKind {
     Doctest,
 }
 
+impl TestKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TestKind::Test => "test",
+            TestKind::Bench => "bench",
+            TestKind::Doctest => "doctest",
+        }
+    }
+}
+
 /// A unit that failed to run.
 struct UnitTestError {
     unit: Unit,
@@ -65,6 +92,7 @@ impl UnitTestError {
 /// On error, the returned [`CliError`] will have the appropriate process exit
 /// code that Cargo should use.
 pub fn run_tests(ws: &Workspace<'_>, options: &TestOptions, test_args: &[&str]) -> CliResult {
+    let mut reporter = TestReporter::new(ws.gctx(), options, test_args)?;
     let compilation = compile_tests(ws, options)?;
 
     if options.no_run {
@@ -73,11 +101,33 @@ pub fn run_tests(ws: &Workspace<'_>, options: &TestOptions, test_args: &[&str])
         }
         return Ok(());
     }
-    let mut errors = run_unit_tests(ws, options, test_args, &compilation, TestKind::Test)?;
+    let result = (|| {
+        let mut errors = run_unit_tests(
+            ws,
+            options,
+            test_args,
+            &compilation,
+            TestKind::Test,
+            &mut reporter,
+        )?;
 
-    let doctest_errors = run_doc_tests(ws, options, test_args, &compilation)?;
-    errors.extend(doctest_errors);
-    no_fail_fast_err(ws, &options.compile_opts, &errors)
+        let doctest_errors = run_doc_tests(ws, options, test_args, &compilation, &mut reporter)?;
+        errors.extend(doctest_errors);
+        no_fail_fast_err(ws, &options.compile_opts, &errors)
+    })();
+    // The report also covers the tests that ran before a failure stopped the
+    // run early, and failing to write it must not hide why the tests failed.
+    if let Err(e) = reporter.write_junit() {
+        if result.is_ok() {
+            return Err(e.into());
+        }
+        crate::display_warning_with_error(
+            "the JUnit report could not be written",
+            &e,
+            &mut ws.gctx().shell(),
+        );
+    }
+    result
 }
 
 /// Compiles and runs benchmarks.
@@ -85,6 +135,7 @@ pub fn run_tests(ws: &Workspace<'_>, options: &TestOptions, test_args: &[&str])
 /// On error, the returned [`CliError`] will have the appropriate process exit
 /// code that Cargo should use.
 pub fn run_benches(ws: &Workspace<'_>, options: &TestOptions, args: &[&str]) -> CliResult {
+    let mut reporter = TestReporter::new(ws.gctx(), options, args)?;
     let compilation = compile_tests(ws, options)?;
 
     if options.no_run {
@@ -97,7 +148,14 @@ pub fn run_benches(ws: &Workspace<'_>, options: &TestOptions, args: &[&str]) ->
     let mut args = args.to_vec();
     args.push("--bench");
 
-    let errors = run_unit_tests(ws, options, &args, &compilation, TestKind::Bench)?;
+    let errors = run_unit_tests(
+        ws,
+        options,
+        &args,
+        &compilation,
+        TestKind::Bench,
+        &mut reporter,
+    )?;
     no_fail_fast_err(ws, &options.compile_opts, &errors)
 }
 
@@ -117,7 +175,20 @@ fn run_unit_tests(
     test_args: &[&str],
     compilation: &Compilation<'_>,
     test_kind: TestKind,
+    reporter: &mut TestReporter,
 ) -> Result<Vec<UnitTestError>, CliError> {
+    if let Some(test_jobs) = options.test_jobs {
+        return run_unit_tests_parallel(
+            ws,
+            options,
+            test_args,
+            compilation,
+            test_kind,
+            test_jobs,
+            reporter,
+        );
+    }
+
     let gctx = ws.gctx();
     let cwd = gctx.cwd();
     let mut errors = Vec::new();
@@ -138,6 +209,11 @@ fn run_unit_tests(
             compilation,
             "unittests",
         )?;
+        if reporter.is_enabled() && unit.target.harness() {
+            if let Some(arg) = piped_color_arg(gctx, test_args) {
+                cmd.arg(arg);
+            }
+        }
 
         if gctx.extra_verbose() {
             cmd.display_env_vars();
@@ -148,7 +224,7 @@ fn run_unit_tests(
         gctx.shell()
             .verbose(|shell| shell.status("Running", &cmd))?;
 
-        if let Err(e) = cmd.exec() {
+        if let Err(e) = exec_test(gctx, &cmd, unit, test_kind, Some(path), reporter) {
             let code = fail_fast_code(&e);
             let unit_err = UnitTestError {
                 unit: unit.clone(),
@@ -164,6 +240,161 @@ fn run_unit_tests(
     Ok(errors)
 }
 
+/// Runs the unit and integration tests of a package with up to `test_jobs`
+/// test executables running at the same time.
+///
+/// The output of each executable is captured and printed in one piece when it
+/// exits, so that the output of different executables is not interleaved.
+/// This means the executables don't run in a terminal, though libtest is
+/// still told to use colors if cargo's output supports them.
+/// Every executable is run even if some of them fail, like with
+/// `--no-fail-fast`, and the failures are returned in the same order as
+/// [`Compilation::tests`].
+fn run_unit_tests_parallel(
+    ws: &Workspace<'_>,
+    options: &TestOptions,
+    test_args: &[&str],
+    compilation: &Compilation<'_>,
+    test_kind: TestKind,
+    test_jobs: u32,
+    reporter: &mut TestReporter,
+) -> Result<Vec<UnitTestError>, CliError> {
+    let gctx = ws.gctx();
+    let cwd = gctx.cwd();
+    let start = Instant::now();
+
+    // `Unit` can't be sent to other threads, so only the processes are shared
+    // with the threads running them.
+    let mut displays = Vec::new();
+    let mut cmds = Vec::new();
+    for UnitOutput {
+        unit,
+        path,
+        script_meta,
+    } in compilation.tests.iter()
+    {
+        let (exe_display, mut cmd) = cmd_builds(
+            gctx,
+            cwd,
+            unit,
+            path,
+            script_meta,
+            test_args,
+            compilation,
+            "unittests",
+        )?;
+        if unit.target.harness() {
+            if let Some(arg) = piped_color_arg(gctx, test_args) {
+                cmd.arg(arg);
+            }
+        }
+        if gctx.extra_verbose() {
+            cmd.display_env_vars();
+        }
+        displays.push((unit, path, exe_display));
+        cmds.push(cmd);
+    }
+
+    /// Sent from the threads running the test executables.
+    enum Event {
+        Started(usize),
+        Finished(usize, CargoResult<Output>),
+    }
+
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    let mut runs: Vec<_> = cmds.iter().map(|_| None).collect();
+    let mut errors = Vec::new();
+    thread::scope(|s| -> CargoResult<()> {
+        for _ in 0..cmds.len().min(test_jobs as usize) {
+            let tx = tx.clone();
+            let cmds = &cmds;
+            let next = &next;
+            s.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(cmd) = cmds.get(i) else {
+                    break;
+                };
+                if tx.send(Event::Started(i)).is_err()
+                    || tx.send(Event::Finished(i, cmd.output())).is_err()
+                {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        for event in rx {
+            let (i, output) = match event {
+                Event::Started(i) => {
+                    let (unit, path, exe_display) = &displays[i];
+                    gctx.shell()
+                        .concise(|shell| shell.status("Running", exe_display))?;
+                    gctx.shell()
+                        .verbose(|shell| shell.status("Running", &cmds[i]))?;
+                    if reporter.is_enabled() {
+                        runs[i] = Some(reporter.start(gctx, unit, test_kind, Some(path))?);
+                    }
+                    continue;
+                }
+                Event::Finished(i, output) => (i, output),
+            };
+            let (unit, _, _) = &displays[i];
+            let cmd = &cmds[i];
+
+            let result = output.and_then(|output| {
+                gctx.shell().out().write_all(&output.stdout)?;
+                gctx.shell().err().write_all(&output.stderr)?;
+                if let Some(run) = &mut runs[i] {
+                    for line in String::from_utf8_lossy(&output.stdout).lines() {
+                        reporter.stdout_line(gctx, run, line)?;
+                    }
+                }
+                if output.status.success() {
+                    Ok(())
+                } else {
+                    Err(ProcessError::new(
+                        &format!("process didn't exit successfully: {cmd}"),
+                        Some(output.status),
+                        None,
+                    )
+                    .into())
+                }
+            });
+            if let Some(run) = runs[i].take() {
+                reporter.finish(gctx, run, &result)?;
+            }
+            if let Err(e) = result {
+                let unit_err = UnitTestError {
+                    unit: (*unit).clone(),
+                    kind: test_kind,
+                };
+                report_test_error(ws, test_args, &options.compile_opts, &unit_err, e);
+                errors.push((i, unit_err));
+            }
+        }
+        Ok(())
+    })?;
+
+    errors.sort_by_key(|(i, _)| *i);
+    let total = cmds.len();
+    let failed = errors.len();
+    gctx.shell().status(
+        "Summary",
+        format!(
+            "{total} test {}: {} passed, {failed} failed; finished in {:.2}s",
+            if total == 1 {
+                "executable"
+            } else {
+                "executables"
+            },
+            total - failed,
+            start.elapsed().as_secs_f64()
+        ),
+    )?;
+    Ok(errors.into_iter().map(|(_, unit_err)| unit_err).collect())
+}
+
 /// Runs doc tests.
 ///
 /// Returns a `Vec` of tests that failed when `--no-fail-fast` is used.
@@ -173,6 +404,7 @@ fn run_doc_tests(
     options: &TestOptions,
     test_args: &[&str],
     compilation: &Compilation<'_>,
+    reporter: &mut TestReporter,
 ) -> Result<Vec<UnitTestError>, CliError> {
     let gctx = ws.gctx();
     let mut errors = Vec::new();
@@ -273,6 +505,11 @@ fn run_doc_tests(
         for arg in test_args {
             p.arg("--test-args").arg(arg);
         }
+        if reporter.is_enabled() {
+            if let Some(arg) = piped_color_arg(gctx, test_args) {
+                p.arg("--test-args").arg(arg);
+            }
+        }
 
         if gctx.shell().verbosity() == Verbosity::Quiet {
             p.arg("--test-args").arg("--quiet");
@@ -293,7 +530,7 @@ fn run_doc_tests(
         gctx.shell()
             .verbose(|shell| shell.status("Running", p.to_string()))?;
 
-        if let Err(e) = p.exec() {
+        if let Err(e) = exec_test(gctx, &p, unit, TestKind::Doctest, None, reporter) {
             let code = fail_fast_code(&e);
             let unit_err = UnitTestError {
                 unit: unit.clone(),
@@ -309,6 +546,49 @@ fn run_doc_tests(
     Ok(errors)
 }
 
+/// Runs a test executable, or rustdoc for doctests, passing its output through
+/// `reporter` if needed.
+fn exec_test(
+    gctx: &GlobalContext,
+    cmd: &ProcessBuilder,
+    unit: &Unit,
+    test_kind: TestKind,
+    executable: Option<&Path>,
+    reporter: &mut TestReporter,
+) -> CargoResult<()> {
+    if !reporter.is_enabled() {
+        return cmd.exec();
+    }
+    let mut run = reporter.start(gctx, unit, test_kind, executable)?;
+    let result = cmd
+        .exec_with_streaming(
+            &mut |line| {
+                writeln!(gctx.shell().out(), "{line}")?;
+                reporter.stdout_line(gctx, &mut run, line)
+            },
+            &mut |line| {
+                writeln!(gctx.shell().err(), "{line}")?;
+                Ok(())
+            },
+            false,
+        )
+        .map(drop);
+    reporter.finish(gctx, run, &result)?;
+    result
+}
+
+/// Returns `--color=always` for libtest if its output is read by cargo
+/// instead of going to the terminal directly, so that it keeps its colors.
+///
+/// Nothing is returned if cargo's own output doesn't support color, or the
+/// color was chosen in `test_args`.
+fn piped_color_arg(gctx: &GlobalContext, test_args: &[&str]) -> Option<&'static str> {
+    let chosen = test_args
+        .iter()
+        .any(|arg| *arg == "--color" || arg.starts_with("--color="));
+    (!chosen && gctx.shell().out_supports_color()).then_some("--color=always")
+}
+
 /// Displays human-readable descriptions of the test executables.
 ///
 /// This is used when `cargo test --no-run` is used.
@@ -0,0 +1,367 @@
+//! Reporting of test results for `--message-format=json` and `--junit`.
+//!
+//! Both are built on the same events: a test executable starting, each test
+//! of it finishing, and the executable exiting. The outcome of each test is
+//! parsed from the human readable output of libtest, which is also what
+//! rustdoc uses for doctests, so it works on stable. This has some limits:
+//!
+//! * Only the default `pretty` format of libtest can be parsed, so `--quiet`
+//!   and `--format` are rejected when reporting.
+//! * With `--nocapture`, output of the tests that isn't terminated by a
+//!   newline can end up on the same line as the outcome of a test, which is
+//!   then missing from the report.
+//!
+//! Executables that don't use libtest only report the outcome of the
+//! executable itself.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::Context as _;
+use cargo_util::ProcessError;
+
+use super::{TestKind, TestOptions};
+use crate::core::compiler::Unit;
+use crate::core::shell::Verbosity;
+use crate::util::errors::CargoResult;
+use crate::util::machine_message::{self, Message};
+use crate::util::GlobalContext;
+
+/// The outcome of a single test.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TestOutcome {
+    Ok,
+    Failed,
+    Ignored,
+}
+
+impl TestOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TestOutcome::Ok => "ok",
+            TestOutcome::Failed => "failed",
+            TestOutcome::Ignored => "ignored",
+        }
+    }
+}
+
+/// A test executable (or rustdoc for doctests) that is currently running.
+pub(super) struct TestRun {
+    unit: Unit,
+    kind: TestKind,
+    start: Instant,
+    /// Finished tests in the order they were reported.
+    cases: Vec<(String, TestOutcome)>,
+    /// The captured output of failed tests, by test name.
+    failure_output: HashMap<String, String>,
+    /// The test whose captured output is currently being printed by libtest.
+    current_section: Option<String>,
+}
+
+/// Receives the events of running tests and turns them into JSON messages and
+/// a JUnit report.
+pub(super) struct TestReporter {
+    emit_json: bool,
+    junit: Option<(PathBuf, Vec<JunitSuite>)>,
+}
+
+impl TestReporter {
+    /// Returns an error if reporting is enabled but `test_args` change the
+    /// output of libtest in a way that can't be parsed.
+    pub fn new(
+        gctx: &GlobalContext,
+        options: &TestOptions,
+        test_args: &[&str],
+    ) -> CargoResult<TestReporter> {
+        let reporter = TestReporter {
+            // The test messages are still unstable.
+            emit_json: options.compile_opts.build_config.emit_json()
+                && gctx.cli_unstable().unstable_options,
+            junit: options
+                .junit
+                .as_ref()
+                .map(|path| (path.clone(), Vec::new())),
+        };
+        if !reporter.is_enabled() || options.no_run {
+            return Ok(reporter);
+        }
+        let flag = if reporter.junit.is_some() {
+            "--junit"
+        } else {
+            "--message-format=json"
+        };
+        if gctx.shell().verbosity() == Verbosity::Quiet
+            || test_args
+                .iter()
+                .any(|arg| *arg == "-q" || *arg == "--quiet")
+        {
+            anyhow::bail!(
+                "`{flag}` can't be used with `--quiet`, \
+                 the outcome of each test is read from the default output of libtest"
+            );
+        }
+        let mut args = test_args.iter();
+        while let Some(arg) = args.next() {
+            let format = match arg.strip_prefix("--format") {
+                Some("") => args.next().copied(),
+                Some(format) => format.strip_prefix('='),
+                None => None,
+            };
+            if let Some(format) = format {
+                if format != "pretty" {
+                    anyhow::bail!(
+                        "`{flag}` can't be used with `--format {format}`, \
+                         the outcome of each test is read from the default output of libtest"
+                    );
+                }
+            }
+        }
+        Ok(reporter)
+    }
+
+    /// Whether the output of tests needs to be passed to [`TestReporter::stdout_line`].
+    pub fn is_enabled(&self) -> bool {
+        self.emit_json || self.junit.is_some()
+    }
+
+    /// Called when a test executable is about to be started.
+    pub fn start(
+        &self,
+        gctx: &GlobalContext,
+        unit: &Unit,
+        kind: TestKind,
+        executable: Option<&Path>,
+    ) -> CargoResult<TestRun> {
+        if self.emit_json {
+            let msg = machine_message::TestBinaryStarted {
+                package_id: unit.pkg.package_id().to_spec(),
+                target: &unit.target,
+                kind: kind.as_str(),
+                executable,
+            }
+            .to_json_string();
+            writeln!(gctx.shell().out(), "{}", msg)?;
+        }
+        Ok(TestRun {
+            unit: unit.clone(),
+            kind,
+            start: Instant::now(),
+            cases: Vec::new(),
+            failure_output: HashMap::new(),
+            current_section: None,
+        })
+    }
+
+    /// Called for each line the test executable prints to stdout.
+    pub fn stdout_line(
+        &self,
+        gctx: &GlobalContext,
+        run: &mut TestRun,
+        line: &str,
+    ) -> CargoResult<()> {
+        if !run.unit.target.harness() && !matches!(run.kind, TestKind::Doctest) {
+            return Ok(());
+        }
+        // The output is colored if cargo's output is, see `piped_color_arg`.
+        let line = &*anstream::adapter::strip_str(line).to_string();
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|line| line.strip_suffix(" stdout ----"))
+        {
+            run.current_section = Some(name.to_string());
+            return Ok(());
+        }
+        if line == "failures:" || line == "successes:" {
+            run.current_section = None;
+            return Ok(());
+        }
+        if let Some(name) = &run.current_section {
+            let output = run.failure_output.entry(name.clone()).or_default();
+            output.push_str(line);
+            output.push('\n');
+            return Ok(());
+        }
+
+        let Some((name, outcome)) = parse_libtest_result(line) else {
+            return Ok(());
+        };
+        if self.emit_json {
+            let msg = machine_message::TestCaseFinished {
+                package_id: run.unit.pkg.package_id().to_spec(),
+                target: &run.unit.target,
+                kind: run.kind.as_str(),
+                name,
+                outcome: outcome.as_str(),
+            }
+            .to_json_string();
+            writeln!(gctx.shell().out(), "{}", msg)?;
+        }
+        run.cases.push((name.to_string(), outcome));
+        Ok(())
+    }
+
+    /// Called when a test executable has exited, or failed to start.
+    pub fn finish(
+        &mut self,
+        gctx: &GlobalContext,
+        mut run: TestRun,
+        result: &CargoResult<()>,
+    ) -> CargoResult<()> {
+        let duration = run.start.elapsed().as_secs_f64();
+        if self.emit_json {
+            let exit_code = match result {
+                Ok(()) => Some(0),
+                Err(e) => e.downcast_ref::<ProcessError>().and_then(|e| e.code),
+            };
+            let msg = machine_message::TestBinaryFinished {
+                package_id: run.unit.pkg.package_id().to_spec(),
+                target: &run.unit.target,
+                kind: run.kind.as_str(),
+                success: result.is_ok(),
+                exit_code,
+                duration,
+            }
+            .to_json_string();
+            writeln!(gctx.shell().out(), "{}", msg)?;
+        }
+
+        if let Some((_, suites)) = &mut self.junit {
+            let name = match run.kind {
+                TestKind::Doctest => format!("{} doctests", run.unit.pkg.name()),
+                TestKind::Test | TestKind::Bench => format!(
+                    "{} {}",
+                    run.unit.pkg.name(),
+                    run.unit.target.description_named()
+                ),
+            };
+            let mut cases: Vec<_> = run
+                .cases
+                .into_iter()
+                .map(|(name, outcome)| JunitCase {
+                    output: run.failure_output.remove(&name),
+                    name,
+                    outcome,
+                })
+                .collect();
+            // Executables without libtest are reported as a single test, and
+            // a failure of the executable itself, like a crash, must show up
+            // in the report even if no test failed.
+            let uses_libtest = run.unit.target.harness() || matches!(run.kind, TestKind::Doctest);
+            let test_failed = cases.iter().any(|case| case.outcome == TestOutcome::Failed);
+            if !uses_libtest || (result.is_err() && !test_failed) {
+                cases.push(JunitCase {
+                    name: name.clone(),
+                    outcome: match result {
+                        Ok(()) => TestOutcome::Ok,
+                        Err(_) => TestOutcome::Failed,
+                    },
+                    output: result.as_ref().err().map(|e| format!("{e:?}")),
+                });
+            }
+            suites.push(JunitSuite {
+                name,
+                duration,
+                cases,
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes the JUnit report with all test executables that have run, if
+    /// one was requested.
+    pub fn write_junit(&self) -> CargoResult<()> {
+        let Some((path, suites)) = &self.junit else {
+            return Ok(());
+        };
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+        for suite in suites {
+            suite.write_xml(&mut xml);
+        }
+        xml.push_str("</testsuites>\n");
+        cargo_util::paths::write(path, xml)
+            .with_context(|| format!("failed to write JUnit report to `{}`", path.display()))
+    }
+}
+
+/// Parses a line like `test tests::foo ... ok` from libtest, returning the
+/// name and outcome of the test.
+fn parse_libtest_result(line: &str) -> Option<(&str, TestOutcome)> {
+    let (name, result) = line.strip_prefix("test ")?.rsplit_once(" ... ")?;
+    let outcome = match result {
+        "ok" => TestOutcome::Ok,
+        "FAILED" => TestOutcome::Failed,
+        result if result.starts_with("ignored") => TestOutcome::Ignored,
+        result if result.starts_with("bench:") => TestOutcome::Ok,
+        _ => return None,
+    };
+    Some((name, outcome))
+}
+
+/// A `<testsuite>` of the JUnit report, which is one test executable.
+struct JunitSuite {
+    name: String,
+    duration: f64,
+    cases: Vec<JunitCase>,
+}
+
+/// A `<testcase>` of the JUnit report.
+struct JunitCase {
+    name: String,
+    outcome: TestOutcome,
+    /// The captured output of a failed test.
+    output: Option<String>,
+}
+
+impl JunitSuite {
+    fn write_xml(&self, xml: &mut String) {
+        let count = |outcome| self.cases.iter().filter(|c| c.outcome == outcome).count();
+        let name = escape_xml(&self.name);
+        writeln!(
+            xml,
+            "  <testsuite name=\"{name}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\">",
+            self.cases.len(),
+            count(TestOutcome::Failed),
+            count(TestOutcome::Ignored),
+            self.duration,
+        )
+        .unwrap();
+        for case in &self.cases {
+            let case_name = escape_xml(&case.name);
+            write!(
+                xml,
+                "    <testcase classname=\"{name}\" name=\"{case_name}\""
+            )
+            .unwrap();
+            match case.outcome {
+                TestOutcome::Ok => xml.push_str("/>\n"),
+                TestOutcome::Ignored => xml.push_str(">\n      <skipped/>\n    </testcase>\n"),
+                TestOutcome::Failed => {
+                    xml.push_str(">\n      <failure message=\"test failed\">");
+                    xml.push_str(&escape_xml(
+                        case.output.as_deref().unwrap_or_default().trim_end(),
+                    ));
+                    xml.push_str("</failure>\n    </testcase>\n");
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            // Not allowed in XML 1.0, even escaped.
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
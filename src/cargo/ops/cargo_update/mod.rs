@@ -24,6 +24,12 @@ use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use tracing::{debug, trace};
 
+mod verify;
+
+pub use self::verify::{update_verified, VerifyCommand};
+
+/// The versions to upgrade dependencies to, by the name they have in the
+/// manifests and their source.
 pub type UpgradeMap = HashMap<(String, SourceId), Version>;
 
 pub struct UpdateOptions<'a> {
@@ -274,14 +280,16 @@ fn upgrade_dependency(
     let name = dependency.package_name();
     let renamed_to = dependency.name_in_toml();
 
-    if name != renamed_to {
+    // A renamed dependency is only upgraded when selected by the name it is
+    // renamed to, as it may be kept at another version on purpose.
+    if name != renamed_to && to_update.is_empty() {
         trace!("skipping dependency renamed from `{name}` to `{renamed_to}`");
         return Ok(dependency);
     }
 
     if !to_update.is_empty()
         && !to_update.iter().any(|spec| {
-            spec.name() == name.as_str()
+            spec.name() == renamed_to.as_str()
                 && dependency.source_id().is_registry()
                 && spec
                     .url()
@@ -368,7 +376,10 @@ fn upgrade_dependency(
             .status_with_color("Upgrading", &upgrade_message, &style::GOOD)?;
     }
 
-    upgrades.insert((name.to_string(), dependency.source_id()), latest.clone());
+    upgrades.insert(
+        (renamed_to.to_string(), dependency.source_id()),
+        latest.clone(),
+    );
 
     let req = OptVersionReq::Req(VersionReq::parse(&latest.to_string())?);
     let mut dep = dependency.clone();
@@ -431,11 +442,6 @@ pub fn write_manifest_upgrades(
                 )?;
                 let name = &dependency.name;
 
-                if let Some(renamed_to) = dependency.rename {
-                    trace!("skipping dependency renamed from `{name}` to `{renamed_to}`");
-                    continue;
-                }
-
                 let Some(current) = dependency.version() else {
                     trace!("skipping dependency without a version: {name}");
                     continue;
@@ -448,7 +454,10 @@ pub fn write_manifest_upgrades(
                     continue;
                 };
 
-                let Some(latest) = upgrades.get(&(name.to_owned(), source_id)) else {
+                // Upgrades of renamed dependencies are recorded under the
+                // name they are renamed to.
+                let key = dependency.toml_key().to_owned();
+                let Some(latest) = upgrades.get(&(key, source_id)) else {
                     trace!("skipping dependency without an upgrade: {name}");
                     continue;
                 };
@@ -0,0 +1,474 @@
+//! Implementation of `cargo update --verify`.
+//!
+//! Instead of applying all updates at once, each dependency is updated on its
+//! own, then the workspace members depending on it are checked or tested.
+//! Updates that fail to resolve or verify have their changes to the manifests
+//! and the lockfile reverted, so they don't keep the other updates from
+//! being applied.
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+use cargo_util::paths;
+use itertools::Itertools;
+
+use super::{PackageDiff, UpdateOptions};
+use crate::core::resolver::features::{CliFeatures, HasDevUnits};
+use crate::core::{PackageId, PackageIdSpec, PackageIdSpecQuery, Resolve, Workspace};
+use crate::ops;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::errors::CargoResult;
+use crate::util::style;
+
+/// The command used to verify each update.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyCommand {
+    Check,
+    Test,
+}
+
+impl VerifyCommand {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerifyCommand::Check => "check",
+            VerifyCommand::Test => "test",
+        }
+    }
+}
+
+impl std::str::FromStr for VerifyCommand {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> CargoResult<VerifyCommand> {
+        match s {
+            "check" => Ok(VerifyCommand::Check),
+            "test" => Ok(VerifyCommand::Test),
+            _ => anyhow::bail!("invalid verify command `{s}`, expected `check` or `test`"),
+        }
+    }
+}
+
+/// The outcome of updating a single dependency.
+enum Outcome {
+    Kept,
+    Reverted(String),
+}
+
+/// Updates the dependencies selected by `opts` one at a time, keeping the
+/// updates for which `verify` succeeds and reverting the others.
+///
+/// With `breaking`, the version requirements in the manifests are upgraded
+/// like `cargo update --breaking` does, otherwise only the lockfile is
+/// updated.
+pub fn update_verified(
+    ws: &Workspace<'_>,
+    opts: &UpdateOptions<'_>,
+    breaking: bool,
+    verify: VerifyCommand,
+) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let previous_resolve = ops::load_pkg_lockfile(ws)?;
+    if previous_resolve.is_none() && !breaking {
+        // Nothing is locked yet, so there is nothing to update.
+        return super::generate_lockfile(ws);
+    }
+
+    let candidates = if breaking {
+        breaking_candidates(ws, &opts.to_update)?
+    } else {
+        compatible_candidates(ws, previous_resolve.as_ref().unwrap(), &opts.to_update)?
+    };
+
+    let mut report = Vec::new();
+    for (name, to_update) in candidates {
+        let before = ops::load_pkg_lockfile(ws)?;
+        if !breaking
+            && before
+                .as_ref()
+                .map_or(true, |resolve| resolve.query(&to_update[0]).is_err())
+        {
+            // Removed from the lockfile by an earlier update.
+            continue;
+        }
+        let snapshot = Snapshot::take(ws)?;
+
+        let mut attempt_ws = reload_workspace(ws)?;
+        let applied = if breaking {
+            apply_breaking(&mut attempt_ws, &to_update)
+        } else {
+            let update_opts = UpdateOptions {
+                gctx,
+                to_update: to_update.clone(),
+                precise: None,
+                recursive: false,
+                dry_run: false,
+                workspace: false,
+            };
+            super::update_lockfile(&attempt_ws, &update_opts).map(|()| true)
+        };
+        let applied = match applied {
+            Ok(applied) => applied,
+            Err(e) => {
+                snapshot.restore()?;
+                crate::display_warning_with_error(
+                    &format!("failed to update {name}, reverting"),
+                    &e,
+                    &mut gctx.shell(),
+                );
+                report.push((name, Outcome::Reverted("failed to resolve".to_string())));
+                continue;
+            }
+        };
+        if !applied || snapshot.is_current()? {
+            // Already up to date.
+            continue;
+        }
+
+        let verify_ws = reload_workspace(ws)?;
+        let after =
+            ops::load_pkg_lockfile(&verify_ws)?.expect("the lockfile was written by the update");
+        let (label, changed) = describe_changes(&name, before.as_ref(), &after);
+        // With `--workspace`, all members are verified, which is what an
+        // empty list of members stands for.
+        let members = if opts.workspace {
+            Vec::new()
+        } else {
+            affected_members(&verify_ws, &after, &changed)
+        };
+
+        gctx.shell().status(
+            "Verifying",
+            format!("{label} with `cargo {}`", verify.as_str()),
+        )?;
+        match run_verify(&verify_ws, verify, &members) {
+            Ok(()) => report.push((label, Outcome::Kept)),
+            Err(e) => {
+                snapshot.restore()?;
+                let reason = format!("`cargo {}` failed", verify.as_str());
+                tracing::debug!("verification of {label} failed: {e:?}");
+                gctx.shell().status_with_color(
+                    "Reverting",
+                    format!("{label} ({reason})"),
+                    &style::WARN,
+                )?;
+                report.push((label, Outcome::Reverted(reason)));
+            }
+        }
+    }
+
+    let kept = report
+        .iter()
+        .filter(|(_, outcome)| matches!(outcome, Outcome::Kept))
+        .count();
+    let reverted = report.len() - kept;
+    gctx.shell().status(
+        "Summary",
+        format!(
+            "{kept} {} kept, {reverted} reverted",
+            if kept == 1 { "update" } else { "updates" }
+        ),
+    )?;
+    for (label, outcome) in &report {
+        match outcome {
+            Outcome::Kept => gctx.shell().status("Kept", label)?,
+            Outcome::Reverted(reason) => gctx.shell().status_with_color(
+                "Reverted",
+                format!("{label} ({reason})"),
+                &style::WARN,
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Returns the names of the direct registry dependencies that may have a
+/// breaking upgrade, along with the specs to pass to
+/// [`ops::upgrade_manifests`] to upgrade only that dependency.
+///
+/// A spec selects a dependency by the name of its package or the name it has
+/// in the manifest. Renamed dependencies are upgraded on their own, selected
+/// by the name they are renamed to.
+fn breaking_candidates(
+    ws: &Workspace<'_>,
+    to_update: &[String],
+) -> CargoResult<Vec<(String, Vec<String>)>> {
+    let specs = to_update
+        .iter()
+        .map(|spec| PackageIdSpec::parse(spec))
+        .collect::<Result<Vec<_>, _>>()?;
+    let deps: BTreeSet<_> = ws
+        .members()
+        .flat_map(|member| member.dependencies())
+        .filter(|dep| dep.source_id().is_registry())
+        .map(|dep| (dep.package_name(), dep.name_in_toml()))
+        .collect();
+    Ok(deps
+        .into_iter()
+        .filter_map(|(name, name_in_toml)| {
+            let matching: Vec<_> = if specs.is_empty() {
+                vec![name_in_toml.to_string()]
+            } else {
+                specs
+                    .iter()
+                    .filter(|spec| name == spec.name() || name_in_toml == spec.name())
+                    .map(|spec| with_name(spec, &name_in_toml).to_string())
+                    .collect()
+            };
+            (!matching.is_empty()).then(|| (name.to_string(), matching))
+        })
+        .collect())
+}
+
+/// Returns the locked packages that `cargo update` would update, along with
+/// the spec to pass to [`ops::update_lockfile`] to update only that package.
+///
+/// A spec selects a package by its name or the name it has as a dependency in
+/// the manifests.
+fn compatible_candidates(
+    ws: &Workspace<'_>,
+    previous_resolve: &Resolve,
+    to_update: &[String],
+) -> CargoResult<Vec<(String, Vec<String>)>> {
+    let renames: HashSet<_> = ws
+        .members()
+        .flat_map(|member| member.dependencies())
+        .filter(|dep| dep.explicit_name_in_toml().is_some())
+        .map(|dep| (dep.name_in_toml(), dep.package_name()))
+        .collect();
+    let specs = to_update
+        .iter()
+        .map(|spec| PackageIdSpec::parse(spec))
+        .collect::<Result<Vec<_>, _>>()?;
+    let matches = |id: PackageId| {
+        specs.iter().any(|spec| {
+            spec.matches(id)
+                || renames.iter().any(|(name_in_toml, name)| {
+                    *name_in_toml == spec.name() && with_name(spec, name).matches(id)
+                })
+        })
+    };
+
+    let _lock = ws
+        .gctx()
+        .acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+    let mut registry = ws.package_registry()?;
+    let resolve = ops::resolve_with_previous(
+        &mut registry,
+        ws,
+        &CliFeatures::new_all(true),
+        HasDevUnits::Yes,
+        Some(previous_resolve),
+        Some(&|_| false),
+        &[],
+        true,
+    )?;
+
+    let mut ids: Vec<_> = PackageDiff::diff(previous_resolve, &resolve)
+        .filter(|diff| !diff.added.is_empty())
+        .flat_map(|diff| diff.removed)
+        .filter(|id| !ws.is_member_id(*id))
+        .filter(|id| specs.is_empty() || matches(*id))
+        .collect();
+    ids.sort();
+    Ok(ids
+        .into_iter()
+        .map(|id| (id.name().to_string(), vec![id.to_spec().to_string()]))
+        .collect())
+}
+
+/// Returns `spec` selecting packages named `name` instead.
+fn with_name(spec: &PackageIdSpec, name: &str) -> PackageIdSpec {
+    let mut with_name = PackageIdSpec::new(name.to_string());
+    if let Some(version) = spec.partial_version() {
+        with_name = with_name.with_version(version.clone());
+    }
+    if let Some(url) = spec.url() {
+        with_name = with_name.with_url(url.clone());
+    }
+    if let Some(kind) = spec.kind() {
+        with_name = with_name.with_kind(kind.clone());
+    }
+    with_name
+}
+
+/// Upgrades the requirements in the manifests for `to_update`, then updates
+/// the lockfile. Returns whether there was anything to upgrade.
+fn apply_breaking(ws: &mut Workspace<'_>, to_update: &Vec<String>) -> CargoResult<bool> {
+    let upgrades = ops::upgrade_manifests(ws, to_update)?;
+    if upgrades.is_empty() {
+        return Ok(false);
+    }
+    ops::resolve_ws(ws, false)?;
+    ops::write_manifest_upgrades(ws, &upgrades, false)
+}
+
+/// Describes how the packages named `name` changed in the lockfile, and
+/// returns all packages added to it.
+fn describe_changes(
+    name: &str,
+    before: Option<&Resolve>,
+    after: &Resolve,
+) -> (String, HashSet<PackageId>) {
+    let diffs: Vec<_> = match before {
+        Some(before) => PackageDiff::diff(before, after).collect(),
+        None => PackageDiff::new(after).collect(),
+    };
+    let changed = diffs
+        .iter()
+        .flat_map(|diff| diff.added.iter().copied())
+        .collect();
+
+    let versions = |ids: &[PackageId]| ids.iter().map(|id| format!("v{}", id.version())).join(", ");
+    let label = diffs
+        .iter()
+        .filter(|diff| {
+            diff.removed
+                .iter()
+                .chain(&diff.added)
+                .any(|id| id.name() == name)
+        })
+        .map(|diff| {
+            if diff.removed.is_empty() {
+                format!("{name} {}", versions(&diff.added))
+            } else if diff.added.is_empty() {
+                format!("{name} {} (removed)", versions(&diff.removed))
+            } else {
+                format!(
+                    "{name} {} -> {}",
+                    versions(&diff.removed),
+                    versions(&diff.added)
+                )
+            }
+        })
+        .join(", ");
+    let label = if label.is_empty() {
+        // Only the manifests or other packages changed.
+        name.to_string()
+    } else {
+        label
+    };
+    (label, changed)
+}
+
+/// Returns the names of the workspace members that depend on any of
+/// `changed`, directly or not.
+fn affected_members(
+    ws: &Workspace<'_>,
+    resolve: &Resolve,
+    changed: &HashSet<PackageId>,
+) -> Vec<String> {
+    ws.members()
+        .filter(|member| {
+            let mut seen = HashSet::new();
+            let mut queue = vec![member.package_id()];
+            while let Some(id) = queue.pop() {
+                if changed.contains(&id) && id != member.package_id() {
+                    return true;
+                }
+                if seen.insert(id) {
+                    queue.extend(resolve.deps(id).map(|(dep_id, _)| dep_id));
+                }
+            }
+            false
+        })
+        .map(|member| member.name().to_string())
+        .collect()
+}
+
+/// Runs `cargo check` or `cargo test` for `members`, or all members if it is
+/// empty, in a new process, so it sees the updated manifests and lockfile
+/// like any other build would.
+fn run_verify(ws: &Workspace<'_>, verify: VerifyCommand, members: &[String]) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let mut cmd = cargo_util::ProcessBuilder::new(gctx.cargo_exe()?);
+    for config in gctx.cli_config_args() {
+        cmd.arg("--config").arg(config);
+    }
+    for flag in gctx.cli_unstable_args() {
+        cmd.arg(format!("-Z{flag}"));
+    }
+    cmd.arg(verify.as_str())
+        .arg("--manifest-path")
+        .arg(ws.root_manifest())
+        .arg("--locked");
+    if verify == VerifyCommand::Check {
+        cmd.arg("--all-targets");
+    }
+    if let Some(offline_flag) = gctx.offline_flag() {
+        cmd.arg(offline_flag);
+    }
+    if let Some(lockfile_path) = ws.requested_lockfile_path() {
+        cmd.arg("-Zunstable-options")
+            .arg("--lockfile-path")
+            .arg(lockfile_path);
+    }
+    if members.is_empty() {
+        cmd.arg("--workspace");
+    }
+    for member in members {
+        cmd.arg("-p").arg(member);
+    }
+    cmd.exec()
+}
+
+/// Creates a new workspace for `ws`, picking up changes to the manifests.
+fn reload_workspace<'gctx>(ws: &Workspace<'gctx>) -> CargoResult<Workspace<'gctx>> {
+    let mut new_ws = Workspace::new(ws.root_manifest(), ws.gctx())?;
+    new_ws.set_resolve_honors_rust_version(Some(ws.resolve_honors_rust_version()));
+    new_ws.set_require_optional_deps(ws.require_optional_deps());
+    new_ws.set_requested_lockfile_path(ws.requested_lockfile_path().map(Path::to_path_buf));
+    Ok(new_ws)
+}
+
+/// The contents of the manifests and the lockfile of a workspace, to revert
+/// a failed update.
+struct Snapshot {
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl Snapshot {
+    fn take(ws: &Workspace<'_>) -> CargoResult<Snapshot> {
+        let lockfile = ws
+            .lock_root()
+            .as_path_unlocked()
+            .join(ops::lockfile::LOCKFILE_NAME);
+        let files = std::iter::once(ws.root_manifest().to_path_buf())
+            .chain(
+                ws.members()
+                    .map(|member| member.manifest_path().to_path_buf()),
+            )
+            .chain(std::iter::once(lockfile))
+            .unique()
+            .map(|path| {
+                let contents = path
+                    .exists()
+                    .then(|| paths::read_bytes(&path))
+                    .transpose()?;
+                Ok((path, contents))
+            })
+            .collect::<CargoResult<_>>()?;
+        Ok(Snapshot { files })
+    }
+
+    /// Whether none of the files changed since the snapshot was taken.
+    fn is_current(&self) -> CargoResult<bool> {
+        for (path, contents) in &self.files {
+            let current = path.exists().then(|| paths::read_bytes(path)).transpose()?;
+            if &current != contents {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn restore(&self) -> CargoResult<()> {
+        for (path, contents) in &self.files {
+            match contents {
+                Some(contents) => paths::write_if_changed(path, contents)?,
+                None if path.exists() => paths::remove_file(path)?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,348 @@
+//! Implementation of `cargo why`.
+
+use std::fmt::Write as _;
+
+use cargo_util_schemas::core::PartialVersion;
+use semver::Version;
+
+use crate::core::dependency::{DepKind, Dependency};
+use crate::core::registry::PackageRegistry;
+use crate::core::resolver::features::{CliFeatures, HasDevUnits};
+use crate::core::Registry as _;
+use crate::core::{PackageId, PackageIdSpec, PackageIdSpecQuery, Resolve, Summary, Workspace};
+use crate::drop_println;
+use crate::ops;
+use crate::sources::source::QueryKind;
+use crate::sources::IndexSummary;
+use crate::util::cache_lock::CacheLockMode;
+use crate::util::{CargoResult, OptVersionReq};
+
+/// Explains why the packages matching `spec` were selected at their version
+/// when resolving the dependencies of `ws`.
+pub fn why(ws: &Workspace<'_>, spec: &PackageIdSpec) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    // Querying the available versions may update the index, so hold the lock
+    // for the resolve and the queries alike.
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+
+    let previous_resolve = ops::load_pkg_lockfile(ws)?;
+    let mut registry = ws.package_registry()?;
+    let resolve = ops::resolve_with_previous(
+        &mut registry,
+        ws,
+        &CliFeatures::new_all(true),
+        HasDevUnits::Yes,
+        previous_resolve.as_ref(),
+        None,
+        &[],
+        true,
+    )?;
+
+    let mut pkg_ids: Vec<_> = resolve.iter().filter(|id| spec.matches(*id)).collect();
+    if pkg_ids.is_empty() {
+        // Reuse the error message, including suggestions for similar names.
+        spec.query(resolve.iter())?;
+    }
+    pkg_ids.sort();
+
+    // The registry used for resolving has the versions of Cargo.lock locked,
+    // so look for other versions with one that isn't.
+    let mut registry = ws.package_registry()?;
+    registry.lock_patches();
+
+    let rust_versions = rust_versions(ws)?;
+    let why = Why {
+        ws,
+        resolve: &resolve,
+        previous_resolve: previous_resolve.as_ref(),
+        rust_versions: &rust_versions,
+    };
+    for (i, pkg_id) in pkg_ids.into_iter().enumerate() {
+        if i != 0 {
+            drop_println!(gctx);
+        }
+        let explanation = why.explain(pkg_id, &mut registry)?;
+        drop_println!(gctx, "{}", explanation.trim_end());
+    }
+    Ok(())
+}
+
+/// The Rust versions that the resolver prefers dependencies to be
+/// compatible with, mirroring what [`ops::resolve_with_previous`] uses.
+fn rust_versions(ws: &Workspace<'_>) -> CargoResult<Vec<PartialVersion>> {
+    if !ws.resolve_honors_rust_version() {
+        return Ok(Vec::new());
+    }
+    let mut rust_versions: Vec<_> = ws
+        .members()
+        .filter_map(|p| p.rust_version().map(|rv| rv.as_partial().clone()))
+        .collect();
+    if rust_versions.is_empty() {
+        let rustc = ws.gctx().load_global_rustc(Some(ws))?;
+        rust_versions.push(rustc.version.clone().into());
+    }
+    Ok(rust_versions)
+}
+
+struct Why<'a, 'gctx> {
+    ws: &'a Workspace<'gctx>,
+    resolve: &'a Resolve,
+    previous_resolve: Option<&'a Resolve>,
+    rust_versions: &'a [PartialVersion],
+}
+
+impl Why<'_, '_> {
+    fn explain(
+        &self,
+        pkg_id: PackageId,
+        registry: &mut PackageRegistry<'_>,
+    ) -> CargoResult<String> {
+        let mut out = format!("{pkg_id}\n");
+
+        let dependents = self.dependents(pkg_id);
+        if self
+            .ws
+            .members()
+            .any(|member| member.package_id() == pkg_id)
+        {
+            writeln!(out, "  workspace member")?;
+        }
+        if !dependents.is_empty() {
+            writeln!(out, "  required by:")?;
+            for (parent, dep) in &dependents {
+                writeln!(out, "    {}", describe_requirement(*parent, dep))?;
+            }
+        }
+
+        self.explain_patch(pkg_id, &mut out)?;
+        self.explain_lockfile(pkg_id, &mut out)?;
+
+        let summary = self.resolve.summary(pkg_id);
+        if !self.rust_versions.is_empty() {
+            if let Some(rust_version) = summary.rust_version() {
+                let required = self.lowest_rust_version();
+                if self.msrv_compat_count(summary) == self.rust_versions.len() {
+                    writeln!(
+                        out,
+                        "  rust-version: requires Rust {rust_version}, compatible with Rust {required}"
+                    )?;
+                } else {
+                    writeln!(
+                        out,
+                        "  rust-version: requires Rust {rust_version}, newer than Rust {required} \
+                         (no compatible version satisfies all requirements)"
+                    )?;
+                }
+            }
+        }
+
+        let newer = self.newer_versions(pkg_id, registry)?;
+        if !newer.is_empty() {
+            writeln!(out, "  newer versions not selected:")?;
+            for candidate in &newer {
+                let reason = self.rejection_reason(pkg_id, candidate, &dependents);
+                writeln!(out, "    v{}: {reason}", candidate.as_summary().version())?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Returns every package depending on `pkg_id`, along with the dependency
+    /// declaration that was matched against it.
+    fn dependents(&self, pkg_id: PackageId) -> Vec<(PackageId, &Dependency)> {
+        let mut dependents: Vec<_> = self
+            .resolve
+            .iter()
+            .flat_map(|parent| {
+                self.resolve
+                    .deps(parent)
+                    .filter(move |(dep_id, _)| *dep_id == pkg_id)
+                    .flat_map(move |(_, deps)| deps.iter().map(move |dep| (parent, dep)))
+            })
+            .collect();
+        dependents.sort_by_key(|(parent, dep)| (*parent, dep.kind(), dep.platform().cloned()));
+        dependents
+    }
+
+    fn explain_patch(&self, pkg_id: PackageId, out: &mut String) -> CargoResult<()> {
+        let mut patches: Vec<_> = self.ws.root_patch()?.into_iter().collect();
+        patches.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (url, deps) in patches {
+            if deps.iter().any(|dep| dep.matches_id(pkg_id)) {
+                let table = if url.as_str() == crate::sources::CRATES_IO_INDEX {
+                    "crates-io".to_string()
+                } else {
+                    format!("'{url}'")
+                };
+                writeln!(out, "  patched: provided by `[patch.{table}]`")?;
+            }
+        }
+        if let Some((original, _)) = self
+            .resolve
+            .replacements()
+            .iter()
+            .find(|(_, replacement)| **replacement == pkg_id)
+        {
+            writeln!(out, "  replaced: provided by `[replace]` for {original}")?;
+        }
+        Ok(())
+    }
+
+    fn explain_lockfile(&self, pkg_id: PackageId, out: &mut String) -> CargoResult<()> {
+        if pkg_id.source_id().is_path() {
+            return Ok(());
+        }
+        let Some(previous_resolve) = self.previous_resolve else {
+            writeln!(out, "  not locked: there is no Cargo.lock")?;
+            return Ok(());
+        };
+        if previous_resolve.contains(&pkg_id) {
+            writeln!(out, "  locked: Cargo.lock pins v{}", pkg_id.version())?;
+            return Ok(());
+        }
+        let mut locked: Vec<_> = previous_resolve
+            .iter()
+            .filter(|id| id.name() == pkg_id.name() && id.source_id() == pkg_id.source_id())
+            .collect();
+        locked.sort();
+        if locked.is_empty() {
+            writeln!(out, "  not locked: not in Cargo.lock yet")?;
+        } else {
+            let versions = locked
+                .iter()
+                .map(|id| format!("v{}", id.version()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                out,
+                "  not locked: Cargo.lock has {versions}, which no longer satisfies the requirements"
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the versions of the registry that are newer than `pkg_id`,
+    /// newest first.
+    fn newer_versions(
+        &self,
+        pkg_id: PackageId,
+        registry: &mut PackageRegistry<'_>,
+    ) -> CargoResult<Vec<IndexSummary>> {
+        if !pkg_id.source_id().is_registry() {
+            return Ok(Vec::new());
+        }
+        // Sources of packages in Cargo.lock are marked as locked, which keeps
+        // the index from being updated.
+        let source_id = pkg_id.source_id().without_precise();
+        let query = Dependency::parse(pkg_id.name(), None, source_id)?;
+        let mut summaries = loop {
+            match registry.query_vec(&query, QueryKind::RejectedVersions) {
+                std::task::Poll::Ready(res) => break res?,
+                std::task::Poll::Pending => registry.block_until_ready()?,
+            }
+        };
+        summaries.retain(|s| s.as_summary().version() > pkg_id.version());
+        summaries.sort_by(|a, b| b.as_summary().version().cmp(a.as_summary().version()));
+        summaries.dedup_by(|a, b| a.as_summary().version() == b.as_summary().version());
+        Ok(summaries)
+    }
+
+    fn rejection_reason(
+        &self,
+        pkg_id: PackageId,
+        candidate: &IndexSummary,
+        dependents: &[(PackageId, &Dependency)],
+    ) -> String {
+        let summary = candidate.as_summary();
+        match candidate {
+            IndexSummary::Candidate(_) => {}
+            IndexSummary::Yanked(_) => return "yanked".to_string(),
+            IndexSummary::Offline(_) => return "not downloaded, and Cargo is offline".to_string(),
+            IndexSummary::Unsupported(_, _) => {
+                return "requires a newer version of Cargo".to_string()
+            }
+            IndexSummary::Invalid(_) => return "invalid index entry".to_string(),
+        }
+        if let Some((parent, dep)) = dependents
+            .iter()
+            .find(|(_, dep)| !original_req_matches(dep.version_req(), summary.version()))
+        {
+            return format!("does not match {}", describe_requirement(*parent, dep));
+        }
+        if !self.rust_versions.is_empty()
+            && self.msrv_compat_count(summary)
+                < self.msrv_compat_count(self.resolve.summary(pkg_id))
+        {
+            let rust_version = summary
+                .rust_version()
+                .expect("incompatible without rust-version");
+            return format!(
+                "requires Rust {rust_version}, newer than Rust {}",
+                self.lowest_rust_version()
+            );
+        }
+        if self
+            .previous_resolve
+            .is_some_and(|previous| previous.contains(&pkg_id))
+        {
+            return format!(
+                "Cargo.lock pins v{}, run `cargo update {}` to update",
+                pkg_id.version(),
+                pkg_id.name()
+            );
+        }
+        if self.ws.gctx().cli_unstable().minimal_versions {
+            return "`-Zminimal-versions` prefers the lowest version".to_string();
+        }
+        "conflicts with other requirements in the dependency graph".to_string()
+    }
+
+    fn lowest_rust_version(&self) -> &PartialVersion {
+        self.rust_versions
+            .iter()
+            .min()
+            .expect("checked for emptiness")
+    }
+
+    /// Same as `VersionPreferences::msrv_compat_count`.
+    fn msrv_compat_count(&self, summary: &Summary) -> usize {
+        let Some(rust_version) = summary.rust_version() else {
+            return self.rust_versions.len();
+        };
+        self.rust_versions
+            .iter()
+            .filter(|max| rust_version.is_compatible_with(max))
+            .count()
+    }
+}
+
+/// Whether `version` satisfies the requirement written in the manifest, even
+/// if `req` has since been locked to a specific version.
+fn original_req_matches(req: &OptVersionReq, version: &Version) -> bool {
+    match req {
+        OptVersionReq::Any => true,
+        OptVersionReq::Req(req)
+        | OptVersionReq::Locked(_, req)
+        | OptVersionReq::Precise(_, req) => req.matches(version),
+    }
+}
+
+fn describe_requirement(parent: PackageId, dep: &Dependency) -> String {
+    let mut desc = format!("`{}` from {parent}", dep.version_req());
+    let mut notes = Vec::new();
+    match dep.kind() {
+        DepKind::Normal => {}
+        DepKind::Development => notes.push("dev".to_string()),
+        DepKind::Build => notes.push("build".to_string()),
+    }
+    if let Some(platform) = dep.platform() {
+        notes.push(format!("target {platform}"));
+    }
+    if dep.explicit_name_in_toml().is_some() {
+        notes.push(format!("as `{}`", dep.name_in_toml()));
+    }
+    if !notes.is_empty() {
+        write!(desc, " ({})", notes.join(", ")).unwrap();
+    }
+    desc
+}
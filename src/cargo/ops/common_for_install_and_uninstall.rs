@@ -12,7 +12,8 @@ use cargo_util_schemas::core::PartialVersion;
 use ops::FilterRule;
 use serde::{Deserialize, Serialize};
 
-use crate::core::compiler::{DirtyReason, Freshness};
+use crate::core::compiler::{CompileKind, CompileTarget, DirtyReason, Freshness};
+use crate::core::resolver::features::CliFeatures;
 use crate::core::Target;
 use crate::core::{Dependency, FeatureValue, Package, PackageId, SourceId};
 use crate::ops::{self, CompileFilter, CompileOptions};
@@ -21,6 +22,7 @@ use crate::sources::source::Source;
 use crate::sources::PathSource;
 use crate::util::cache_lock::CacheLockMode;
 use crate::util::errors::CargoResult;
+use crate::util::interning::InternedString;
 use crate::util::GlobalContext;
 use crate::util::{FileLock, Filesystem};
 
@@ -63,11 +65,10 @@ struct CrateListingV2 {
 /// then Cargo will inform the user that it is "up to date".
 ///
 /// This is only used for the v2 format.
-#[derive(Debug, Deserialize, Serialize)]
-struct InstallInfo {
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InstallInfo {
     /// Version requested via `--version`.
-    /// None if `--version` not specified. Currently not used, possibly may be
-    /// used in the future.
+    /// None if `--version` not specified. Reused by `--update-all`.
     version_req: Option<String>,
     /// Set of binary names installed.
     bins: BTreeSet<String>,
@@ -309,6 +310,13 @@ impl InstallTracker {
         self.v1.v1.iter()
     }
 
+    /// Iterator of all installed packages.
+    /// Items are `(pkg_id, info)` where `info` holds the settings that
+    /// package was installed with.
+    pub fn all_installs(&self) -> impl Iterator<Item = (&PackageId, &InstallInfo)> {
+        self.v2.installs.iter()
+    }
+
     /// Set of binaries installed by a particular package.
     /// Returns None if the package is not installed.
     pub fn installed_bins(&self, pkg_id: PackageId) -> Option<&BTreeSet<String>> {
@@ -530,6 +538,33 @@ impl InstallInfo {
         }
     }
 
+    /// The version requirement the package was installed with, if any.
+    pub fn version_req(&self) -> Option<&str> {
+        self.version_req.as_deref()
+    }
+
+    /// Changes `opts` to build with the features, profile and target this
+    /// package was installed with.
+    ///
+    /// Packages built for the `host` target, or for an unknown one, are built
+    /// for the host again.
+    pub fn apply_to(&self, opts: &mut CompileOptions, host: &str) -> CargoResult<()> {
+        let features = self.features.iter().cloned().collect::<Vec<_>>();
+        opts.cli_features = CliFeatures::from_command_line(
+            &features,
+            self.all_features,
+            !self.no_default_features,
+        )?;
+        opts.build_config.requested_profile = InternedString::new(&self.profile);
+        opts.build_config.requested_kinds = match self.target.as_deref() {
+            Some(target) if target != host => {
+                vec![CompileKind::Target(CompileTarget::new(target)?)]
+            }
+            _ => vec![CompileKind::Host],
+        };
+        Ok(())
+    }
+
     /// Determine if this installation is "up to date", or if it needs to be reinstalled.
     ///
     /// This does not do Package/Source/Version checking.
@@ -31,14 +31,19 @@
 //! - If rustfix fails to apply any suggestions (for example, they are
 //!   overlapping), but at least some suggestions succeeded, it will try the
 //!   previous two steps up to 4 times as long as some suggestions succeed.
+//!   Suggestions that still can't be applied are reported along with the
+//!   suggestions they conflicted with.
 //! - Assuming there's at least one suggestion applied, and the suggestions
 //!   applied cleanly, rustc is run again to verify the suggestions didn't
 //!   break anything. The change will be backed out if it fails (unless
 //!   `--broken-code` is used).
+//! - With `--interactive`, nothing is written by the proxy. The suggestions
+//!   from a single run of rustc are sent back over the diagnostic server, and
+//!   the master cargo process reviews them once the build is done.
 
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ffi::OsString;
-use std::io::Write;
+use std::io::{IsTerminal as _, Write};
 use std::path::{Path, PathBuf};
 use std::process::{self, ExitStatus, Output};
 use std::{env, fs, str};
@@ -47,7 +52,7 @@ use anyhow::{bail, Context as _};
 use cargo_util::{exit_status_to_string, is_simple_exit_code, paths, ProcessBuilder};
 use cargo_util_schemas::manifest::TomlManifest;
 use rustfix::diagnostics::Diagnostic;
-use rustfix::CodeFix;
+use rustfix::{CodeFix, Suggestion};
 use semver::Version;
 use tracing::{debug, trace, warn};
 
@@ -59,7 +64,7 @@ use crate::core::PackageIdSpecQuery as _;
 use crate::core::{Edition, MaybePackage, Package, PackageId, Workspace};
 use crate::ops::resolve::WorkspaceResolve;
 use crate::ops::{self, CompileOptions};
-use crate::util::diagnostic_server::{Message, RustfixDiagnosticServer};
+use crate::util::diagnostic_server::{DiagnosticPrinter, Message, RustfixDiagnosticServer};
 use crate::util::errors::CargoResult;
 use crate::util::toml_mut::manifest::LocalManifest;
 use crate::util::GlobalContext;
@@ -75,6 +80,9 @@ const FIX_ENV_INTERNAL: &str = "__CARGO_FIX_PLZ";
 /// For passing [`FixOptions::broken_code`] through to cargo running in proxy mode.
 const BROKEN_CODE_ENV_INTERNAL: &str = "__CARGO_FIX_BROKEN_CODE";
 /// **Internal only.**
+/// For passing [`FixOptions::interactive`] through to cargo running in proxy mode.
+const INTERACTIVE_ENV_INTERNAL: &str = "__CARGO_FIX_INTERACTIVE";
+/// **Internal only.**
 /// For passing [`FixOptions::edition`] through to cargo running in proxy mode.
 const EDITION_ENV_INTERNAL: &str = "__CARGO_FIX_EDITION";
 /// **Internal only.**
@@ -97,6 +105,8 @@ pub struct FixOptions {
     pub allow_no_vcs: bool,
     pub allow_staged: bool,
     pub broken_code: bool,
+    /// Collect the fixes for review instead of applying them.
+    pub interactive: bool,
     pub requested_lockfile_path: Option<PathBuf>,
 }
 
@@ -137,6 +147,10 @@ pub fn fix(
         wrapper.env(BROKEN_CODE_ENV_INTERNAL, "1");
     }
 
+    if opts.interactive {
+        wrapper.env(INTERACTIVE_ENV_INTERNAL, "1");
+    }
+
     if opts.edition {
         wrapper.env(EDITION_ENV_INTERNAL, "1");
     }
@@ -149,11 +163,13 @@ pub fn fix(
         wrapper.env(SYSROOT_INTERNAL, sysroot);
     }
 
+    let server = RustfixDiagnosticServer::new()?;
+    let suggested = server.suggested();
     *opts
         .compile_opts
         .build_config
         .rustfix_diagnostic_server
-        .borrow_mut() = Some(RustfixDiagnosticServer::new()?);
+        .borrow_mut() = Some(server);
 
     if let Some(server) = opts
         .compile_opts
@@ -176,9 +192,152 @@ pub fn fix(
     opts.compile_opts.build_config.primary_unit_rustc = Some(wrapper);
 
     ops::compile(&ws, &opts.compile_opts)?;
+
+    if opts.interactive {
+        let suggested = std::mem::take(&mut *suggested.lock().unwrap());
+        review_fixes(&ws, suggested)?;
+    }
+    Ok(())
+}
+
+/// Shows the fixes collected by `cargo fix --interactive` and applies the approved ones.
+///
+/// On a terminal each fix is shown as a diff and the user is asked whether to
+/// apply it. Otherwise all fixes are printed as a unified diff and no file is
+/// modified.
+fn review_fixes(
+    ws: &Workspace<'_>,
+    suggested: BTreeMap<PathBuf, Vec<Suggestion>>,
+) -> CargoResult<()> {
+    let gctx = ws.gctx();
+    let prompt = std::io::stdin().is_terminal()
+        && std::io::stdout().is_terminal()
+        && gctx.shell().is_err_tty();
+    if suggested.is_empty() {
+        return gctx.shell().note("no fixes to review");
+    }
+
+    let mut printer = DiagnosticPrinter::new(gctx, &None);
+    let mut apply_remaining = false;
+    let mut quit = false;
+    for (path, suggestions) in suggested {
+        if quit {
+            break;
+        }
+        let name = path.strip_prefix(ws.root()).unwrap_or(&path);
+        let name = name.display().to_string();
+        let code = paths::read(&path)?;
+
+        // Fixes that overlap are reported and left for a later `cargo fix`,
+        // in the same order `cargo fix` would apply them.
+        let mut all = CodeFix::new(&code);
+        let rejected = all.apply_all(suggestions.iter().rev());
+        for rejected in rejected.iter().filter(|r| !r.is_identical()) {
+            let msg = if rejected.conflicts_with.is_empty() {
+                format!(
+                    "skipping fix for `{name}` ({}): {}",
+                    describe_suggestion(&rejected.suggestion),
+                    rejected.error
+                )
+            } else {
+                format!(
+                    "skipping fix for `{name}` ({}) as it overlaps with {}",
+                    describe_suggestion(&rejected.suggestion),
+                    rejected
+                        .conflicts_with
+                        .iter()
+                        .map(|s| format!("({})", describe_suggestion(s)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            };
+            gctx.shell().warn(msg)?;
+        }
+
+        if !prompt {
+            let fixed = all.finish()?;
+            let diff = similar::TextDiff::from_lines(&code, &fixed);
+            let diff = diff
+                .unified_diff()
+                .header(&format!("a/{name}"), &format!("b/{name}"))
+                .to_string();
+            write!(gctx.shell().out(), "{diff}")?;
+            continue;
+        }
+
+        let mut fixed = CodeFix::new(&code);
+        let mut fixes = 0;
+        // Same order as `cargo fix` applies them in, so that the same fixes
+        // are rejected.
+        let applicable = suggestions
+            .iter()
+            .rev()
+            .filter(|s| !rejected.iter().any(|r| &r.suggestion == *s));
+        for suggestion in applicable {
+            let mut candidate = fixed.clone();
+            candidate.apply(suggestion)?;
+            if !apply_remaining {
+                let before = fixed.finish()?;
+                let after = candidate.finish()?;
+                let diff = similar::TextDiff::from_lines(&before, &after);
+                let diff = diff.unified_diff().header(&name, &name).to_string();
+                drop_eprint!(gctx, "\n{name}:{}\n{diff}", describe_suggestion(suggestion));
+                match prompt_fix(gctx)? {
+                    FixAnswer::Yes => {}
+                    FixAnswer::No => continue,
+                    FixAnswer::All => apply_remaining = true,
+                    FixAnswer::Quit => {
+                        quit = true;
+                        break;
+                    }
+                }
+            }
+            fixed = candidate;
+            fixes += 1;
+        }
+        if fixed.modified() {
+            paths::write(&path, fixed.finish()?)?;
+            printer.print(&Message::Fixed { file: name, fixes })?;
+        }
+    }
+    if !prompt {
+        gctx.shell()
+            .note("no files were modified, run `cargo fix` to apply these fixes")?;
+    }
     Ok(())
 }
 
+enum FixAnswer {
+    Yes,
+    No,
+    All,
+    Quit,
+}
+
+/// Asks whether to apply the fix that was just shown.
+fn prompt_fix(gctx: &GlobalContext) -> CargoResult<FixAnswer> {
+    loop {
+        drop_eprint!(gctx, "Apply this fix [y,n,a,q,?]? ");
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            return Ok(FixAnswer::Quit);
+        }
+        match line.trim() {
+            "y" => return Ok(FixAnswer::Yes),
+            "n" => return Ok(FixAnswer::No),
+            "a" => return Ok(FixAnswer::All),
+            "q" => return Ok(FixAnswer::Quit),
+            _ => drop_eprintln!(
+                gctx,
+                "y - apply this fix\n\
+                 n - do not apply this fix\n\
+                 a - apply this fix and all remaining fixes\n\
+                 q - quit; do not apply this fix or any remaining ones"
+            ),
+        }
+    }
+}
+
 fn check_version_control(gctx: &GlobalContext, opts: &FixOptions) -> CargoResult<()> {
     if opts.allow_no_vcs {
         return Ok(());
@@ -751,7 +910,7 @@ struct FixedCrate {
 
 #[derive(Debug)]
 struct FixedFile {
-    errors_applying_fixes: Vec<String>,
+    errors_applying_fixes: Vec<rustfix::Rejected>,
     fixes_applied: u32,
     original_code: String,
 }
@@ -795,6 +954,30 @@ fn rustfix_crate(
         return Ok(fixes);
     }
 
+    if gctx.get_env_os(INTERACTIVE_ENV_INTERNAL).is_some() {
+        // Nothing is written in this mode. The suggestions from a single run
+        // of rustc are sent to the parent cargo, which reviews them once all
+        // units are done.
+        debug!("collecting suggestions for {filename:?}: {rustc}");
+        let output = rustc.output()?;
+        if output.status.success() || gctx.get_env_os(BROKEN_CODE_ENV_INTERNAL).is_some() {
+            let cwd = env::current_dir()?;
+            for (file, suggestions) in suggestions_by_file(&output, filename, args, gctx)? {
+                Message::Suggested {
+                    file: cwd.join(file),
+                    suggestions,
+                }
+                .post(gctx)?;
+            }
+        }
+        let fixes = FixedCrate {
+            files,
+            first_output: output.clone(),
+            last_output: output,
+        };
+        return Ok(fixes);
+    }
+
     // Next up, this is a bit suspicious, but we *iteratively* execute rustc and
     // collect suggestions to feed to rustfix. Once we hit our limit of times to
     // execute rustc or we appear to be reaching a fixed point we stop running
@@ -876,10 +1059,16 @@ fn rustfix_crate(
     // Any errors still remaining at this point need to be reported as probably
     // bugs in Cargo and/or rustfix.
     for (path, file) in files.iter_mut() {
-        for error in file.errors_applying_fixes.drain(..) {
+        for rejected in file.errors_applying_fixes.drain(..) {
             Message::ReplaceFailed {
                 file: path.clone(),
-                message: error,
+                message: rejected.error.to_string(),
+                suggestion: describe_suggestion(&rejected.suggestion),
+                conflicts_with: rejected
+                    .conflicts_with
+                    .iter()
+                    .map(describe_suggestion)
+                    .collect(),
             }
             .post(gctx)?;
         }
@@ -903,10 +1092,6 @@ fn rustfix_and_fix(
     args: &FixArgs,
     gctx: &GlobalContext,
 ) -> CargoResult<(Output, bool)> {
-    // If not empty, filter by these lints.
-    // TODO: implement a way to specify this.
-    let only = HashSet::new();
-
     debug!("calling rustc to collect suggestions and validate previous fixes: {rustc}");
     let output = rustc.output()?;
 
@@ -924,6 +1109,70 @@ fn rustfix_and_fix(
         return Ok((output, false));
     }
 
+    let file_map = suggestions_by_file(&output, filename, args, gctx)?;
+
+    let mut made_changes = false;
+    for (file, suggestions) in file_map {
+        // Attempt to read the source code for this file. If this fails then
+        // that'd be pretty surprising, so log a message and otherwise keep
+        // going.
+        let code = match paths::read(file.as_ref()) {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("failed to read `{}`: {}", file, e);
+                continue;
+            }
+        };
+        let num_suggestions = suggestions.len();
+        debug!("applying {} fixes to {}", num_suggestions, file);
+
+        // If this file doesn't already exist then we just read the original
+        // code, so save it. If the file already exists then the original code
+        // doesn't need to be updated as we've just read an interim state with
+        // some fixes but perhaps not all.
+        let fixed_file = files.entry(file.clone()).or_insert_with(|| FixedFile {
+            errors_applying_fixes: Vec::new(),
+            fixes_applied: 0,
+            original_code: code.clone(),
+        });
+        let mut fixed = CodeFix::new(&code);
+
+        // As mentioned above in `rustfix_crate`,
+        // we don't immediately warn about suggestions that fail to apply here,
+        // and instead we save them off for later processing.
+        //
+        // However, we don't bother reporting conflicts that exactly match prior replacements.
+        // This is currently done to reduce noise for things like rust-lang/rust#51211,
+        // although it may be removed if that's fixed deeper in the compiler.
+        let rejected = fixed.apply_all(suggestions.iter().rev());
+        fixed_file.fixes_applied += (num_suggestions - rejected.len()) as u32;
+        fixed_file
+            .errors_applying_fixes
+            .extend(rejected.into_iter().filter(|r| !r.is_identical()));
+        if fixed.modified() {
+            made_changes = true;
+            let new_code = fixed.finish()?;
+            paths::write(&file, new_code)?;
+        }
+    }
+
+    Ok((output, made_changes))
+}
+
+/// Collects the suggestions from the output of `rustc`, grouped by the file they apply to.
+///
+/// Suggestions without a file, or touching several files or files outside of
+/// the workspace's control, are dropped.
+fn suggestions_by_file(
+    output: &Output,
+    filename: &Path,
+    args: &FixArgs,
+    gctx: &GlobalContext,
+) -> CargoResult<HashMap<String, Vec<Suggestion>>> {
+    // If not empty, filter by these lints.
+    // TODO: implement a way to specify this.
+    let only = HashSet::new();
+
     let fix_mode = gctx
         .get_env_os("__CARGO_FIX_YOLO")
         .map(|_| rustfix::Filter::Everything)
@@ -996,56 +1245,23 @@ fn rustfix_and_fix(
         filename.display(),
     );
 
-    let mut made_changes = false;
-    for (file, suggestions) in file_map {
-        // Attempt to read the source code for this file. If this fails then
-        // that'd be pretty surprising, so log a message and otherwise keep
-        // going.
-        let code = match paths::read(file.as_ref()) {
-            Ok(s) => s,
-            Err(e) => {
-                warn!("failed to read `{}`: {}", file, e);
-                continue;
-            }
-        };
-        let num_suggestions = suggestions.len();
-        debug!("applying {} fixes to {}", num_suggestions, file);
-
-        // If this file doesn't already exist then we just read the original
-        // code, so save it. If the file already exists then the original code
-        // doesn't need to be updated as we've just read an interim state with
-        // some fixes but perhaps not all.
-        let fixed_file = files.entry(file.clone()).or_insert_with(|| FixedFile {
-            errors_applying_fixes: Vec::new(),
-            fixes_applied: 0,
-            original_code: code.clone(),
-        });
-        let mut fixed = CodeFix::new(&code);
+    Ok(file_map)
+}
 
-        for suggestion in suggestions.iter().rev() {
-            // As mentioned above in `rustfix_crate`,
-            // we don't immediately warn about suggestions that fail to apply here,
-            // and instead we save them off for later processing.
-            //
-            // However, we don't bother reporting conflicts that exactly match prior replacements.
-            // This is currently done to reduce noise for things like rust-lang/rust#51211,
-            // although it may be removed if that's fixed deeper in the compiler.
-            match fixed.apply(suggestion) {
-                Ok(()) => fixed_file.fixes_applied += 1,
-                Err(rustfix::Error::AlreadyReplaced {
-                    is_identical: true, ..
-                }) => continue,
-                Err(e) => fixed_file.errors_applying_fixes.push(e.to_string()),
-            }
-        }
-        if fixed.modified() {
-            made_changes = true;
-            let new_code = fixed.finish()?;
-            paths::write(&file, new_code)?;
-        }
+/// Describes a suggestion by its message and the lines it would have replaced.
+fn describe_suggestion(suggestion: &Suggestion) -> String {
+    let line_range = suggestion
+        .solutions
+        .iter()
+        .flat_map(|s| &s.replacements)
+        .map(|r| &r.snippet)
+        .chain(&suggestion.snippets)
+        .next()
+        .map(|snippet| snippet.line_range);
+    match line_range {
+        Some(line_range) => format!("{line_range}: {}", suggestion.message),
+        None => suggestion.message.clone(),
     }
-
-    Ok((output, made_changes))
 }
 
 fn exit_with(status: ExitStatus) -> ! {
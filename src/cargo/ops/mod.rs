@@ -2,26 +2,34 @@ use crate::sources::CRATES_IO_DOMAIN;
 
 pub use self::cargo_clean::{clean, CleanContext, CleanOptions};
 pub use self::cargo_compile::{
-    compile, compile_with_exec, compile_ws, create_bcx, print, resolve_all_features, CompileOptions,
+    compile, compile_with_exec, compile_ws, create_bcx, print, resolve_all_features,
+    CompileOptions, Shard,
 };
 pub use self::cargo_compile::{CompileFilter, FilterRule, LibRule, Packages};
 pub use self::cargo_doc::{doc, DocOptions, OutputFormat};
 pub use self::cargo_fetch::{fetch, FetchOptions};
-pub use self::cargo_install::{install, install_list};
+pub use self::cargo_install::{install, install_from_manifest, install_list, install_update_all};
 pub use self::cargo_new::{init, new, NewOptions, NewProjectKind, VersionControl};
 pub use self::cargo_output_metadata::{output_metadata, ExportInfo, OutputMetadataOptions};
 pub use self::cargo_package::{check_yanked, package, PackageOpts};
 pub use self::cargo_pkgid::pkgid;
 pub use self::cargo_read_manifest::read_package;
+pub use self::cargo_report::{
+    compare_timings, report_build_scripts, report_rebuild_reasons, report_timings,
+    CompareTimingsOptions,
+};
 pub use self::cargo_run::run;
 pub use self::cargo_test::{run_benches, run_tests, TestOptions};
 pub use self::cargo_uninstall::uninstall;
 pub use self::cargo_update::generate_lockfile;
 pub use self::cargo_update::print_lockfile_changes;
 pub use self::cargo_update::update_lockfile;
+pub use self::cargo_update::update_verified;
 pub use self::cargo_update::upgrade_manifests;
 pub use self::cargo_update::write_manifest_upgrades;
 pub use self::cargo_update::UpdateOptions;
+pub use self::cargo_update::VerifyCommand;
+pub use self::cargo_why::why;
 pub use self::common_for_install_and_uninstall::{resolve_root, InstallTracker};
 pub use self::fix::{fix, fix_exec_rustc, fix_get_proxy_lock_addr, FixOptions};
 pub use self::lockfile::{load_pkg_lockfile, resolve_to_string, write_pkg_lockfile};
@@ -55,10 +63,12 @@ mod cargo_package;
 mod cargo_pkgid;
 mod cargo_read_manifest;
 pub mod cargo_remove;
+mod cargo_report;
 mod cargo_run;
 mod cargo_test;
 mod cargo_uninstall;
 mod cargo_update;
+mod cargo_why;
 mod common_for_install_and_uninstall;
 mod fix;
 pub(crate) mod lockfile;
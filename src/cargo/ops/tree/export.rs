@@ -0,0 +1,262 @@
+//! Machine readable output of the dependency graph, for `--output-format`.
+//!
+//! Instead of a tree, these print the nodes and edges that would be displayed,
+//! each of them once. Nodes are numbered in the order they are reached from
+//! the roots, breadth-first, so the output is stable for the same graph.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+
+use anyhow::Context as _;
+use serde::Serialize;
+
+use super::format::Pattern;
+use super::graph::{Edge, Graph};
+use super::{DepFilter, EdgeKind, Node, NodeId, TreeOptions};
+use crate::core::compiler::CompileKind;
+use crate::core::dependency::DepKind;
+use crate::core::{PackageIdSpec, Workspace};
+use crate::drop_println;
+use crate::util::interning::InternedString;
+use crate::util::CargoResult;
+
+/// The nodes and edges to print, with nodes renumbered from 0.
+struct Subgraph {
+    nodes: Vec<NodeId>,
+    /// `(from, to, edge)`, with indexes into `nodes`.
+    edges: Vec<(usize, usize, Edge)>,
+    roots: Vec<usize>,
+}
+
+impl Subgraph {
+    fn new(
+        ws: &Workspace<'_>,
+        opts: &TreeOptions,
+        roots: &[NodeId],
+        pkgs_to_prune: &[PackageIdSpec],
+        graph: &Graph<'_>,
+    ) -> CargoResult<Subgraph> {
+        let (max_display_depth, filter) = DepFilter::new(ws, opts.display_depth, pkgs_to_prune)?;
+        let mut subgraph = Subgraph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            roots: Vec::new(),
+        };
+        let mut indexes = HashMap::new();
+        let mut queue = VecDeque::new();
+        for root in roots {
+            let index = subgraph.index_of(&mut indexes, *root, &mut queue, 0);
+            subgraph.roots.push(index);
+        }
+        while let Some((node, depth)) = queue.pop_front() {
+            if depth >= max_display_depth as usize {
+                continue;
+            }
+            let from = indexes[&node];
+            for kind in EDGE_KINDS {
+                for edge in graph.edges_of_kind(node, kind) {
+                    if !filter.is_displayed(ws, graph, &edge) {
+                        continue;
+                    }
+                    let to = subgraph.index_of(&mut indexes, edge.node(), &mut queue, depth + 1);
+                    subgraph.edges.push((from, to, edge));
+                }
+            }
+        }
+        Ok(subgraph)
+    }
+
+    /// Returns the index of `node`, queueing it to visit its edges if it
+    /// wasn't seen before.
+    fn index_of(
+        &mut self,
+        indexes: &mut HashMap<NodeId, usize>,
+        node: NodeId,
+        queue: &mut VecDeque<(NodeId, usize)>,
+        depth: usize,
+    ) -> usize {
+        *indexes.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            queue.push_back((node, depth));
+            self.nodes.len() - 1
+        })
+    }
+}
+
+const EDGE_KINDS: &[EdgeKind] = &[
+    EdgeKind::Dep(DepKind::Normal),
+    EdgeKind::Dep(DepKind::Build),
+    EdgeKind::Dep(DepKind::Development),
+    EdgeKind::Feature,
+];
+
+fn edge_kind_name(kind: EdgeKind) -> &'static str {
+    match kind {
+        EdgeKind::Dep(DepKind::Normal) => "normal",
+        EdgeKind::Dep(DepKind::Build) => "build",
+        EdgeKind::Dep(DepKind::Development) => "dev",
+        EdgeKind::Feature => "feature",
+    }
+}
+
+#[derive(Serialize)]
+struct JsonGraph<'a> {
+    version: u32,
+    /// The platforms dependencies were filtered for, `None` for all.
+    targets: Option<&'a [String]>,
+    /// Whether edges go from a dependency to what depends on it, with
+    /// `--invert`.
+    inverted: bool,
+    roots: Vec<usize>,
+    nodes: Vec<JsonNode>,
+    edges: Vec<JsonEdge>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum JsonNode {
+    Package {
+        id: usize,
+        package_id: String,
+        name: InternedString,
+        version: String,
+        features: Vec<InternedString>,
+        platform: CompileKind,
+    },
+    Feature {
+        id: usize,
+        /// The node of the package this feature is for.
+        package: usize,
+        name: InternedString,
+        /// Whether the feature was enabled on the command-line.
+        command_line: bool,
+    },
+}
+
+#[derive(Serialize)]
+struct JsonEdge {
+    from: usize,
+    to: usize,
+    kind: &'static str,
+    public: bool,
+}
+
+/// Prints the graph as JSON.
+pub fn print_json(
+    ws: &Workspace<'_>,
+    opts: &TreeOptions,
+    targets: Option<&[String]>,
+    roots: &[NodeId],
+    pkgs_to_prune: &[PackageIdSpec],
+    graph: &Graph<'_>,
+) -> CargoResult<()> {
+    let mut subgraph = Subgraph::new(ws, opts, roots, pkgs_to_prune, graph)?;
+    // Feature nodes refer to their package, which may not be displayed
+    // otherwise, like with `--depth`.
+    let mut indexes: HashMap<NodeId, usize> = subgraph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (*node, index))
+        .collect();
+    let mut i = 0;
+    while i < subgraph.nodes.len() {
+        if let Node::Feature { node_index, .. } = graph.node(subgraph.nodes[i]) {
+            if !indexes.contains_key(node_index) {
+                indexes.insert(*node_index, subgraph.nodes.len());
+                subgraph.nodes.push(*node_index);
+            }
+        }
+        i += 1;
+    }
+
+    let nodes = subgraph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(id, node_id)| match graph.node(*node_id) {
+            Node::Package {
+                package_id,
+                features,
+                kind,
+            } => JsonNode::Package {
+                id,
+                package_id: package_id.to_spec().to_string(),
+                name: package_id.name(),
+                version: package_id.version().to_string(),
+                features: features.clone(),
+                platform: *kind,
+            },
+            Node::Feature { node_index, name } => JsonNode::Feature {
+                id,
+                package: indexes[node_index],
+                name: *name,
+                command_line: graph.is_cli_feature(*node_id),
+            },
+        })
+        .collect();
+    let edges = subgraph
+        .edges
+        .iter()
+        .map(|(from, to, edge)| JsonEdge {
+            from: *from,
+            to: *to,
+            kind: edge_kind_name(edge.kind()),
+            // Only dependencies can be public.
+            public: edge.kind() != EdgeKind::Feature && edge.public(),
+        })
+        .collect();
+    let json = JsonGraph {
+        version: 1,
+        targets,
+        inverted: !opts.invert.is_empty(),
+        roots: subgraph.roots,
+        nodes,
+        edges,
+    };
+    drop_println!(ws.gctx(), "{}", serde_json::to_string(&json)?);
+    Ok(())
+}
+
+/// Prints the graph in the DOT language of Graphviz.
+pub fn print_dot(
+    ws: &Workspace<'_>,
+    opts: &TreeOptions,
+    roots: &[NodeId],
+    pkgs_to_prune: &[PackageIdSpec],
+    graph: &Graph<'_>,
+) -> CargoResult<()> {
+    let format = Pattern::new(&opts.format)
+        .with_context(|| format!("tree format `{}` not valid", opts.format))?;
+    let subgraph = Subgraph::new(ws, opts, roots, pkgs_to_prune, graph)?;
+
+    let mut dot = String::from("digraph {\n");
+    if !opts.invert.is_empty() {
+        // Edges go from a dependency to what depends on it, so draw them
+        // upwards to keep dependencies below.
+        dot.push_str("    rankdir=BT;\n");
+    }
+    for (id, node_id) in subgraph.nodes.iter().enumerate() {
+        let label = escape_dot(&format.display(graph, *node_id).to_string());
+        match graph.node(*node_id) {
+            Node::Package { .. } => writeln!(dot, "    {id} [label=\"{label}\"];")?,
+            Node::Feature { .. } => writeln!(dot, "    {id} [label=\"{label}\", shape=box];")?,
+        }
+    }
+    for (from, to, edge) in &subgraph.edges {
+        let attrs = match edge.kind() {
+            EdgeKind::Dep(DepKind::Normal) => "",
+            EdgeKind::Dep(DepKind::Build) => " [label=\"build\", color=blue]",
+            EdgeKind::Dep(DepKind::Development) => " [label=\"dev\", color=cyan4]",
+            EdgeKind::Feature => " [style=dashed, color=magenta]",
+        };
+        writeln!(dot, "    {from} -> {to}{attrs};")?;
+    }
+    dot.push('}');
+    drop_println!(ws.gctx(), "{dot}");
+    Ok(())
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
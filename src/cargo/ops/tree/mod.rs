@@ -9,10 +9,11 @@ use crate::ops::{self, Packages};
 use crate::util::CargoResult;
 use crate::{drop_print, drop_println};
 use anyhow::Context as _;
-use graph::Graph;
+use graph::{Edge, Graph};
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
+mod export;
 mod format;
 mod graph;
 
@@ -49,6 +50,8 @@ pub struct TreeOptions {
     pub display_depth: DisplayDepth,
     /// Excludes proc-macro dependencies.
     pub no_proc_macro: bool,
+    /// Whether to print a tree, or the graph in another format.
+    pub output_format: OutputFormat,
 }
 
 #[derive(PartialEq)]
@@ -88,6 +91,26 @@ impl FromStr for Prefix {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Dot,
+}
+
+impl FromStr for OutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<OutputFormat, &'static str> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "dot" => Ok(OutputFormat::Dot),
+            _ => Err("invalid output format"),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum DisplayDepth {
     MaxDisplayDepth(u32),
@@ -233,7 +256,31 @@ pub fn build_and_print(ws: &Workspace<'_>, opts: &TreeOptions) -> CargoResult<()
         try to use option `--target all` first, and then narrow your search scope accordingly.",
         )?;
     } else {
-        print(ws, opts, root_indexes, &pkgs_to_prune, &graph)?;
+        match opts.output_format {
+            OutputFormat::Text => print(ws, opts, root_indexes, &pkgs_to_prune, &graph)?,
+            OutputFormat::Json => {
+                let targets = match opts.target {
+                    Target::All => None,
+                    Target::Host | Target::Specific(_) => Some(
+                        requested_kinds
+                            .iter()
+                            .map(|kind| target_data.short_name(kind).to_string())
+                            .collect::<Vec<_>>(),
+                    ),
+                };
+                export::print_json(
+                    ws,
+                    opts,
+                    targets.as_deref(),
+                    &root_indexes,
+                    &pkgs_to_prune,
+                    &graph,
+                )?
+            }
+            OutputFormat::Dot => {
+                export::print_dot(ws, opts, &root_indexes, &pkgs_to_prune, &graph)?
+            }
+        }
     }
     Ok(())
 }
@@ -419,16 +466,7 @@ fn print_dependencies<'a>(
         }
     }
 
-    let (max_display_depth, filter_non_workspace_member, filter_private) = match display_depth {
-        DisplayDepth::MaxDisplayDepth(max) => (max, false, false),
-        DisplayDepth::Workspace => (u32::MAX, true, false),
-        DisplayDepth::Public => {
-            if !ws.gctx().cli_unstable().unstable_options {
-                anyhow::bail!("`--depth public` requires `-Zunstable-options`")
-            }
-            (u32::MAX, false, true)
-        }
-    };
+    let (max_display_depth, filter) = DepFilter::new(ws, display_depth, pkgs_to_prune)?;
 
     // Current level exceeds maximum display depth. Skip.
     if levels_continue.len() + 1 > max_display_depth as usize {
@@ -437,26 +475,7 @@ fn print_dependencies<'a>(
 
     let mut it = deps
         .iter()
-        .filter(|dep| {
-            // Filter out packages to prune.
-            match graph.node(dep.node()) {
-                Node::Package { package_id, .. } => {
-                    if filter_non_workspace_member && !ws.is_member_id(*package_id) {
-                        return false;
-                    }
-                    if filter_private && !dep.public() {
-                        return false;
-                    }
-                    !pkgs_to_prune.iter().any(|spec| spec.matches(*package_id))
-                }
-                Node::Feature { .. } => {
-                    if filter_private && !dep.public() {
-                        return false;
-                    }
-                    true
-                }
-            }
-        })
+        .filter(|dep| filter.is_displayed(ws, graph, dep))
         .peekable();
 
     while let Some(dependency) = it.next() {
@@ -482,6 +501,65 @@ fn print_dependencies<'a>(
     Ok(())
 }
 
+/// Which dependencies are displayed, according to `--depth` and `--prune`.
+struct DepFilter<'a> {
+    pkgs_to_prune: &'a [PackageIdSpec],
+    /// Only displays workspace members, for `--depth workspace`.
+    workspace_only: bool,
+    /// Only displays public dependencies, for `--depth public`.
+    public_only: bool,
+}
+
+impl<'a> DepFilter<'a> {
+    /// Returns the maximum depth to display, along with the filter.
+    fn new(
+        ws: &Workspace<'_>,
+        display_depth: DisplayDepth,
+        pkgs_to_prune: &'a [PackageIdSpec],
+    ) -> CargoResult<(u32, DepFilter<'a>)> {
+        let (max_display_depth, workspace_only, public_only) = match display_depth {
+            DisplayDepth::MaxDisplayDepth(max) => (max, false, false),
+            DisplayDepth::Workspace => (u32::MAX, true, false),
+            DisplayDepth::Public => {
+                if !ws.gctx().cli_unstable().unstable_options {
+                    anyhow::bail!("`--depth public` requires `-Zunstable-options`")
+                }
+                (u32::MAX, false, true)
+            }
+        };
+        let filter = DepFilter {
+            pkgs_to_prune,
+            workspace_only,
+            public_only,
+        };
+        Ok((max_display_depth, filter))
+    }
+
+    fn is_displayed(&self, ws: &Workspace<'_>, graph: &Graph<'_>, dep: &Edge) -> bool {
+        // Filter out packages to prune.
+        match graph.node(dep.node()) {
+            Node::Package { package_id, .. } => {
+                if self.workspace_only && !ws.is_member_id(*package_id) {
+                    return false;
+                }
+                if self.public_only && !dep.public() {
+                    return false;
+                }
+                !self
+                    .pkgs_to_prune
+                    .iter()
+                    .any(|spec| spec.matches(*package_id))
+            }
+            Node::Feature { .. } => {
+                if self.public_only && !dep.public() {
+                    return false;
+                }
+                true
+            }
+        }
+    }
+}
+
 fn edge_line_color(kind: EdgeKind) -> anstyle::Style {
     match kind {
         EdgeKind::Dep(DepKind::Normal) => anstyle::Style::new() | anstyle::Effects::DIMMED,
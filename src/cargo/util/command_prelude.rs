@@ -366,7 +366,9 @@ pub trait CommandExt: Sized {
 
     fn arg_build_plan(self) -> Self {
         self._arg(
-            flag("build-plan", "Output the build plan in JSON (unstable)")
+            optional_opt("build-plan", "Output the build plan in JSON (unstable)")
+                .value_name("VERSION")
+                .require_equals(true)
                 .help_heading(heading::COMPILATION_OPTIONS),
         )
     }
@@ -394,6 +396,14 @@ pub trait CommandExt: Sized {
                 .add(clap_complete::ArgValueCandidates::new(get_bin_candidates)),
         )
         ._arg(flag("lib", "Use a library template"))
+        ._arg(
+            opt(
+                "template",
+                "Create the package from the files of a template directory or git repository (unstable)",
+            )
+            .value_name("PATH|URL")
+            .conflicts_with_all(["bin", "lib", "registry"]),
+        )
         ._arg(
             opt("edition", "Edition to set for the crate generated")
                 .value_parser(Edition::CLI_VALUES)
@@ -784,7 +794,16 @@ Run `{cmd}` to see possible targets."
         )?;
         build_config.message_format = message_format.unwrap_or(MessageFormat::Human);
         build_config.requested_profile = self.get_profile_name("dev", profile_checking)?;
-        build_config.build_plan = self.flag("build-plan");
+        if self._contains("build-plan") {
+            build_config.build_plan = true;
+            build_config.build_plan_version = match self._value_of("build-plan") {
+                None | Some("1") => 1,
+                Some("2") => 2,
+                Some(version) => {
+                    bail!("invalid build plan version `{version}`, expected `1` or `2`")
+                }
+            };
+        }
         build_config.unit_graph = self.flag("unit-graph");
         build_config.future_incompat_report = self.flag("future-incompat-report");
 
@@ -843,6 +862,7 @@ Run `{cmd}` to see possible targets."
             target_rustc_crate_types: None,
             rustdoc_document_private_items: false,
             honor_rust_version: self.honor_rust_version(),
+            shard: None,
         };
 
         if let Some(ws) = workspace {
@@ -893,6 +913,10 @@ Run `{cmd}` to see possible targets."
             "none" => VersionControl::NoVcs,
             vcs => panic!("Impossible vcs: {:?}", vcs),
         });
+        let template = self._value_of("template").map(|s| s.to_string());
+        if template.is_some() {
+            gctx.cli_unstable().fail_if_stable_opt("--template", None)?;
+        }
         NewOptions::new(
             vcs,
             self.flag("bin"),
@@ -901,6 +925,7 @@ Run `{cmd}` to see possible targets."
             self._value_of("name").map(|s| s.to_string()),
             self._value_of("edition").map(|s| s.to_string()),
             self.registry(gctx)?,
+            template,
         )
     }
 
@@ -400,6 +400,12 @@ impl GlobalContext {
         self.registry_base_path().join("src")
     }
 
+    /// Gets the directory of compiled units shared between workspaces
+    /// (`<cargo_home>/build-cache`).
+    pub fn shared_build_cache_path(&self) -> Filesystem {
+        self.home_path.join("build-cache")
+    }
+
     /// Gets the default Cargo registry.
     pub fn default_registry(&self) -> CargoResult<Option<String>> {
         Ok(self
@@ -1189,6 +1195,16 @@ impl GlobalContext {
         &self.unstable_flags
     }
 
+    /// The `--config` arguments passed on the command line.
+    pub fn cli_config_args(&self) -> &[String] {
+        self.cli_config.as_deref().unwrap_or_default()
+    }
+
+    /// The `-Z` flags passed on the command line, without the `-Z`.
+    pub fn cli_unstable_args(&self) -> &[String] {
+        self.unstable_flags_cli.as_deref().unwrap_or_default()
+    }
+
     pub fn extra_verbose(&self) -> bool {
         self.extra_verbose
     }
@@ -1610,7 +1626,7 @@ impl GlobalContext {
     /// Both 'config.toml' and 'credentials.toml' should be valid with or without extension.
     /// When both exist, we want to prefer the one without an extension for
     /// backwards compatibility, but warn the user appropriately.
-    fn get_file_path(
+    pub(crate) fn get_file_path(
         &self,
         dir: &Path,
         filename_without_extension: &str,
@@ -2751,6 +2767,9 @@ pub struct CargoBuildConfig {
     pub warnings: Option<WarningHandling>,
     /// Unstable feature `-Zsbom`.
     pub sbom: Option<bool>,
+    pub checksum_freshness: Option<bool>,
+    pub memory_limit: Option<String>,
+    pub sample_free_memory: Option<bool>,
 }
 
 /// Whether warnings should warn, be allowed, or cause an error.
@@ -95,6 +95,12 @@ impl<N: Hash + Eq + Clone, E: Eq + Hash + Clone, V> DependencyQueue<N, E, V> {
         self.cost.insert(key, cost);
     }
 
+    /// Iterates over all nodes that haven't been dequeued, along with their
+    /// values.
+    pub fn iter(&self) -> impl Iterator<Item = (&N, &V)> {
+        self.dep_map.iter().map(|(key, (_, value))| (key, value))
+    }
+
     /// All nodes have been added, calculate some internal metadata and prepare
     /// for `dequeue`.
     pub fn queue_finished(&mut self) {
@@ -1,16 +1,17 @@
 //! A small TCP server to handle collection of diagnostics information in a
 //! cross-platform way for the `cargo fix` command.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::io::{BufReader, Read, Write};
 use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
 
 use anyhow::{Context as _, Error};
 use cargo_util::ProcessBuilder;
+use rustfix::Suggestion;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
@@ -44,11 +45,22 @@ pub enum Message {
     ReplaceFailed {
         file: String,
         message: String,
+        /// The suggestion that was skipped.
+        suggestion: String,
+        /// The already applied suggestions it overlapped with.
+        conflicts_with: Vec<String>,
     },
     EditionAlreadyEnabled {
         message: String,
         edition: Edition,
     },
+    /// Suggestions for a file that were collected instead of applied,
+    /// for `cargo fix --interactive`.
+    Suggested {
+        /// Absolute path of the file, as the proxy may not share our cwd.
+        file: PathBuf,
+        suggestions: Vec<Suggestion>,
+    },
 }
 
 impl Message {
@@ -124,7 +136,12 @@ impl<'a> DiagnosticPrinter<'a> {
                 let msg = format!("{} ({} {})", file, fixes, msg);
                 self.gctx.shell().status("Fixed", msg)
             }
-            Message::ReplaceFailed { file, message } => {
+            Message::ReplaceFailed {
+                file,
+                message,
+                suggestion,
+                conflicts_with,
+            } => {
                 let msg = format!("error applying suggestions to `{}`\n", file);
                 self.gctx.shell().warn(&msg)?;
                 write!(
@@ -132,6 +149,18 @@ impl<'a> DiagnosticPrinter<'a> {
                     "The full error message was:\n\n> {}\n\n",
                     message,
                 )?;
+                write!(
+                    self.gctx.shell().err(),
+                    "The skipped suggestion was:\n\n  * {}\n\n",
+                    suggestion,
+                )?;
+                if !conflicts_with.is_empty() {
+                    writeln!(self.gctx.shell().err(), "It overlapped with:\n")?;
+                    for other in conflicts_with {
+                        writeln!(self.gctx.shell().err(), "  * {}", other)?;
+                    }
+                    writeln!(self.gctx.shell().err())?;
+                }
                 let issue_link = get_bug_report_url(self.workspace_wrapper);
                 write!(
                     self.gctx.shell().err(),
@@ -224,6 +253,8 @@ https://doc.rust-lang.org/edition-guide/editions/transitioning-an-existing-proje
                     self.gctx.shell().warn(message)
                 }
             }
+            // These are collected by the server rather than printed.
+            Message::Suggested { .. } => Ok(()),
         }
     }
 }
@@ -253,10 +284,14 @@ fn get_bug_report_url(rustc_workspace_wrapper: &Option<PathBuf>) -> &str {
     issue_link
 }
 
+/// Suggestions collected from [`Message::Suggested`], keyed by file.
+pub type SuggestedFixes = Arc<Mutex<BTreeMap<PathBuf, Vec<Suggestion>>>>;
+
 #[derive(Debug)]
 pub struct RustfixDiagnosticServer {
     listener: TcpListener,
     addr: SocketAddr,
+    suggested: SuggestedFixes,
 }
 
 pub struct StartedServer {
@@ -271,13 +306,25 @@ impl RustfixDiagnosticServer {
             .context("failed to bind TCP listener to manage locking")?;
         let addr = listener.local_addr()?;
 
-        Ok(RustfixDiagnosticServer { listener, addr })
+        Ok(RustfixDiagnosticServer {
+            listener,
+            addr,
+            suggested: Default::default(),
+        })
     }
 
     pub fn configure(&self, process: &mut ProcessBuilder) {
         process.env(DIAGNOSTICS_SERVER_VAR, self.addr.to_string());
     }
 
+    /// Returns the suggestions the server will collect from [`Message::Suggested`].
+    ///
+    /// The same suggestion may be reported by several units sharing a file,
+    /// so duplicates are dropped.
+    pub fn suggested(&self) -> SuggestedFixes {
+        self.suggested.clone()
+    }
+
     pub fn start<F>(self, on_message: F) -> Result<StartedServer, Error>
     where
         F: Fn(Message) + Send + 'static,
@@ -307,6 +354,15 @@ impl RustfixDiagnosticServer {
                 warn!("diagnostic server failed to read: {}", e);
             } else {
                 match serde_json::from_str(&s) {
+                    Ok(Message::Suggested { file, suggestions }) => {
+                        let mut suggested = self.suggested.lock().unwrap();
+                        let collected = suggested.entry(file).or_default();
+                        for suggestion in suggestions {
+                            if !collected.contains(&suggestion) {
+                                collected.push(suggestion);
+                            }
+                        }
+                    }
                     Ok(message) => on_message(message),
                     Err(e) => warn!("invalid diagnostics message: {}", e),
                 }
@@ -35,6 +35,9 @@ use sys::*;
 pub struct FileLock {
     f: Option<File>,
     path: PathBuf,
+    /// Whether the PID of this process was written to the file, see
+    /// [`FileLock::record_holder`].
+    holder: bool,
 }
 
 impl FileLock {
@@ -56,6 +59,21 @@ impl FileLock {
         self.path.parent().unwrap()
     }
 
+    /// Writes the PID of this process to the locked file, so that processes
+    /// waiting for the lock can tell who holds it.
+    ///
+    /// This must only be used for exclusive locks on files that have no
+    /// content of their own. The file is truncated again once the lock is
+    /// released.
+    pub fn record_holder(&mut self) -> CargoResult<()> {
+        let mut f = self.file();
+        f.set_len(0)?;
+        f.seek(SeekFrom::Start(0))?;
+        write!(f, "{}", std::process::id())?;
+        self.holder = true;
+        Ok(())
+    }
+
     /// Removes all sibling files to this locked file.
     ///
     /// This can be useful if a directory is locked with a sentinel file but it
@@ -103,6 +121,9 @@ impl Write for FileLock {
 impl Drop for FileLock {
     fn drop(&mut self) {
         if let Some(f) = self.f.take() {
+            if self.holder {
+                let _ = f.set_len(0);
+            }
             if let Err(e) = unlock(&f) {
                 tracing::warn!("failed to release lock: {e:?}");
             }
@@ -219,7 +240,11 @@ impl Filesystem {
         acquire(gctx, msg, &path, &|| try_lock_exclusive(&f), &|| {
             lock_exclusive(&f)
         })?;
-        Ok(FileLock { f: Some(f), path })
+        Ok(FileLock {
+            f: Some(f),
+            path,
+            holder: false,
+        })
     }
 
     /// A non-blocking version of [`Filesystem::open_rw_exclusive_create`].
@@ -234,7 +259,11 @@ impl Filesystem {
         opts.read(true).write(true).create(true);
         let (path, f) = self.open(path.as_ref(), &opts, true)?;
         if try_acquire(&path, &|| try_lock_exclusive(&f))? {
-            Ok(Some(FileLock { f: Some(f), path }))
+            Ok(Some(FileLock {
+                f: Some(f),
+                path,
+                holder: false,
+            }))
         } else {
             Ok(None)
         }
@@ -262,7 +291,11 @@ impl Filesystem {
         acquire(gctx, msg, &path, &|| try_lock_shared(&f), &|| {
             lock_shared(&f)
         })?;
-        Ok(FileLock { f: Some(f), path })
+        Ok(FileLock {
+            f: Some(f),
+            path,
+            holder: false,
+        })
     }
 
     /// Opens read-only shared access to a file, returning the locked version of a file.
@@ -282,7 +315,11 @@ impl Filesystem {
         acquire(gctx, msg, &path, &|| try_lock_shared(&f), &|| {
             lock_shared(&f)
         })?;
-        Ok(FileLock { f: Some(f), path })
+        Ok(FileLock {
+            f: Some(f),
+            path,
+            holder: false,
+        })
     }
 
     /// A non-blocking version of [`Filesystem::open_ro_shared_create`].
@@ -297,7 +334,11 @@ impl Filesystem {
         opts.read(true).write(true).create(true);
         let (path, f) = self.open(path.as_ref(), &opts, true)?;
         if try_acquire(&path, &|| try_lock_shared(&f))? {
-            Ok(Some(FileLock { f: Some(f), path }))
+            Ok(Some(FileLock {
+                f: Some(f),
+                path,
+                holder: false,
+            }))
         } else {
             Ok(None)
         }
@@ -399,7 +440,10 @@ fn acquire(
     if try_acquire(path, lock_try)? {
         return Ok(());
     }
-    let msg = format!("waiting for file lock on {}", msg);
+    let msg = match holder_pid(path) {
+        Some(pid) => format!("waiting for file lock on {msg} (held by PID {pid})"),
+        None => format!("waiting for file lock on {}", msg),
+    };
     gctx.shell()
         .status_with_color("Blocking", &msg, &style::NOTE)?;
 
@@ -407,6 +451,12 @@ fn acquire(
     Ok(())
 }
 
+/// Reads the PID recorded by [`FileLock::record_holder`] in a lock file, if
+/// any.
+fn holder_pid(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
 #[cfg(all(target_os = "linux", not(target_env = "musl")))]
 fn is_on_nfs_mount(path: &Path) -> bool {
     use std::ffi::CString;
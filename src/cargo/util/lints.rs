@@ -1,7 +1,8 @@
-use crate::core::{Edition, Feature, Features, Manifest, Package};
+use crate::core::dependency::DepKind;
+use crate::core::{Dependency, Edition, Feature, Features, Manifest, Package};
 use crate::{CargoResult, GlobalContext};
 use annotate_snippets::{Level, Snippet};
-use cargo_util_schemas::manifest::{TomlLintLevel, TomlToolLints};
+use cargo_util_schemas::manifest::{TomlLintLevel, TomlLints, TomlToolLints};
 use pathdiff::diff_paths;
 use std::fmt::Display;
 use std::ops::Range;
@@ -9,7 +10,7 @@ use std::path::Path;
 use toml_edit::ImDocument;
 
 const LINT_GROUPS: &[LintGroup] = &[TEST_DUMMY_UNSTABLE];
-pub const LINTS: &[Lint] = &[IM_A_TEAPOT, UNKNOWN_LINTS];
+pub const LINTS: &[Lint] = &[IM_A_TEAPOT, UNKNOWN_LINTS, UNUSED_DEPENDENCIES];
 
 pub fn analyze_cargo_lints_table(
     pkg: &Package,
@@ -605,6 +606,128 @@ fn output_unknown_lints(
     Ok(())
 }
 
+/// Returns the `[lints.cargo]` table of `pkg`, with `[workspace.lints]`
+/// already inherited.
+pub fn cargo_lints(pkg: &Package) -> TomlToolLints {
+    pkg.manifest()
+        .normalized_toml()
+        .lints
+        .clone()
+        .map(|lints| lints.lints)
+        .unwrap_or(TomlLints::default())
+        .get("cargo")
+        .cloned()
+        .unwrap_or(TomlToolLints::default())
+}
+
+const UNUSED_DEPENDENCIES: Lint = Lint {
+    name: "unused_dependencies",
+    desc: "unused dependency",
+    groups: &[],
+    default_level: LintLevel::Allow,
+    edition_lint_opts: None,
+    feature_gate: None,
+    docs: Some(
+        r#"
+### What it does
+Checks for dependencies that are not used by any target of the package that
+was built.
+
+This is checked after a build, like `cargo check`, with the files rustc reports
+to have loaded, so it requires a nightly toolchain. Only the targets that were
+built are considered, so use `--all-targets` to take tests, examples, and
+benchmarks into account. `[dev-dependencies]` are not checked as they may only
+be used by doctests.
+
+### Why it is bad
+Unused dependencies still need to be downloaded and built, slowing down builds
+for no benefit.
+
+### Example
+```toml
+[dependencies]
+regex = "1.0" # never used
+```
+"#,
+    ),
+};
+
+/// Whether the `unused_dependencies` lint is enabled for `pkg`.
+pub fn unused_dependencies_enabled(pkg: &Package) -> bool {
+    let manifest = pkg.manifest();
+    let (lint_level, _) = UNUSED_DEPENDENCIES.level(
+        &cargo_lints(pkg),
+        manifest.edition(),
+        manifest.unstable_features(),
+    );
+    lint_level != LintLevel::Allow
+}
+
+pub fn check_unused_dependencies(
+    pkg: &Package,
+    unused: &[&Dependency],
+    error_count: &mut usize,
+    gctx: &GlobalContext,
+) -> CargoResult<()> {
+    let manifest = pkg.manifest();
+    let (lint_level, reason) = UNUSED_DEPENDENCIES.level(
+        &cargo_lints(pkg),
+        manifest.edition(),
+        manifest.unstable_features(),
+    );
+    if lint_level == LintLevel::Allow {
+        return Ok(());
+    }
+
+    let level = lint_level.to_diagnostic_level();
+    let manifest_path = rel_cwd_manifest_path(pkg.manifest_path(), gctx);
+    let emitted_reason = format!(
+        "`cargo::{}` is set to `{lint_level}` {reason}",
+        UNUSED_DEPENDENCIES.name
+    );
+    for (i, dep) in unused.iter().enumerate() {
+        if lint_level == LintLevel::Forbid || lint_level == LintLevel::Deny {
+            *error_count += 1;
+        }
+        let title = format!("{}: `{}`", UNUSED_DEPENDENCIES.desc, dep.name_in_toml());
+        let mut message = match dependency_span(manifest.document(), dep) {
+            Some(span) => level.title(&title).snippet(
+                Snippet::source(manifest.contents())
+                    .origin(&manifest_path)
+                    .annotation(level.span(span))
+                    .fold(true),
+            ),
+            None => level.title(&title),
+        };
+        if i == 0 {
+            message = message.footer(Level::Note.title(&emitted_reason));
+        }
+        gctx.shell().print_message(message)?;
+    }
+    Ok(())
+}
+
+/// Finds where `dep` is declared in the manifest, including its value.
+fn dependency_span(document: &ImDocument<String>, dep: &Dependency) -> Option<Range<usize>> {
+    let tables: &[&str] = match dep.kind() {
+        DepKind::Normal => &["dependencies"],
+        DepKind::Development => &["dev-dependencies", "dev_dependencies"],
+        DepKind::Build => &["build-dependencies", "build_dependencies"],
+    };
+    let platform = dep.platform().map(|p| p.to_string());
+    let name = dep.name_in_toml();
+    tables.iter().find_map(|table| {
+        let mut path = Vec::new();
+        if let Some(platform) = &platform {
+            path.extend(["target", platform.as_str()]);
+        }
+        path.extend([*table, name.as_str()]);
+        let key_span = get_span(document, &path, false)?;
+        let value_span = get_span(document, &path, true)?;
+        Some(key_span.start..value_span.end)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
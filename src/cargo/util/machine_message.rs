@@ -103,6 +103,51 @@ impl<'a> Message for TimingInfo<'a> {
     }
 }
 
+#[derive(Serialize)]
+pub struct TestBinaryStarted<'a> {
+    pub package_id: PackageIdSpec,
+    pub target: &'a Target,
+    pub kind: &'static str,
+    pub executable: Option<&'a Path>,
+}
+
+impl<'a> Message for TestBinaryStarted<'a> {
+    fn reason(&self) -> &str {
+        "test-binary-started"
+    }
+}
+
+#[derive(Serialize)]
+pub struct TestCaseFinished<'a> {
+    pub package_id: PackageIdSpec,
+    pub target: &'a Target,
+    pub kind: &'static str,
+    pub name: &'a str,
+    pub outcome: &'static str,
+}
+
+impl<'a> Message for TestCaseFinished<'a> {
+    fn reason(&self) -> &str {
+        "test-case-finished"
+    }
+}
+
+#[derive(Serialize)]
+pub struct TestBinaryFinished<'a> {
+    pub package_id: PackageIdSpec,
+    pub target: &'a Target,
+    pub kind: &'static str,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub duration: f64,
+}
+
+impl<'a> Message for TestBinaryFinished<'a> {
+    fn reason(&self) -> &str {
+        "test-binary-finished"
+    }
+}
+
 #[derive(Serialize)]
 pub struct BuildFinished {
     pub success: bool,
@@ -2479,6 +2479,23 @@ pub fn validate_profile(
             )?;
         }
     }
+    if let Some(ref targets) = root.target {
+        match (
+            features.require(Feature::profile_target_overrides()),
+            cli_unstable.profile_target_overrides,
+        ) {
+            (Err(e), false) => return Err(e),
+            _ => {}
+        }
+        for (platform, profile) in targets {
+            platform
+                .parse::<Platform>()
+                .with_context(|| format!("invalid target `{platform}` in profile `{name}`"))?;
+            let target_name = format!("{name}.target.{platform}");
+            validate_profile_target_override(profile, &target_name)?;
+            validate_profile_layer(profile, &target_name, cli_unstable, features)?;
+        }
+    }
 
     if let Some(dir_name) = &root.dir_name {
         // This is disabled for now, as we would like to stabilize named
@@ -2571,6 +2588,33 @@ fn validate_profile_layer(
             _ => {}
         }
     }
+    if let Some(env) = &profile.env {
+        match (
+            features.require(Feature::profile_env()),
+            cli_unstable.profile_env,
+        ) {
+            (Err(e), false) => return Err(e),
+            _ => {}
+        }
+        // Same as the `[env]` table, see `GlobalContext::env_config`.
+        for disallowed in ["CARGO_HOME", "RUSTUP_HOME", "RUSTUP_TOOLCHAIN"] {
+            if env.contains_key(disallowed) {
+                bail!(
+                    "setting the `{disallowed}` environment variable is not supported \
+                     in `profile.{name}.env`"
+                );
+            }
+        }
+    }
+    if profile.linker.is_some() || profile.link_args.is_some() {
+        match (
+            features.require(Feature::profile_linker()),
+            cli_unstable.profile_linker,
+        ) {
+            (Err(e), false) => return Err(e),
+            _ => {}
+        }
+    }
     if profile.trim_paths.is_some() {
         match (
             features.require(Feature::trim_paths()),
@@ -2591,6 +2635,9 @@ fn validate_profile_override(profile: &manifest::TomlProfile, which: &str) -> Ca
     if profile.build_override.is_some() {
         bail!("build-override profiles cannot be nested");
     }
+    if profile.target.is_some() {
+        bail!("target-specific profiles cannot be nested");
+    }
     if profile.panic.is_some() {
         bail!("`panic` may not be specified in a `{}` profile", which)
     }
@@ -2603,6 +2650,45 @@ fn validate_profile_override(profile: &manifest::TomlProfile, which: &str) -> Ca
     Ok(())
 }
 
+/// Validation that is specific to a `target` override.
+fn validate_profile_target_override(
+    profile: &manifest::TomlProfile,
+    name: &str,
+) -> CargoResult<()> {
+    if profile.package.is_some() {
+        bail!("package-specific profiles cannot be nested");
+    }
+    if profile.build_override.is_some() {
+        bail!("build-override profiles cannot be nested");
+    }
+    if profile.target.is_some() {
+        bail!("target-specific profiles cannot be nested");
+    }
+    if profile.inherits.is_some() {
+        bail!("`inherits` may not be specified in a `target` profile")
+    }
+    if let Some(panic) = &profile.panic {
+        if panic != "unwind" && panic != "abort" {
+            bail!(
+                "`panic` setting of `{}` is not a valid setting, \
+                     must be `unwind` or `abort`",
+                panic
+            );
+        }
+    }
+
+    if let Some(manifest::StringOrBool::String(arg)) = &profile.lto {
+        if arg == "true" || arg == "false" {
+            bail!(
+                "`lto` setting of string `\"{arg}\"` for `{name}` profile is not \
+                     a valid setting, must be a boolean (`true`/`false`) or a string \
+                    (`\"thin\"`/`\"fat\"`/`\"off\"`) or omitted.",
+            );
+        }
+    }
+    Ok(())
+}
+
 fn verify_lints(
     lints: Option<&manifest::TomlLints>,
     gctx: &GlobalContext,
@@ -242,3 +242,280 @@ fn build_plan_with_dev_dep() {
         .masquerade_as_nightly_cargo(&["build-plan"])
         .run();
 }
+
+#[cargo_test]
+fn build_plan_v2_build_script() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                edition = "2015"
+                links = "foo"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build --build-plan=2 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .with_stdout_data(
+            str![[r#"
+{
+  "host_dest": "[ROOT]/foo/target/debug",
+  "inputs": [
+    "[ROOT]/foo/Cargo.toml"
+  ],
+  "steps": [
+    {
+      "args": "{...}",
+      "build_script": null,
+      "build_script_outputs": [],
+      "compile_mode": "build",
+      "cwd": "[ROOT]/foo",
+      "deps": [],
+      "env": "{...}",
+      "id": 0,
+      "kind": null,
+      "links": "{...}",
+      "outputs": "{...}",
+      "package_id": "path+[ROOTURL]/foo#0.5.0",
+      "package_name": "foo",
+      "package_version": "0.5.0",
+      "program": "rustc",
+      "target_kind": [
+        "custom-build"
+      ],
+      "target_name": "build-script-build"
+    },
+    {
+      "args": [],
+      "build_script": {
+        "library_name": "foo",
+        "links": "foo",
+        "out_dir": "[ROOT]/foo/target/debug/build/foo-[HASH]/out",
+        "output_file": "[ROOT]/foo/target/debug/build/foo-[HASH]/output",
+        "package_targets": [
+          {
+            "crate_types": [
+              "lib"
+            ],
+            "edition": "2015",
+            "kind": "lib",
+            "name": "foo",
+            "src_path": "[ROOT]/foo/src/lib.rs"
+          },
+          {
+            "crate_types": [
+              "bin"
+            ],
+            "edition": "2015",
+            "kind": "custom-build",
+            "name": "build-script-build",
+            "src_path": "[ROOT]/foo/build.rs"
+          }
+        ],
+        "rust_version": null
+      },
+      "build_script_outputs": [],
+      "compile_mode": "run-custom-build",
+      "cwd": "[ROOT]/foo",
+      "deps": [
+        0
+      ],
+      "env": "{...}",
+      "id": 1,
+      "kind": null,
+      "links": {},
+      "outputs": [],
+      "package_id": "path+[ROOTURL]/foo#0.5.0",
+      "package_name": "foo",
+      "package_version": "0.5.0",
+      "program": "[ROOT]/foo/target/debug/build/foo-[HASH]/build-script-build",
+      "target_kind": [
+        "custom-build"
+      ],
+      "target_name": "build-script-build"
+    },
+    {
+      "args": "{...}",
+      "build_script": null,
+      "build_script_outputs": [
+        {
+          "cfgs": true,
+          "dylib_path": false,
+          "env": true,
+          "link_args": [
+            "rustc-link-arg"
+          ],
+          "link_libs": true,
+          "link_search": true,
+          "metadata": false,
+          "step": 1
+        }
+      ],
+      "compile_mode": "build",
+      "cwd": "[ROOT]/foo",
+      "deps": [
+        1
+      ],
+      "env": "{...}",
+      "id": 2,
+      "kind": null,
+      "links": "{...}",
+      "outputs": "{...}",
+      "package_id": "path+[ROOTURL]/foo#0.5.0",
+      "package_name": "foo",
+      "package_version": "0.5.0",
+      "program": "rustc",
+      "target_kind": [
+        "lib"
+      ],
+      "target_name": "foo"
+    }
+  ],
+  "version": 2
+}
+"#]]
+            .is_json(),
+        )
+        .run();
+    assert!(!p.root().join("target/debug/libfoo.rlib").is_file());
+}
+
+#[cargo_test]
+fn from_plan_replays_build_scripts() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.5.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"
+                fn main() {
+                    let unused = 1;
+                    #[cfg(from_build)]
+                    println!("{} {}", env!("ANSWER"), bar::bar());
+                }
+            "#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::rustc-cfg=from_build");
+                    println!("cargo::rustc-check-cfg=cfg(from_build)");
+                    let answer = std::env::var("DEP_BAR_ANSWER").unwrap();
+                    println!("cargo::rustc-env=ANSWER={answer}");
+                }
+            "#,
+        )
+        .file(
+            "bar/Cargo.toml",
+            r#"
+                [package]
+                name = "bar"
+                version = "0.0.1"
+                edition = "2015"
+                links = "bar"
+            "#,
+        )
+        .file("bar/src/lib.rs", "pub fn bar() -> &'static str { \"bar\" }")
+        .file(
+            "bar/build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::metadata=answer=42");
+                }
+            "#,
+        )
+        .build();
+
+    let output = p
+        .cargo("build --build-plan=2 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .exec_with_output()
+        .unwrap();
+    p.change_file("plan.json", std::str::from_utf8(&output.stdout).unwrap());
+
+    p.cargo("build --from-plan plan.json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .with_stderr_data(str![[r#"
+[COMPILING] bar v0.0.1
+[COMPILING] foo v0.5.0
+[WARNING] unused variable: `unused`
+...
+[FINISHED] replaying build plan in [ELAPSED]s
+
+"#]])
+        .run();
+    p.process(&p.bin("foo"))
+        .with_stdout_data(str![[r#"
+42 bar
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn from_plan_requires_version_2() {
+    let p = project().file("src/lib.rs", "").build();
+
+    let output = p
+        .cargo("build --build-plan -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .exec_with_output()
+        .unwrap();
+    p.change_file("plan.json", std::str::from_utf8(&output.stdout).unwrap());
+
+    p.cargo("build --from-plan plan.json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] build plan `[ROOT]/foo/plan.json` can't be replayed, only version 2 of the format can
+[HELP] generate it with `cargo build --build-plan=2`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn from_plan_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build --from-plan plan.json")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--from-plan` flag is unstable, pass `-Z unstable-options` to enable it
+See https://github.com/rust-lang/cargo/issues/5579 for more information about the `--from-plan` flag.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn build_plan_invalid_version() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build --build-plan=3 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["build-plan"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] invalid build plan version `3`, expected `1` or `2`
+
+"#]])
+        .run();
+}
@@ -0,0 +1,184 @@
+//! Tests for `cargo report rebuild-reasons` and `cargo report build-scripts`.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::{project, str};
+
+#[cargo_test]
+fn rebuild_reasons() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .run();
+    p.cargo("report rebuild-reasons -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_stdout_data(str![[r#"
+2 of 2 units were rebuilt in the last build:
+  foo v0.0.1 lib: fresh build
+  foo v0.0.1 bin "foo": fresh build
+
+"#]])
+        .run();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .run();
+    p.cargo("report rebuild-reasons -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_stdout_data(str![[r#"
+all 2 units were fresh in the last build
+
+"#]])
+        .run();
+
+    p.change_file("src/lib.rs", "pub fn f() {}");
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .run();
+    p.cargo("report rebuild-reasons -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_stdout_data(str![[r#"
+2 of 2 units were rebuilt in the last build:
+  foo v0.0.1 lib: the file `src/lib.rs` has changed ([..])
+  foo v0.0.1 bin "foo": the dependency foo was rebuilt
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn rebuild_reasons_failed_build() {
+    let p = project().file("src/lib.rs", "invalid").build();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[ERROR] expected one of `!` or `::`, found `<eof>`
+...
+"#]])
+        .run();
+    p.cargo("report rebuild-reasons -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_stdout_data(str![[r#"
+the last build failed
+1 of 1 units were rebuilt in the last build:
+  foo v0.0.1 lib: fresh build
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn build_scripts() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo::rerun-if-changed=build.rs");
+                    println!("cargo::rerun-if-env-changed=FOO");
+                    println!("cargo::rustc-check-cfg=cfg(foo)");
+                    println!("cargo::rustc-cfg=foo");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .run();
+    p.cargo("report build-scripts -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_stdout_data(str![[r#"
+foo v0.0.1: ran in [..]s
+  rerun-if-changed: build.rs
+  rerun-if-env-changed: FOO
+  cfg: foo
+
+"#]])
+        .run();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .run();
+    p.cargo("report build-scripts -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_stdout_data(str![[r#"
+foo v0.0.1: fresh
+  rerun-if-changed: build.rs
+  rerun-if-env-changed: FOO
+  cfg: foo
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn build_scripts_without_rerun_if() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .run();
+    p.cargo("report build-scripts -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_stdout_data(str![[r#"
+foo v0.0.1: ran in [..]s
+  no rerun-if instructions, reruns when any file of the package changes
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn no_build_report() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("report rebuild-reasons -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] no build report is available, run a build with `-Zunstable-options` first
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn not_saved_without_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build").run();
+    assert!(!p.build_dir().join(".last-build-report.json").exists());
+    p.cargo("report rebuild-reasons -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] no build report is available, run a build with `-Zunstable-options` first
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("report build-scripts")
+        .masquerade_as_nightly_cargo(&["report"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `cargo report build-scripts` command is unstable, pass `-Z unstable-options` to enable it
+
+"#]])
+        .run();
+}
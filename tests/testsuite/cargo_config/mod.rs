@@ -1,6 +1,7 @@
 //! Tests for the `cargo config` command.
 
 use super::config::write_config_at;
+use cargo_test_support::compare::assert_e2e;
 use cargo_test_support::paths;
 use cargo_test_support::prelude::*;
 use cargo_test_support::str;
@@ -574,6 +575,190 @@ profile.dev.package.foo.opt-level = 1
 target.'cfg(target_os = "linux")'.runner = "runme"
 
 
+"#]])
+        .with_stderr_data(str![[r#""#]])
+        .run();
+}
+
+#[cargo_test]
+fn set_project() {
+    let cwd = paths::root().join("foo");
+    fs::create_dir_all(&cwd).unwrap();
+
+    for (key, value) in [
+        ("build.jobs", "4"),
+        ("build.rustflags", r#"["-C", "debuginfo=1"]"#),
+        ("target.'cfg(unix)'.runner", "my-runner"),
+        ("net.offline", "true"),
+    ] {
+        cargo_process("config set -Zunstable-options")
+            .arg(key)
+            .arg(value)
+            .cwd(&cwd)
+            .masquerade_as_nightly_cargo(&["cargo-config"])
+            .run();
+    }
+
+    assert_e2e().eq(
+        fs::read_to_string(cwd.join(".cargo/config.toml")).unwrap(),
+        str![[r#"
+[build]
+jobs = 4
+rustflags = ["-C", "debuginfo=1"]
+
+[target.'cfg(unix)']
+runner = "my-runner"
+
+[net]
+offline = true
+
+"#]],
+    );
+
+    cargo_process("config get -Zunstable-options target")
+        .cwd(&cwd)
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .with_stdout_data(str![[r#"
+target."cfg(unix)".runner = "my-runner"
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn set_preserves_formatting() {
+    let cwd = paths::root().join("foo");
+    write_config_at(
+        cwd.join(".cargo/config.toml"),
+        "\
+# Settings for the team.
+[build]
+jobs = 2 # Keep CI fast.
+target-dir = \"out\"
+",
+    );
+
+    cargo_process("config set -Zunstable-options build.jobs 8")
+        .cwd(&cwd)
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .run();
+    cargo_process("config unset -Zunstable-options build.target-dir")
+        .cwd(&cwd)
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .run();
+
+    assert_e2e().eq(
+        fs::read_to_string(cwd.join(".cargo/config.toml")).unwrap(),
+        str![[r#"
+# Settings for the team.
+[build]
+jobs = 8 # Keep CI fast.
+
+"#]],
+    );
+}
+
+#[cargo_test]
+fn set_user_and_file() {
+    let cwd = paths::root().join("foo");
+    fs::create_dir_all(&cwd).unwrap();
+
+    cargo_process("config set -Zunstable-options --user alias.b build")
+        .cwd(&cwd)
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .run();
+    assert_e2e().eq(
+        fs::read_to_string(paths::home().join(".cargo/config.toml")).unwrap(),
+        str![[r#"
+[alias]
+b = "build"
+
+"#]],
+    );
+
+    cargo_process("config set -Zunstable-options --file ci.toml build.jobs 1")
+        .cwd(&cwd)
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .run();
+    assert_e2e().eq(
+        fs::read_to_string(cwd.join("ci.toml")).unwrap(),
+        str![[r#"
+[build]
+jobs = 1
+
+"#]],
+    );
+    assert!(!cwd.join(".cargo").exists());
+}
+
+#[cargo_test]
+fn set_errors() {
+    let cwd = paths::root().join("foo");
+    write_config_at(
+        cwd.join(".cargo/config.toml"),
+        "
+        [build]
+        jobs = 2
+        ",
+    );
+
+    cargo_process("config set -Zunstable-options build.jobs.x 1")
+        .cwd(&cwd)
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] expected table for configuration key `build.jobs` in `[ROOT]/foo/.cargo/config.toml`, but found a value
+
+"#]])
+        .run();
+
+    cargo_process("config set -Zunstable-options build 1")
+        .cwd(&cwd)
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `build` is a table in `[ROOT]/foo/.cargo/config.toml`, and can't be set to a value
+
+"#]])
+        .run();
+
+    cargo_process("config unset -Zunstable-options build.target-dir")
+        .cwd(&cwd)
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `build.target-dir` is not set in `[ROOT]/foo/.cargo/config.toml`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn list() {
+    let sub_folder = common_setup();
+    cargo_process("config list -Zunstable-options")
+        .cwd(&sub_folder.parent().unwrap())
+        .masquerade_as_nightly_cargo(&["cargo-config"])
+        .env("CARGO_BUILD_JOBS", "100")
+        .with_stdout_data(str![[r#"
+alias.foo = "abc --xyz" # [ROOT]/home/.cargo/config.toml
+alias.sub-example = [
+    "sub", # [ROOT]/foo/.cargo/config.toml
+    "example", # [ROOT]/foo/.cargo/config.toml
+]
+build.jobs = 100 # environment variable `CARGO_BUILD_JOBS`
+build.rustflags = [
+    "--flag-global", # [ROOT]/home/.cargo/config.toml
+    "--flag-directory", # [ROOT]/foo/.cargo/config.toml
+]
+extra-table.somekey = "somevalue" # [ROOT]/home/.cargo/config.toml
+profile.dev.opt-level = 3 # [ROOT]/home/.cargo/config.toml
+profile.dev.package.foo.opt-level = 1 # [ROOT]/home/.cargo/config.toml
+target.'cfg(target_os = "linux")'.runner = "runme" # [ROOT]/home/.cargo/config.toml
+# The following environment variables may affect the loaded values.
+# CARGO_BUILD_JOBS=100
+# CARGO_HOME=[ROOT]/home/.cargo
+
 "#]])
         .with_stderr_data(str![[r#""#]])
         .run();
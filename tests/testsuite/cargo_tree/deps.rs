@@ -2317,3 +2317,537 @@ foo v1.0.0 ([ROOT]/foo)
 "#]])
         .run();
 }
+
+#[cargo_test]
+fn output_format_json() {
+    let p = make_simple_proj();
+
+    p.cargo("tree --output-format json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(
+            str![[r#"
+{
+  "edges": [
+    {
+      "from": 0,
+      "kind": "normal",
+      "public": false,
+      "to": 1
+    },
+    {
+      "from": 0,
+      "kind": "normal",
+      "public": false,
+      "to": 2
+    },
+    {
+      "from": 0,
+      "kind": "build",
+      "public": false,
+      "to": 3
+    },
+    {
+      "from": 0,
+      "kind": "dev",
+      "public": false,
+      "to": 4
+    },
+    {
+      "from": 1,
+      "kind": "normal",
+      "public": false,
+      "to": 5
+    },
+    {
+      "from": 3,
+      "kind": "normal",
+      "public": false,
+      "to": 5
+    },
+    {
+      "from": 4,
+      "kind": "normal",
+      "public": false,
+      "to": 5
+    },
+    {
+      "from": 5,
+      "kind": "normal",
+      "public": false,
+      "to": 2
+    }
+  ],
+  "inverted": false,
+  "nodes": [
+    {
+      "features": [],
+      "id": 0,
+      "kind": "package",
+      "name": "foo",
+      "package_id": "path+[ROOTURL]/foo#0.1.0",
+      "platform": null,
+      "version": "0.1.0"
+    },
+    {
+      "features": [],
+      "id": 1,
+      "kind": "package",
+      "name": "a",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#a@1.0.0",
+      "platform": null,
+      "version": "1.0.0"
+    },
+    {
+      "features": [],
+      "id": 2,
+      "kind": "package",
+      "name": "c",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#c@1.0.0",
+      "platform": null,
+      "version": "1.0.0"
+    },
+    {
+      "features": [],
+      "id": 3,
+      "kind": "package",
+      "name": "bdep",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#bdep@1.0.0",
+      "platform": null,
+      "version": "1.0.0"
+    },
+    {
+      "features": [],
+      "id": 4,
+      "kind": "package",
+      "name": "devdep",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#devdep@1.0.0",
+      "platform": null,
+      "version": "1.0.0"
+    },
+    {
+      "features": [],
+      "id": 5,
+      "kind": "package",
+      "name": "b",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#b@1.0.0",
+      "platform": null,
+      "version": "1.0.0"
+    }
+  ],
+  "roots": [
+    0
+  ],
+  "targets": [
+    "[HOST_TARGET]"
+  ],
+  "version": 1
+}
+"#]]
+            .is_json(),
+        )
+        .run();
+
+    p.cargo("tree --output-format json -Zunstable-options -e features --target")
+        .arg(rustc_host())
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(
+            str![[r#"
+{
+  "edges": [
+    {
+      "from": 0,
+      "kind": "normal",
+      "public": false,
+      "to": 1
+    },
+    {
+      "from": 0,
+      "kind": "normal",
+      "public": false,
+      "to": 2
+    },
+    {
+      "from": 0,
+      "kind": "build",
+      "public": false,
+      "to": 3
+    },
+    {
+      "from": 0,
+      "kind": "dev",
+      "public": false,
+      "to": 4
+    },
+    {
+      "from": 1,
+      "kind": "feature",
+      "public": false,
+      "to": 5
+    },
+    {
+      "from": 2,
+      "kind": "feature",
+      "public": false,
+      "to": 6
+    },
+    {
+      "from": 3,
+      "kind": "feature",
+      "public": false,
+      "to": 7
+    },
+    {
+      "from": 4,
+      "kind": "feature",
+      "public": false,
+      "to": 8
+    },
+    {
+      "from": 5,
+      "kind": "normal",
+      "public": false,
+      "to": 9
+    },
+    {
+      "from": 7,
+      "kind": "normal",
+      "public": false,
+      "to": 10
+    },
+    {
+      "from": 8,
+      "kind": "normal",
+      "public": false,
+      "to": 9
+    },
+    {
+      "from": 9,
+      "kind": "feature",
+      "public": false,
+      "to": 11
+    },
+    {
+      "from": 10,
+      "kind": "feature",
+      "public": false,
+      "to": 12
+    },
+    {
+      "from": 11,
+      "kind": "normal",
+      "public": false,
+      "to": 2
+    },
+    {
+      "from": 12,
+      "kind": "normal",
+      "public": false,
+      "to": 13
+    },
+    {
+      "from": 13,
+      "kind": "feature",
+      "public": false,
+      "to": 14
+    }
+  ],
+  "inverted": false,
+  "nodes": [
+    {
+      "features": [],
+      "id": 0,
+      "kind": "package",
+      "name": "foo",
+      "package_id": "path+[ROOTURL]/foo#0.1.0",
+      "platform": "[HOST_TARGET]",
+      "version": "0.1.0"
+    },
+    {
+      "command_line": false,
+      "id": 1,
+      "kind": "feature",
+      "name": "default",
+      "package": 5
+    },
+    {
+      "command_line": false,
+      "id": 2,
+      "kind": "feature",
+      "name": "default",
+      "package": 6
+    },
+    {
+      "command_line": false,
+      "id": 3,
+      "kind": "feature",
+      "name": "default",
+      "package": 7
+    },
+    {
+      "command_line": false,
+      "id": 4,
+      "kind": "feature",
+      "name": "default",
+      "package": 8
+    },
+    {
+      "features": [],
+      "id": 5,
+      "kind": "package",
+      "name": "a",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#a@1.0.0",
+      "platform": "[HOST_TARGET]",
+      "version": "1.0.0"
+    },
+    {
+      "features": [],
+      "id": 6,
+      "kind": "package",
+      "name": "c",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#c@1.0.0",
+      "platform": "[HOST_TARGET]",
+      "version": "1.0.0"
+    },
+    {
+      "features": [],
+      "id": 7,
+      "kind": "package",
+      "name": "bdep",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#bdep@1.0.0",
+      "platform": null,
+      "version": "1.0.0"
+    },
+    {
+      "features": [],
+      "id": 8,
+      "kind": "package",
+      "name": "devdep",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#devdep@1.0.0",
+      "platform": "[HOST_TARGET]",
+      "version": "1.0.0"
+    },
+    {
+      "command_line": false,
+      "id": 9,
+      "kind": "feature",
+      "name": "default",
+      "package": 11
+    },
+    {
+      "command_line": false,
+      "id": 10,
+      "kind": "feature",
+      "name": "default",
+      "package": 12
+    },
+    {
+      "features": [],
+      "id": 11,
+      "kind": "package",
+      "name": "b",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#b@1.0.0",
+      "platform": "[HOST_TARGET]",
+      "version": "1.0.0"
+    },
+    {
+      "features": [],
+      "id": 12,
+      "kind": "package",
+      "name": "b",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#b@1.0.0",
+      "platform": null,
+      "version": "1.0.0"
+    },
+    {
+      "command_line": false,
+      "id": 13,
+      "kind": "feature",
+      "name": "default",
+      "package": 14
+    },
+    {
+      "features": [],
+      "id": 14,
+      "kind": "package",
+      "name": "c",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#c@1.0.0",
+      "platform": null,
+      "version": "1.0.0"
+    }
+  ],
+  "roots": [
+    0
+  ],
+  "targets": [
+    "[HOST_TARGET]"
+  ],
+  "version": 1
+}
+"#]]
+            .is_json(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn output_format_dot() {
+    let p = make_simple_proj();
+
+    p.cargo("tree --output-format dot -Zunstable-options --depth 2")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+digraph {
+    0 [label="foo v0.1.0 ([ROOT]/foo)"];
+    1 [label="a v1.0.0"];
+    2 [label="c v1.0.0"];
+    3 [label="bdep v1.0.0"];
+    4 [label="devdep v1.0.0"];
+    5 [label="b v1.0.0"];
+    0 -> 1;
+    0 -> 2;
+    0 -> 3 [label="build", color=blue];
+    0 -> 4 [label="dev", color=cyan4];
+    1 -> 5;
+    3 -> 5;
+    4 -> 5;
+}
+
+"#]])
+        .run();
+
+    p.cargo("tree --output-format dot -Zunstable-options -i b")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+digraph {
+    rankdir=BT;
+    0 [label="b v1.0.0"];
+    1 [label="a v1.0.0"];
+    2 [label="bdep v1.0.0"];
+    3 [label="devdep v1.0.0"];
+    4 [label="foo v0.1.0 ([ROOT]/foo)"];
+    0 -> 1;
+    0 -> 2;
+    0 -> 3;
+    1 -> 4;
+    2 -> 4 [label="build", color=blue];
+    3 -> 4 [label="dev", color=cyan4];
+}
+
+"#]])
+        .run();
+
+    p.cargo("tree --output-format json -Zunstable-options -i a")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(
+            str![[r#"
+{
+  "edges": [
+    {
+      "from": 0,
+      "kind": "normal",
+      "public": false,
+      "to": 1
+    }
+  ],
+  "inverted": true,
+  "nodes": [
+    {
+      "features": [],
+      "id": 0,
+      "kind": "package",
+      "name": "a",
+      "package_id": "registry+https://github.com/rust-lang/crates.io-index#a@1.0.0",
+      "platform": null,
+      "version": "1.0.0"
+    },
+    {
+      "features": [],
+      "id": 1,
+      "kind": "package",
+      "name": "foo",
+      "package_id": "path+[ROOTURL]/foo#0.1.0",
+      "platform": null,
+      "version": "0.1.0"
+    }
+  ],
+  "roots": [
+    0
+  ],
+  "targets": [
+    "[HOST_TARGET]"
+  ],
+  "version": 1
+}
+"#]]
+            .is_json(),
+        )
+        .run();
+
+    p.cargo("tree --output-format dot -Zunstable-options -e features -p c")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+digraph {
+    0 [label="c v1.0.0"];
+}
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn output_format_requires_unstable_options() {
+    let p = make_simple_proj();
+
+    p.cargo("tree --output-format json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `--output-format` requires `-Zunstable-options`
+
+"#]])
+        .run();
+
+    p.cargo("tree --output-format dot -Zunstable-options --duplicates")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--output-format` flag does not support `--duplicates`
+
+"#]])
+        .run();
+
+    p.cargo("tree --output-format json -Zunstable-options --prefix depth")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--output-format` flag does not support `--prefix`
+
+"#]])
+        .run();
+
+    p.cargo("tree --output-format dot -Zunstable-options --no-dedupe")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--output-format` flag does not support `--no-dedupe`
+
+"#]])
+        .run();
+
+    p.cargo("tree --output-format text --prefix none --no-dedupe")
+        .with_stdout_data(str![[r#"
+foo v0.1.0 ([ROOT]/foo)
+a v1.0.0
+b v1.0.0
+c v1.0.0
+c v1.0.0
+bdep v1.0.0
+b v1.0.0
+c v1.0.0
+devdep v1.0.0
+b v1.0.0
+c v1.0.0
+
+"#]])
+        .run();
+}
@@ -1,6 +1,7 @@
 //! Tests for running multiple `cargo` processes at the same time.
 
 use std::fs;
+use std::io::{BufRead, BufReader, Read};
 use std::net::TcpListener;
 use std::process::Stdio;
 use std::sync::mpsc::channel;
@@ -508,3 +509,236 @@ fn no_deadlock_with_git_dependencies() {
         execs().run_output(&result);
     }
 }
+
+#[cfg(unix)]
+#[cargo_test]
+fn global_jobserver() {
+    let p = project()
+        .no_manifest()
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                edition = "2015"
+
+                [dependencies]
+                dep1 = { path = "../dep1" }
+                dep2 = { path = "../dep2" }
+            "#,
+        )
+        .file("a/src/main.rs", "fn main() {}")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.0.0"))
+        .file("b/src/main.rs", "fn main() {}")
+        .file("dep1/Cargo.toml", &basic_manifest("dep1", "0.0.0"))
+        .file("dep1/src/lib.rs", "")
+        .file("dep2/Cargo.toml", &basic_manifest("dep2", "0.0.0"))
+        .file("dep2/src/lib.rs", "")
+        .build();
+
+    let mut a = p
+        .cargo("build -Zglobal-jobserver")
+        .masquerade_as_nightly_cargo(&["global-jobserver"])
+        .cwd("a")
+        .build_command();
+    let mut b = p
+        .cargo("build -Zglobal-jobserver")
+        .masquerade_as_nightly_cargo(&["global-jobserver"])
+        .cwd("b")
+        .build_command();
+
+    a.stdout(Stdio::piped()).stderr(Stdio::piped());
+    b.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    let a = a.spawn().unwrap();
+    let b = b.spawn().unwrap();
+    let a = thread::spawn(move || a.wait_with_output().unwrap());
+    let b = b.wait_with_output().unwrap();
+    let a = a.join().unwrap();
+
+    execs().run_output(&a);
+    execs().run_output(&b);
+
+    use std::os::unix::fs::FileTypeExt;
+    let fifo = paths::cargo_home().join("jobserver/fifo");
+    assert!(fs::metadata(&fifo).unwrap().file_type().is_fifo());
+
+    // The pool is filled again by the next build on its own.
+    p.cargo("build -Zglobal-jobserver")
+        .masquerade_as_nightly_cargo(&["global-jobserver"])
+        .cwd("a")
+        .with_stderr_data(str![[r#"
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+/// A build script that blocks until the test closes its connection, so that
+/// the build holds its locks meanwhile.
+const BLOCKING_BUILD_SCRIPT: &str = r#"
+    use std::io::Read;
+    use std::net::TcpStream;
+
+    fn main() {
+        if let Ok(addr) = std::env::var("ADDR") {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let _ = stream.read(&mut [0]);
+        }
+    }
+"#;
+
+#[cargo_test]
+fn blocking_message_shows_holder_pid() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", BLOCKING_BUILD_SCRIPT)
+        .build();
+
+    let l = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut a = p.cargo("build").build_command();
+    let mut b = p.cargo("build").build_command();
+    a.stdout(Stdio::piped()).stderr(Stdio::piped());
+    b.stdout(Stdio::piped()).stderr(Stdio::piped());
+    a.env("ADDR", l.local_addr().unwrap().to_string());
+
+    // Once the build script of `a` runs, `a` holds the lock.
+    let a = a.spawn().unwrap();
+    let a_pid = a.id();
+    let (conn, _) = l.accept().unwrap();
+
+    let mut b = b.spawn().unwrap();
+    let mut b_stderr = BufReader::new(b.stderr.take().unwrap());
+    let mut line = String::new();
+    loop {
+        line.clear();
+        assert_ne!(
+            b_stderr.read_line(&mut line).unwrap(),
+            0,
+            "`b` didn't block"
+        );
+        if line.contains("Blocking") {
+            break;
+        }
+    }
+    assert_eq!(
+        line.trim(),
+        format!("Blocking waiting for file lock on build directory (held by PID {a_pid})")
+    );
+
+    drop(conn);
+    execs().run_output(&a.wait_with_output().unwrap());
+    assert!(b.wait().unwrap().success());
+}
+
+#[cargo_test]
+fn fine_grain_locking_check_while_building() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dev-dependencies]
+                blocker = { path = "blocker" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("blocker/Cargo.toml", &basic_manifest("blocker", "0.0.0"))
+        .file("blocker/src/lib.rs", "")
+        .file("blocker/build.rs", BLOCKING_BUILD_SCRIPT)
+        .build();
+
+    // `blocker` is a dev-dependency, so only `a` builds it, and `b` doesn't
+    // share any unit with `a`.
+    let l = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut a = p
+        .cargo("test --no-run -Zfine-grain-locking")
+        .masquerade_as_nightly_cargo(&["fine-grain-locking"])
+        .build_command();
+    a.stdout(Stdio::piped()).stderr(Stdio::piped());
+    a.env("ADDR", l.local_addr().unwrap().to_string());
+    let a = a.spawn().unwrap();
+    let (conn, _) = l.accept().unwrap();
+
+    p.cargo("check -Zfine-grain-locking")
+        .masquerade_as_nightly_cargo(&["fine-grain-locking"])
+        .with_stderr_data(str![[r#"
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    drop(conn);
+    execs().run_output(&a.wait_with_output().unwrap());
+
+    // Both builds left their units fresh.
+    p.cargo("check -Zfine-grain-locking")
+        .masquerade_as_nightly_cargo(&["fine-grain-locking"])
+        .with_stderr_data(str![[r#"
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+    p.cargo("test --no-run -Zfine-grain-locking")
+        .masquerade_as_nightly_cargo(&["fine-grain-locking"])
+        .with_stderr_data(str![[r#"
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[EXECUTABLE] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[EXECUTABLE] unittests src/main.rs (target/debug/deps/foo-[HASH][EXE])
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn fine_grain_locking_shared_unit() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file("build.rs", BLOCKING_BUILD_SCRIPT)
+        .build();
+
+    // The build script is run by both `a` and `b`, so `b` waits for `a`, then
+    // finds everything fresh.
+    let l = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut a = p
+        .cargo("build -Zfine-grain-locking")
+        .masquerade_as_nightly_cargo(&["fine-grain-locking"])
+        .build_command();
+    a.stdout(Stdio::piped()).stderr(Stdio::piped());
+    a.env("ADDR", l.local_addr().unwrap().to_string());
+    let a = a.spawn().unwrap();
+    let a_pid = a.id();
+    let (conn, _) = l.accept().unwrap();
+
+    let mut b = p
+        .cargo("build -Zfine-grain-locking")
+        .masquerade_as_nightly_cargo(&["fine-grain-locking"])
+        .build_command();
+    b.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut b = b.spawn().unwrap();
+    let mut b_stderr = BufReader::new(b.stderr.take().unwrap());
+    let mut line = String::new();
+    b_stderr.read_line(&mut line).unwrap();
+    assert_eq!(
+        line.trim(),
+        format!(
+            r#"Blocking waiting for file lock on bin "foo" of `foo v0.0.1` (held by PID {a_pid})"#
+        )
+    );
+
+    drop(conn);
+    execs().run_output(&a.wait_with_output().unwrap());
+    let mut rest = String::new();
+    b_stderr.read_to_string(&mut rest).unwrap();
+    assert!(b.wait().unwrap().success());
+    assert!(
+        !rest.contains("Compiling"),
+        "`b` rebuilt a fresh unit:\n{rest}"
+    );
+}
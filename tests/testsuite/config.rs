@@ -1665,14 +1665,24 @@ fn all_profile_options() {
         package: None,
         build_override: None,
         rustflags: None,
+        linker: Some(String::from("cc")),
+        link_args: Some(vec![String::from("-fno-omit-frame-pointer")]),
+        env: Some(BTreeMap::from([(
+            String::from("CFLAGS"),
+            String::from("-fno-omit-frame-pointer"),
+        )])),
         trim_paths: None,
+        target: None,
     };
     let mut overrides = BTreeMap::new();
     let key = cargo_toml::ProfilePackageSpec::Spec(PackageIdSpec::parse("foo").unwrap());
     overrides.insert(key, base_settings.clone());
+    let mut target_overrides = BTreeMap::new();
+    target_overrides.insert("cfg(unix)".to_string(), base_settings.clone());
     let profile = cargo_toml::TomlProfile {
         build_override: Some(Box::new(base_settings.clone())),
         package: Some(overrides),
+        target: Some(target_overrides),
         ..base_settings
     };
     let profile_toml = toml::to_string(&profile).unwrap();
@@ -2867,3 +2867,95 @@ dep_df_false = { workspace = true, default-features = false }
 "#]],
     );
 }
+
+#[cargo_test]
+fn interactive_prints_diff_without_a_terminal() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            "\
+pub mod bar;
+
+pub fn foo() -> u32 {
+    let mut x = 3;
+    x
+}
+",
+        )
+        .file(
+            "src/bar.rs",
+            "\
+pub fn bar() -> u32 {
+    let mut y = 3;
+    y
+}
+",
+        )
+        .build();
+
+    p.cargo("fix --allow-no-vcs --interactive -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .env("__CARGO_FIX_YOLO", "1")
+        .with_stdout_data(str![[r#"
+--- a/src/bar.rs
++++ b/src/bar.rs
+@@ -1,4 +1,4 @@
+ pub fn bar() -> u32 {
+-    let mut y = 3;
++    let y = 3;
+     y
+ }
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,6 +1,6 @@
+ pub mod bar;
+ 
+ pub fn foo() -> u32 {
+-    let mut x = 3;
++    let x = 3;
+     x
+ }
+
+"#]])
+        .with_stderr_data(str![[r#"
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+...
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[NOTE] no files were modified, run `cargo fix` to apply these fixes
+
+"#]])
+        .run();
+    assert!(p.read_file("src/lib.rs").contains("let mut x = 3;"));
+    assert!(p.read_file("src/bar.rs").contains("let mut y = 3;"));
+}
+
+#[cargo_test]
+fn interactive_conflicts_with_edition() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("fix --allow-no-vcs --interactive --edition")
+        .with_status(1)
+        .with_stderr_data(str![[r#"
+[ERROR] the argument '--interactive' cannot be used with '--edition'
+
+Usage: cargo fix --allow-no-vcs --interactive
+
+For more information, try '--help'.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn interactive_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("fix --allow-no-vcs --interactive")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--interactive` flag is unstable, pass `-Z unstable-options` to enable it
+
+"#]])
+        .run();
+}
@@ -332,6 +332,14 @@ The full error message was:
 
 > cannot replace slice of data that was already replaced
 
+The skipped suggestion was:
+
+  * 1:14-1:15: rustc fix shim comment 4
+
+It overlapped with:
+
+  * 1:14-1:15: rustc fix shim comment 5
+
 This likely indicates a bug in either rustc or cargo itself,
 and we would appreciate a bug report! You're likely to see
 a number of compiler warnings after this message which cargo
@@ -2885,3 +2885,199 @@ fn dry_run_remove_orphan() {
     // Ensure server is still installed after the dry run
     assert_has_installed_exe(paths::cargo_home(), "server");
 }
+
+#[cargo_test]
+fn from_manifest() {
+    pkg("foo", "0.0.1");
+    pkg("foo", "0.0.2");
+    Package::new("bar", "0.1.0")
+        .feature("extra", &[])
+        .file(
+            "src/main.rs",
+            r#"fn main() { println!("{}", cfg!(feature = "extra")) }"#,
+        )
+        .publish();
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "tools.toml",
+            r#"
+            [tools]
+            foo = "0.0.1"
+            bar = { version = "0.1.0", features = ["extra"] }
+            "#,
+        )
+        .build();
+
+    p.cargo("install --from-manifest -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v0.1.0 (registry `dummy-registry`)
+[WARNING] no Cargo.lock file published in bar v0.1.0
+[INSTALLING] bar v0.1.0
+[COMPILING] bar v0.1.0
+[FINISHED] `release` profile [optimized] target(s) in [ELAPSED]s
+[INSTALLING] [ROOT]/home/.cargo/tools/foo-[HASH]/bin/bar[EXE]
+[INSTALLED] package `bar v0.1.0` (executable `bar[EXE]`)
+[DOWNLOADING] crates ...
+[DOWNLOADED] foo v0.0.1 (registry `dummy-registry`)
+[WARNING] no Cargo.lock file published in foo v0.0.1
+[INSTALLING] foo v0.0.1
+[COMPILING] foo v0.0.1
+[FINISHED] `release` profile [optimized] target(s) in [ELAPSED]s
+[INSTALLING] [ROOT]/home/.cargo/tools/foo-[HASH]/bin/foo[EXE]
+[INSTALLED] package `foo v0.0.1` (executable `foo[EXE]`)
+[SUMMARY] Successfully installed bar, foo!
+
+"#]])
+        .run();
+    let tools = paths::cargo_home()
+        .join("tools")
+        .read_dir()
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    assert_has_installed_exe(&tools, "foo");
+    assert_has_not_installed_exe(paths::cargo_home(), "foo");
+    let output = ProcessBuilder::new(tools.join("bin").join(exe("bar")))
+        .exec_with_output()
+        .unwrap();
+    assert_eq!(output.stdout, b"true\n");
+
+    // Tools already installed at the requested version are skipped.
+    p.cargo("install --from-manifest -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[IGNORED] package `bar v0.1.0` is already installed, use --force to override
+[IGNORED] package `foo v0.0.1` is already installed, use --force to override
+[SUMMARY] Successfully installed bar, foo!
+
+"#]])
+        .run();
+
+    p.change_file("tools.toml", "[tools]\nfoo = \"0.0.2\"\n");
+    p.cargo("install --from-manifest -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[DOWNLOADING] crates ...
+[DOWNLOADED] foo v0.0.2 (registry `dummy-registry`)
+[UPDATING] `dummy-registry` index
+[WARNING] no Cargo.lock file published in foo v0.0.2
+[INSTALLING] foo v0.0.2
+[COMPILING] foo v0.0.2
+[FINISHED] `release` profile [optimized] target(s) in [ELAPSED]s
+[REPLACING] [ROOT]/home/.cargo/tools/foo-[HASH]/bin/foo[EXE]
+[REPLACED] package `foo v0.0.1` with `foo v0.0.2` (executable `foo[EXE]`)
+[SUMMARY] Successfully installed foo!
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn from_manifest_git() {
+    let git_project = git::new("tool", |project| {
+        project
+            .file("Cargo.toml", &basic_manifest("tool", "0.1.0"))
+            .file("src/main.rs", "fn main() {}")
+    });
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "tools.toml",
+            &format!("[tools]\ntool = {{ git = '{}' }}\n", git_project.url()),
+        )
+        .build();
+
+    p.cargo("install --from-manifest --root tools -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] git repository `[ROOTURL]/tool`
+[WARNING] no Cargo.lock file published in tool v0.1.0 ([ROOTURL]/tool#[..])
+[INSTALLING] tool v0.1.0 ([ROOTURL]/tool#[..])
+[COMPILING] tool v0.1.0 ([ROOT]/home/.cargo/git/checkouts/tool-[HASH]/[..])
+[FINISHED] `release` profile [optimized] target(s) in [ELAPSED]s
+[INSTALLING] tools/bin/tool[EXE]
+[INSTALLED] package `tool v0.1.0 ([ROOTURL]/tool#[..])` (executable `tool[EXE]`)
+[SUMMARY] Successfully installed tool!
+
+"#]])
+        .run();
+    assert_has_installed_exe(p.root().join("tools"), "tool");
+}
+
+#[cargo_test]
+fn from_manifest_invalid() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("tools.toml", "[tools]\nfoo = {}\n")
+        .build();
+
+    p.cargo("install --from-manifest -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] tool `foo` in `[ROOT]/foo/tools.toml` must specify a `version`
+
+"#]])
+        .run();
+
+    p.change_file(
+        "tools.toml",
+        "[tools]\nfoo = { git = 'https://example.com', rev = 'a', tag = 'b' }\n",
+    );
+    p.cargo("install --from-manifest -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] invalid tool `foo` in `[ROOT]/foo/tools.toml`
+
+Caused by:
+  only one of `branch`, `tag` or `rev` can be used
+
+"#]])
+        .run();
+
+    p.change_file(
+        "tools.toml",
+        "[tools]\nfoo = { version = '1.0', bins = ['foo'] }\n",
+    );
+    p.cargo("install --from-manifest -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse `[ROOT]/foo/tools.toml`
+
+Caused by:
+  TOML parse error at line 2, column 7
+    |
+  2 | foo = { version = '1.0', bins = ['foo'] }
+    |       ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+  unknown field `bins`, expected one of `version`, `registry`, `git`, `branch`, `tag`, `rev`, `features`, `all-features`, `default-features`
+
+
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn from_manifest_requires_unstable_options() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("tools.toml", "[tools]\nfoo = \"0.0.1\"\n")
+        .build();
+
+    p.cargo("install --from-manifest")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--from-manifest` flag is unstable, pass `-Z unstable-options` to enable it
+
+"#]])
+        .run();
+}
@@ -984,3 +984,220 @@ fn partially_already_installed_does_one_update() {
 "#]])
         .run();
 }
+
+#[cargo_test]
+fn list_outdated() {
+    pkg("foo", "1.0.0");
+    pkg("bar", "1.0.0");
+    cargo_process("install foo bar").run();
+    pkg("foo", "1.0.1");
+    pkg("foo", "2.0.0-beta.1");
+    cargo_process("install --list --outdated -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+foo v1.0.0 -> v1.0.1:
+    foo[EXE]
+
+"#]])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+
+"#]])
+        .run();
+    // Packages installed with `--version` only look for matching versions.
+    cargo_process("install foo --version ~1.0.0").run();
+    pkg("bar", "1.1.0");
+    pkg("foo", "1.1.0");
+    cargo_process("install --list --outdated -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+bar v1.0.0 -> v1.1.0:
+    bar[EXE]
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn update_all_keeps_settings() {
+    let publish = |vers: &str| {
+        Package::new("foo", vers)
+            .file(
+                "src/main.rs",
+                r#"
+                fn main() {
+                    if cfg!(feature = "f1") {
+                        println!("f1");
+                    }
+                    if cfg!(feature = "f2") {
+                        println!("f2");
+                    }
+                    println!("{} {}", env!("CARGO_PKG_VERSION"), cfg!(debug_assertions));
+                }
+                "#,
+            )
+            .feature("f1", &[])
+            .feature("f2", &[])
+            .feature("default", &["f1"])
+            .publish();
+    };
+    publish("1.0.0");
+    pkg("bar", "1.0.0");
+    cargo_process("install foo --no-default-features --features f2 --debug").run();
+    cargo_process("install bar").run();
+    publish("1.0.1");
+    cargo_process("install --update-all -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[DOWNLOADING] crates ...
+[DOWNLOADED] foo v1.0.1 (registry `dummy-registry`)
+[INSTALLING] foo v1.0.1
+[COMPILING] foo v1.0.1
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[REPLACING] [ROOT]/home/.cargo/bin/foo[EXE]
+[REPLACED] package `foo v1.0.0` with `foo v1.0.1` (executable `foo[EXE]`)
+[SUMMARY] Successfully updated foo!
+
+"#]])
+        .run();
+    installed_process("foo")
+        .with_stdout_data(str![[r#"
+f2
+1.0.1 true
+
+"#]])
+        .run();
+    validate_trackers("foo", "1.0.1", &["foo"]);
+    validate_trackers("bar", "1.0.0", &["bar"]);
+    cargo_process("install --update-all -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[NOTE] all installed packages are up to date
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn update_all_git() {
+    let git_project = git::new("foo", |project| project.file("src/main.rs", "fn main() {}"));
+    cargo_process("install --git")
+        .arg(git_project.url().to_string())
+        .run();
+    cargo_process("install --list --outdated -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data("")
+        .run();
+    let repo = git2::Repository::open(git_project.root()).unwrap();
+    git_project.change_file("src/main.rs", r#"fn main() {println!("onomatopoeia");}"#);
+    git::add(&repo);
+    git::commit(&repo);
+    cargo_process("install --list --outdated -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+foo v0.0.1 ([ROOTURL]/foo#[..]) -> v0.0.1 (#[..]):
+    foo[EXE]
+
+"#]])
+        .run();
+    cargo_process("install --update-all -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] git repository `[ROOTURL]/foo`
+[UPDATING] git repository `[ROOTURL]/foo`
+[INSTALLING] foo v0.0.1 ([ROOTURL]/foo#[..])
+[COMPILING] foo v0.0.1 ([ROOT]/home/.cargo/git/checkouts/foo-[HASH]/[..])
+[FINISHED] `release` profile [optimized] target(s) in [ELAPSED]s
+[REPLACING] [ROOT]/home/.cargo/bin/foo[EXE]
+[REPLACED] package `foo v0.0.1 ([ROOTURL]/foo#[..])` with `foo v0.0.1 ([ROOTURL]/foo#[..])` (executable `foo[EXE]`)
+[SUMMARY] Successfully updated foo!
+
+"#]])
+        .run();
+    installed_process("foo")
+        .with_stdout_data(str![[r#"
+onomatopoeia
+
+"#]])
+        .run();
+    cargo_process("install --list --outdated -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data("")
+        .run();
+}
+
+#[cargo_test]
+fn outdated_and_update_all_require_unstable_options() {
+    cargo_process("install --list --outdated")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--outdated` flag is unstable, pass `-Z unstable-options` to enable it
+
+"#]])
+        .run();
+    cargo_process("install --update-all")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--update-all` flag is unstable, pass `-Z unstable-options` to enable it
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn update_all_nothing_to_install() {
+    pkg("foo", "1.0.0");
+    pkg("bar", "1.0.0");
+    cargo_process("install foo bar").run();
+    // The binary of the new version needs a feature that isn't enabled.
+    Package::new("foo", "1.0.1")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "1.0.1"
+                edition = "2015"
+
+                [features]
+                f = []
+
+                [[bin]]
+                name = "foo"
+                required-features = ["f"]
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .feature("f", &[])
+        .publish();
+    pkg("bar", "1.0.1");
+    cargo_process("install --update-all -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+...
+[SUMMARY] Successfully updated bar! Left foo unchanged.
+
+"#]])
+        .run();
+    validate_trackers("foo", "1.0.0", &["foo"]);
+    validate_trackers("bar", "1.0.1", &["bar"]);
+}
+
+#[cargo_test]
+fn update_all_conflicts_with_crate() {
+    cargo_process("install foo --update-all")
+        .with_status(1)
+        .with_stderr_data(str![[r#"
+[ERROR] the argument '[CRATE[@<VER>]]...' cannot be used with '--update-all'
+
+Usage: cargo install [CRATE[@<VER>]]...
+
+For more information, try '--help'.
+
+"#]])
+        .run();
+}
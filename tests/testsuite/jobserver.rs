@@ -5,11 +5,11 @@ use std::net::TcpListener;
 use std::process::Command;
 use std::thread;
 
-use cargo_test_support::basic_bin_manifest;
 use cargo_test_support::cargo_exe;
 use cargo_test_support::install::assert_has_installed_exe;
 use cargo_test_support::paths;
 use cargo_test_support::prelude::*;
+use cargo_test_support::{basic_bin_manifest, basic_manifest};
 use cargo_test_support::{project, rustc_host, str};
 use cargo_util::is_ci;
 
@@ -410,3 +410,116 @@ all:
 "#]])
         .run();
 }
+
+#[cargo_test]
+fn memory_limit_runs_one_job_at_a_time() {
+    // Each build script fails if the other one is running at the same time.
+    let build_rs = r#"
+        use std::fs::{self, File};
+        use std::path::Path;
+        use std::time::Duration;
+
+        fn main() {
+            let running = Path::new(&std::env::var("MARKER_DIR").unwrap()).join("running");
+            File::create_new(&running).expect("another build script is running");
+            std::thread::sleep(Duration::from_millis(500));
+            fs::remove_file(&running).unwrap();
+        }
+    "#;
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                a = { path = "a" }
+                b = { path = "b" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("a/Cargo.toml", &basic_manifest("a", "0.0.1"))
+        .file("a/src/lib.rs", "")
+        .file("a/build.rs", build_rs)
+        .file("b/Cargo.toml", &basic_manifest("b", "0.0.1"))
+        .file("b/src/lib.rs", "")
+        .file("b/build.rs", build_rs)
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                memory-limit = "512MiB"
+            "#,
+        )
+        .build();
+
+    p.cargo("build -j2 -Zmemory-limit")
+        .masquerade_as_nightly_cargo(&["memory-limit"])
+        .env("MARKER_DIR", p.root())
+        .with_stderr_data(
+            str![[r#"
+[LOCKING] 2 packages to latest compatible versions
+[COMPILING] a v0.0.1 ([ROOT]/foo/a)
+[COMPILING] b v0.0.1 ([ROOT]/foo/b)
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn memory_limit_invalid() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                memory-limit = "lots"
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Zmemory-limit")
+        .masquerade_as_nightly_cargo(&["memory-limit"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse `build.memory-limit`
+
+Caused by:
+  invalid size `lots`, expected a number with an optional B, kB, MB, GB, kiB, MiB, or GiB suffix
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn memory_limit_requires_unstable_flag() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [build]
+                memory-limit = "lots"
+                sample-free-memory = true
+            "#,
+        )
+        .build();
+
+    p.cargo("build")
+        .with_stderr_data(str![[r#"
+[WARNING] ignoring 'build.memory-limit' config, pass `-Zmemory-limit` to enable it
+[WARNING] ignoring 'build.sample-free-memory' config, pass `-Zmemory-limit` to enable it
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
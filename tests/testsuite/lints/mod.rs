@@ -6,6 +6,7 @@ use cargo_test_support::str;
 mod error;
 mod inherited;
 mod unknown_lints;
+mod unused_dependencies;
 mod warning;
 
 #[cargo_test]
@@ -0,0 +1,205 @@
+use cargo_test_support::prelude::*;
+use cargo_test_support::project;
+use cargo_test_support::registry::Package;
+use cargo_test_support::str;
+
+#[cargo_test]
+fn default() {
+    Package::new("bar", "1.0.0").publish();
+    Package::new("baz", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.0.1"
+edition = "2021"
+authors = []
+
+[dependencies]
+bar = "1.0"
+baz = "1.0"
+"#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 2 packages to latest compatible versions
+[DOWNLOADING] crates ...
+[DOWNLOADED] baz v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] bar v1.0.0 (registry `dummy-registry`)
+[CHECKING] bar v1.0.0
+[CHECKING] baz v1.0.0
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test(nightly, reason = "-Zbinary-dep-depinfo is unstable")]
+fn warn() {
+    Package::new("bar", "1.0.0")
+        .file("src/lib.rs", "pub fn f() {}")
+        .publish();
+    Package::new("baz", "1.0.0").publish();
+    Package::new("qux", "1.0.0").publish();
+    Package::new("build-bar", "1.0.0")
+        .file("src/lib.rs", "pub fn f() {}")
+        .publish();
+    Package::new("build-baz", "1.0.0").publish();
+    Package::new("dev-bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.0.1"
+edition = "2021"
+authors = []
+
+[dependencies]
+bar = "1.0"
+baz = "1.0"
+renamed = { package = "qux", version = "1.0" }
+
+[build-dependencies]
+build-bar = "1.0"
+build-baz = "1.0"
+
+[dev-dependencies]
+dev-bar = "1.0"
+
+[lints.cargo]
+unused_dependencies = "warn"
+"#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() { bar::f(); }")
+        .file("build.rs", "fn main() { build_bar::f(); }")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 6 packages to latest compatible versions
+[DOWNLOADING] crates ...
+[DOWNLOADED] qux v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] build-baz v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] build-bar v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] baz v1.0.0 (registry `dummy-registry`)
+[DOWNLOADED] bar v1.0.0 (registry `dummy-registry`)
+[COMPILING] build-baz v1.0.0
+[COMPILING] build-bar v1.0.0
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[CHECKING] bar v1.0.0
+[CHECKING] qux v1.0.0
+[CHECKING] baz v1.0.0
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[WARNING] unused dependency: `baz`
+  --> Cargo.toml:10:1
+   |
+10 | baz = "1.0"
+   | -----------
+   |
+   = [NOTE] `cargo::unused_dependencies` is set to `warn` in `[lints]`
+[WARNING] unused dependency: `renamed`
+  --> Cargo.toml:11:1
+   |
+11 | renamed = { package = "qux", version = "1.0" }
+   | ----------------------------------------------
+   |
+[WARNING] unused dependency: `build-baz`
+  --> Cargo.toml:15:1
+   |
+15 | build-baz = "1.0"
+   | -----------------
+   |
+
+"#]])
+        .run();
+
+    // Fresh units are checked as well.
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_stderr_data(str![[r#"
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[WARNING] unused dependency: `baz`
+  --> Cargo.toml:10:1
+   |
+10 | baz = "1.0"
+   | -----------
+   |
+   = [NOTE] `cargo::unused_dependencies` is set to `warn` in `[lints]`
+[WARNING] unused dependency: `renamed`
+  --> Cargo.toml:11:1
+   |
+11 | renamed = { package = "qux", version = "1.0" }
+   | ----------------------------------------------
+   |
+[WARNING] unused dependency: `build-baz`
+  --> Cargo.toml:15:1
+   |
+15 | build-baz = "1.0"
+   | -----------------
+   |
+
+"#]])
+        .run();
+}
+
+#[cargo_test(nightly, reason = "-Zbinary-dep-depinfo is unstable")]
+fn deny() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+[package]
+name = "foo"
+version = "0.0.1"
+edition = "2021"
+authors = []
+
+[target.'cfg(all())'.dependencies]
+bar = "1.0"
+
+[lints.cargo]
+unused_dependencies = "deny"
+"#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zcargo-lints")
+        .masquerade_as_nightly_cargo(&["cargo-lints"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `dummy-registry`)
+[CHECKING] bar v1.0.0
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[ERROR] unused dependency: `bar`
+ --> Cargo.toml:9:1
+  |
+9 | bar = "1.0"
+  | ^^^^^^^^^^^
+  |
+  = [NOTE] `cargo::unused_dependencies` is set to `deny` in `[lints]`
+
+"#]])
+        .run();
+}
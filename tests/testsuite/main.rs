@@ -13,6 +13,7 @@ mod binary_name;
 mod build;
 mod build_dir;
 mod build_plan;
+mod build_report;
 mod build_script;
 mod build_script_env;
 mod build_script_extra_link_arg;
@@ -62,6 +63,7 @@ mod cargo_update;
 mod cargo_vendor;
 mod cargo_verify_project;
 mod cargo_version;
+mod cargo_why;
 mod cargo_yank;
 mod cfg;
 mod check;
@@ -171,6 +173,7 @@ mod rustup;
 mod sbom;
 mod script;
 mod search;
+mod shared_build_cache;
 mod shell_quoting;
 mod source_replacement;
 mod ssh;
@@ -186,6 +189,7 @@ mod version;
 mod warn_on_failure;
 mod warning_override;
 mod weak_dep_features;
+mod why;
 mod workspaces;
 mod yank;
 
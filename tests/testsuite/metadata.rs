@@ -4985,3 +4985,270 @@ fn metadata_ignores_build_target_configuration() -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+#[cargo_test]
+fn metadata_units_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("metadata --format-version 1 --units")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `--units` requires `-Zunstable-options`
+
+"#]])
+        .run();
+
+    p.cargo("metadata --format-version 1 --release")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `--release` and `--profile` require `--units`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn metadata_units() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+
+                [profile.release]
+                opt-level = 2
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [env]
+                FOO = "foo"
+
+                [build]
+                rustflags = ["--cfg", "foo"]
+            "#,
+        )
+        .build();
+
+    p.cargo("metadata --format-version 1 --units --release -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(
+            str![[r#"
+{
+  "metadata": null,
+  "packages": "{...}",
+  "resolve": "{...}",
+  "target_directory": "[ROOT]/foo/target",
+  "units": {
+    "roots": [
+      1
+    ],
+    "units": [
+      {
+        "command": "{...}",
+        "dependencies": [],
+        "env": {
+          "FOO": "foo"
+        },
+        "features": [],
+        "mode": "build",
+        "out_dir": null,
+        "outputs": [
+          {
+            "flavor": "linkable",
+            "hardlink": null,
+            "path": "[ROOT]/foo/target/release/deps/libbar-[HASH].rlib"
+          },
+          {
+            "flavor": "rmeta",
+            "hardlink": null,
+            "path": "[ROOT]/foo/target/release/deps/libbar-[HASH].rmeta"
+          }
+        ],
+        "pkg_id": "registry+https://github.com/rust-lang/crates.io-index#bar@1.0.0",
+        "platform": null,
+        "profile": "{...}",
+        "rustflags": [
+          "--cfg",
+          "foo"
+        ],
+        "target": "{...}"
+      },
+      {
+        "command": {
+          "args": [
+            "--crate-name",
+            "foo",
+            "--edition=2015",
+            "src/lib.rs",
+            "--error-format=json",
+            "--json=diagnostic-rendered-ansi,artifacts,future-incompat",
+            "--crate-type",
+            "lib",
+            "--emit=dep-info,metadata,link",
+            "-C",
+            "opt-level=2",
+            "-C",
+            "embed-bitcode=no",
+            "--check-cfg",
+            "cfg(docsrs,test)",
+            "--check-cfg",
+            "cfg(feature, values())",
+            "-C",
+            "metadata=[..]",
+            "-C",
+            "extra-filename=[..]",
+            "--out-dir",
+            "[ROOT]/foo/target/release/deps",
+            "-C",
+            "strip=debuginfo",
+            "-L",
+            "dependency=[ROOT]/foo/target/release/deps",
+            "--extern",
+            "bar=[ROOT]/foo/target/release/deps/libbar-[HASH].rmeta",
+            "--cfg",
+            "foo"
+          ],
+          "program": "rustc"
+        },
+        "dependencies": [
+          {
+            "extern_crate_name": "bar",
+            "index": 0
+          },
+          {
+            "extern_crate_name": "build_script_build",
+            "index": 3
+          }
+        ],
+        "env": {
+          "FOO": "foo"
+        },
+        "features": [],
+        "mode": "build",
+        "out_dir": null,
+        "outputs": [
+          {
+            "flavor": "linkable",
+            "hardlink": "[ROOT]/foo/target/release/libfoo.rlib",
+            "path": "[ROOT]/foo/target/release/deps/libfoo-[HASH].rlib"
+          },
+          {
+            "flavor": "rmeta",
+            "hardlink": null,
+            "path": "[ROOT]/foo/target/release/deps/libfoo-[HASH].rmeta"
+          }
+        ],
+        "pkg_id": "path+[ROOTURL]/foo#0.1.0",
+        "platform": null,
+        "profile": {
+          "codegen_backend": null,
+          "codegen_units": null,
+          "debug_assertions": false,
+          "debuginfo": 0,
+          "incremental": false,
+          "lto": "false",
+          "name": "release",
+          "opt_level": "2",
+          "overflow_checks": false,
+          "panic": "unwind",
+          "rpath": false,
+          "split_debuginfo": null,
+          "strip": {
+            "resolved": {
+              "Named": "debuginfo"
+            }
+          }
+        },
+        "rustflags": [
+          "--cfg",
+          "foo"
+        ],
+        "target": {
+          "crate_types": [
+            "lib"
+          ],
+          "doc": true,
+          "doctest": true,
+          "edition": "2015",
+          "kind": [
+            "lib"
+          ],
+          "name": "foo",
+          "src_path": "[ROOT]/foo/src/lib.rs",
+          "test": true
+        }
+      },
+      {
+        "command": "{...}",
+        "dependencies": [],
+        "env": {
+          "FOO": "foo"
+        },
+        "features": [],
+        "mode": "build",
+        "out_dir": null,
+        "outputs": "{...}",
+        "pkg_id": "path+[ROOTURL]/foo#0.1.0",
+        "platform": null,
+        "profile": "{...}",
+        "rustflags": [
+          "--cfg",
+          "foo"
+        ],
+        "target": "{...}"
+      },
+      {
+        "command": null,
+        "dependencies": [
+          {
+            "extern_crate_name": "build_script_build",
+            "index": 2
+          }
+        ],
+        "env": {
+          "FOO": "foo"
+        },
+        "features": [],
+        "mode": "run-custom-build",
+        "out_dir": "[ROOT]/foo/target/release/build/foo-[HASH]/out",
+        "outputs": [],
+        "pkg_id": "path+[ROOTURL]/foo#0.1.0",
+        "platform": null,
+        "profile": "{...}",
+        "rustflags": [
+          "--cfg",
+          "foo"
+        ],
+        "target": "{...}"
+      }
+    ],
+    "version": 1
+  },
+  "version": 1,
+  "workspace_default_members": [
+    "path+[ROOTURL]/foo#0.1.0"
+  ],
+  "workspace_members": [
+    "path+[ROOTURL]/foo#0.1.0"
+  ],
+  "workspace_root": "[ROOT]/foo"
+}
+"#]]
+            .is_json(),
+        )
+        .run();
+
+    // Nothing is built, and the build directory isn't created.
+    assert!(!p.root().join("target").exists());
+}
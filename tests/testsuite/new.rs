@@ -4,6 +4,7 @@ use std::env;
 use std::fs::{self, File};
 
 use cargo_test_support::cargo_process;
+use cargo_test_support::compare::assert_e2e;
 use cargo_test_support::paths;
 use cargo_test_support::prelude::*;
 use cargo_test_support::str;
@@ -569,3 +570,236 @@ It is recommended to use a different name to avoid problems.
 "#]])
         .run();
 }
+
+fn write_template(dir: &std::path::Path) {
+    fs::create_dir_all(dir.join("src/bin")).unwrap();
+    fs::write(
+        dir.join("Cargo.toml"),
+        r#"[package]
+name = "{{name}}"
+version = "0.1.0"
+edition = "{{edition}}"
+
+[lints.rust]
+unsafe_code = "forbid"
+"#,
+    )
+    .unwrap();
+    fs::write(dir.join("src/lib.rs"), "// Part of {{name}}.\n").unwrap();
+    fs::write(dir.join("src/bin/{{name}}-cli.rs"), "fn main() {}\n").unwrap();
+    fs::write(dir.join(".gitignore"), "/dist\n").unwrap();
+}
+
+#[cargo_test]
+fn template_path() {
+    write_template(&paths::root().join("template"));
+
+    cargo_process("new foo --template template --edition 2018 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[CREATING] `foo` package from template `template`
+[NOTE] see more `Cargo.toml` keys and their definitions at https://doc.rust-lang.org/cargo/reference/manifest.html
+
+"#]])
+        .run();
+
+    let foo = paths::root().join("foo");
+    assert_e2e().eq(
+        fs::read_to_string(foo.join("Cargo.toml")).unwrap(),
+        str![[r#"
+[package]
+name = "foo"
+version = "0.1.0"
+edition = "2018"
+
+[lints.rust]
+unsafe_code = "forbid"
+
+"#]],
+    );
+    assert_e2e().eq(
+        fs::read_to_string(foo.join("src/lib.rs")).unwrap(),
+        str![[r#"
+// Part of foo.
+
+"#]],
+    );
+    assert!(foo.join("src/bin/foo-cli.rs").is_file());
+    assert!(foo.join(".git").is_dir());
+    assert_e2e().eq(
+        fs::read_to_string(foo.join(".gitignore")).unwrap(),
+        str![[r#"
+/dist
+
+
+# Added by cargo
+
+/target
+
+"#]],
+    );
+
+    cargo_process("build").cwd(&foo).run();
+}
+
+#[cargo_test]
+fn template_git() {
+    let template = cargo_test_support::git::repo(&paths::root().join("template"))
+        .file(
+            "Cargo.toml",
+            &cargo_test_support::basic_manifest("{{name}}", "0.1.0"),
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    cargo_process("new foo --vcs none --edition 2021 -Zunstable-options --template")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .arg(template.url().to_string())
+        .with_stderr_data(str![[r#"
+[CREATING] `foo` package from template `[ROOTURL]/template`
+[UPDATING] git repository `[ROOTURL]/template`
+[NOTE] see more `Cargo.toml` keys and their definitions at https://doc.rust-lang.org/cargo/reference/manifest.html
+
+"#]])
+        .run();
+
+    let foo = paths::root().join("foo");
+    assert!(foo.join("src/main.rs").is_file());
+    assert!(!foo.join(".git").exists());
+    let manifest = fs::read_to_string(foo.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains(r#"name = "foo""#));
+    // `--edition` replaces the edition of the template.
+    assert!(manifest.contains(r#"edition = "2021""#));
+}
+
+#[cargo_test]
+fn template_in_workspace() {
+    write_template(&paths::root().join("template"));
+    fs::write(
+        paths::root().join("Cargo.toml"),
+        r#"
+[workspace]
+resolver = "2"
+
+[workspace.package]
+version = "1.0.0"
+
+[workspace.lints.rust]
+unused = "deny"
+"#,
+    )
+    .unwrap();
+
+    cargo_process("new foo --template template -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[CREATING] `foo` package from template `template`
+[ADDING] `foo` as member of workspace at `[ROOT]`
+[NOTE] see more `Cargo.toml` keys and their definitions at https://doc.rust-lang.org/cargo/reference/manifest.html
+
+"#]])
+        .run();
+
+    assert_e2e().eq(
+        fs::read_to_string(paths::root().join("Cargo.toml")).unwrap(),
+        str![[r#"
+
+[workspace]
+resolver = "2"
+members = ["foo"]
+
+[workspace.package]
+version = "1.0.0"
+
+[workspace.lints.rust]
+unused = "deny"
+
+"#]],
+    );
+    // The template's own keys are kept.
+    assert!(fs::read_to_string(paths::root().join("foo/Cargo.toml"))
+        .unwrap()
+        .contains(r#"unsafe_code = "forbid""#));
+}
+
+#[cargo_test]
+fn template_init_keeps_existing_files() {
+    write_template(&paths::root().join("template"));
+    let foo = paths::root().join("foo");
+    fs::create_dir_all(foo.join("src")).unwrap();
+    fs::write(foo.join("src/lib.rs"), "// Mine.\n").unwrap();
+
+    cargo_process("init --vcs none --template ../template -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .cwd(&foo)
+        .with_stderr_data(str![[r#"
+[CREATING] package from template `../template`
+[NOTE] see more `Cargo.toml` keys and their definitions at https://doc.rust-lang.org/cargo/reference/manifest.html
+
+"#]])
+        .run();
+
+    assert_eq!(
+        fs::read_to_string(foo.join("src/lib.rs")).unwrap(),
+        "// Mine.\n"
+    );
+    assert!(foo.join("src/bin/foo-cli.rs").is_file());
+}
+
+#[cargo_test]
+fn template_invalid() {
+    fs::create_dir_all(paths::root().join("template")).unwrap();
+
+    cargo_process("new foo --template template -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[CREATING] `foo` package from template `template`
+[ERROR] template `template` does not contain a `Cargo.toml`
+
+"#]])
+        .run();
+
+    cargo_process("new foo --template missing -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[CREATING] `foo` package from template `missing`
+[ERROR] template `missing` is not a directory or a git URL
+
+"#]])
+        .run();
+
+    cargo_process("new foo --lib --template template")
+        .with_status(1)
+        .with_stderr_data(str![[r#"
+[ERROR] the argument '--lib' cannot be used with '--template <PATH|URL>'
+
+Usage: cargo new --lib <PATH>
+
+For more information, try '--help'.
+
+"#]])
+        .run();
+
+    cargo_process("new foo --registry alternative --template template")
+        .with_status(1)
+        .with_stderr_data(str![[r#"
+[ERROR] the argument '--registry <REGISTRY>' cannot be used with '--template <PATH|URL>'
+
+Usage: cargo new --registry <REGISTRY> <PATH>
+
+For more information, try '--help'.
+
+"#]])
+        .run();
+
+    cargo_process("new foo --template template")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `--template` flag is unstable, pass `-Z unstable-options` to enable it
+
+"#]])
+        .run();
+}
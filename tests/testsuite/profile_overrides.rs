@@ -609,3 +609,244 @@ fn build_override_shared() {
 
     p.cargo("run").run();
 }
+
+#[cargo_test]
+fn profile_target_override_requires_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.dev.target.'cfg(all())']
+                opt-level = 1
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  feature `profile-target-overrides` is required
+
+  The package requires the Cargo feature called `profile-target-overrides`, but that feature is not stabilized in this version of Cargo (1.[..]).
+  Consider trying a newer version of Cargo (this may require the nightly release).
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#profile-target-overrides for more information about the status of this feature.
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn profile_target_override() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    cargo-features = ["profile-target-overrides"]
+
+                    [package]
+                    name = "foo"
+                    version = "0.0.1"
+                    edition = "2015"
+
+                    [dependencies]
+                    bar = {{path = "bar"}}
+
+                    [build-dependencies]
+                    baz = {{path = "baz"}}
+
+                    [profile.dev.target.'cfg(all())']
+                    opt-level = 1
+                    panic = "abort"
+
+                    [profile.dev.target.'cfg(any())']
+                    opt-level = 3
+
+                    [profile.dev.target.{}]
+                    opt-level = 2
+
+                    [profile.dev.package.bar]
+                    opt-level = "s"
+                "#,
+                cargo_test_support::rustc_host()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file("bar/Cargo.toml", &basic_lib_manifest("bar"))
+        .file("bar/src/lib.rs", "")
+        .file("baz/Cargo.toml", &basic_lib_manifest("baz"))
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    // The target triple wins over `cfg()`, and package overrides over both.
+    // Build scripts and their dependencies are not built for the target.
+    p.cargo("check -v")
+        .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+        .with_stderr_data(
+            str![[r#"
+[LOCKING] 2 packages to latest compatible versions
+[COMPILING] baz v0.5.0 ([ROOT]/foo/baz)
+[RUNNING] `rustc --crate-name baz [..]`
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name build_script_build [..]`
+[RUNNING] `[ROOT]/foo/target/debug/build/foo-[HASH]/build-script-build`
+[CHECKING] bar v0.5.0 ([ROOT]/foo/bar)
+[RUNNING] `rustc --crate-name bar [..] -C opt-level=s -C panic=abort [..]`
+[RUNNING] `rustc --crate-name foo [..] -C opt-level=2 -C panic=abort [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn profile_target_override_config() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [profile.release.target.'cfg(all())']
+                opt-level = 1
+            "#,
+        )
+        .build();
+
+    p.cargo("check --release -v")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] config profile `release` is not valid (defined in `[ROOT]/foo/.cargo/config.toml`)
+
+Caused by:
+  feature `profile-target-overrides` is required
+
+  The package requires the Cargo feature called `profile-target-overrides`, but that feature is not stabilized in this version of Cargo (1.[..]).
+  Consider trying a newer version of Cargo (this may require the nightly release).
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#profile-target-overrides for more information about the status of this feature.
+
+"#]])
+        .run();
+
+    p.cargo("check --release -v -Zprofile-target-overrides")
+        .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+        .with_stderr_data(str![[r#"
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name foo [..] -C opt-level=1 [..]`
+[FINISHED] `release` profile [optimized] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn profile_target_override_bad_settings() {
+    let check = |key: &str, setting: &str, expected| {
+        let p = project()
+            .file(
+                "Cargo.toml",
+                &format!(
+                    r#"
+                        cargo-features = ["profile-target-overrides"]
+
+                        [package]
+                        name = "foo"
+                        version = "0.0.1"
+                        edition = "2015"
+
+                        [profile.dev.target.{key}]
+                        {setting}
+                    "#,
+                ),
+            )
+            .file("src/lib.rs", "")
+            .build();
+        p.cargo("check")
+            .masquerade_as_nightly_cargo(&["profile-target-overrides"])
+            .with_status(101)
+            .with_stderr_data(expected)
+            .run();
+    };
+
+    check(
+        "'cfg(all())'.package.bar",
+        "opt-level = 3",
+        str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  package-specific profiles cannot be nested
+
+"#]],
+    );
+    check(
+        "'cfg(all())'.build-override",
+        "opt-level = 3",
+        str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  build-override profiles cannot be nested
+
+"#]],
+    );
+    check(
+        "'cfg(all())'.target.foo",
+        "opt-level = 3",
+        str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  target-specific profiles cannot be nested
+
+"#]],
+    );
+    check(
+        "'cfg(all())'",
+        "inherits = \"release\"",
+        str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  `inherits` may not be specified in a `target` profile
+
+"#]],
+    );
+    check(
+        "'cfg(all())'",
+        "panic = \"bad\"",
+        str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  `panic` setting of `bad` is not a valid setting, must be `unwind` or `abort`
+
+"#]],
+    );
+    check(
+        "'cfg(all'",
+        "opt-level = 3",
+        str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  invalid target `cfg(all` in profile `dev`
+
+Caused by:
+  failed to parse `cfg(all` as a cfg expression: invalid target specifier: unexpected `(` character, cfg expressions must start with `cfg(`
+
+"#]],
+    );
+}
@@ -4,7 +4,7 @@ use std::env;
 
 use cargo_test_support::prelude::*;
 use cargo_test_support::registry::Package;
-use cargo_test_support::{project, rustc_host, str};
+use cargo_test_support::{basic_manifest, project, rustc_host, str};
 
 #[cargo_test]
 fn profile_overrides() {
@@ -876,3 +876,273 @@ fn debug_options_valid() {
         .with_stderr_does_not_contain("[..]-C debuginfo[..]")
         .run();
 }
+
+#[cargo_test]
+fn profile_env_works() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-env"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.dev.env]
+                FOO = "dev"
+                CFLAGS = "-fno-omit-frame-pointer"
+            "#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    assert_eq!(std::env::var("CFLAGS").unwrap(), "-fno-omit-frame-pointer");
+                    assert_eq!(std::env::var("FOO").unwrap(), "dev");
+                    assert!(std::env::var("OUT_DIR").unwrap().contains("foo-"));
+                }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            r#"fn main() { println!("{}", env!("FOO")); }"#,
+        )
+        .build();
+
+    p.cargo("run")
+        .masquerade_as_nightly_cargo(&["profile-env"])
+        .with_stdout_data(str![[r#"
+dev
+
+"#]])
+        .run();
+
+    // Changing the environment rebuilds, and runs the build script again.
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["profile-env"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+
+            [profile.dev.env]
+            FOO = "dev"
+            CFLAGS = "-O1"
+        "#,
+    );
+    p.cargo("run")
+        .masquerade_as_nightly_cargo(&["profile-env"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[ERROR] failed to run custom build command for `foo v0.0.1 ([ROOT]/foo)`
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn profile_env_with_config() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file(
+            "src/main.rs",
+            r#"fn main() { println!("{} {}", env!("FOO"), env!("BAR")); }"#,
+        )
+        .file(
+            ".cargo/config.toml",
+            r#"
+                [env]
+                FOO = "config"
+                BAR = "config"
+
+                [profile.release.env]
+                FOO = "release"
+            "#,
+        )
+        .build();
+
+    // Profile variables take precedence over `[env]`.
+    p.cargo("run --release -Zprofile-env")
+        .masquerade_as_nightly_cargo(&["profile-env"])
+        .with_stdout_data(str![[r#"
+release config
+
+"#]])
+        .run();
+    p.cargo("run -Zprofile-env")
+        .masquerade_as_nightly_cargo(&["profile-env"])
+        .with_stdout_data(str![[r#"
+config config
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn profile_env_requires_cargo_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.dev.env]
+                FOO = "foo"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-env"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  feature `profile-env` is required
+
+  The package requires the Cargo feature called `profile-env`, but that feature is not stabilized in this version of Cargo (1.[..]).
+  Consider adding `cargo-features = ["profile-env"]` to the top of Cargo.toml (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#profile-env-option for more information about the status of this feature.
+
+"#]])
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            cargo-features = ["profile-env"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            edition = "2015"
+
+            [profile.dev.env]
+            CARGO_HOME = "foo"
+        "#,
+    );
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-env"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  setting the `CARGO_HOME` environment variable is not supported in `profile.dev.env`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn profile_linker_works() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-linker"]
+
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.dev]
+                linker = "nonexistent-linker"
+                link-args = ["-fno-omit-frame-pointer"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    // Link arguments are only passed to crates that are linked.
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo(&["profile-linker"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[RUNNING] `rustc --crate-name foo [..]--crate-type lib [..] -C debuginfo=2 --check-cfg [..] -C linker=nonexistent-linker [..]`
+[RUNNING] `rustc --crate-name foo [..]--crate-type bin [..] -C debuginfo=2 -C link-arg=-fno-omit-frame-pointer --check-cfg [..] -C linker=nonexistent-linker [..]`
+[ERROR] linker `nonexistent-linker` not found
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn profile_linker_relative_to_workspace_root() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                cargo-features = ["profile-linker"]
+
+                [workspace]
+                members = ["bar"]
+
+                [profile.dev]
+                linker = "tools/nonexistent-linker"
+            "#,
+        )
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -v")
+        .masquerade_as_nightly_cargo(&["profile-linker"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] bar v0.0.1 ([ROOT]/foo/bar)
+[RUNNING] `rustc --crate-name bar [..] -C linker=[ROOT]/foo/tools/nonexistent-linker [..]`
+[ERROR] linker `[ROOT]/foo/tools/nonexistent-linker` not found
+...
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn profile_linker_requires_cargo_feature() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [profile.dev]
+                link-args = ["-fno-omit-frame-pointer"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("check")
+        .masquerade_as_nightly_cargo(&["profile-linker"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] failed to parse manifest at `[ROOT]/foo/Cargo.toml`
+
+Caused by:
+  feature `profile-linker` is required
+
+  The package requires the Cargo feature called `profile-linker`, but that feature is not stabilized in this version of Cargo (1.[..]).
+  Consider adding `cargo-features = ["profile-linker"]` to the top of Cargo.toml (above the [package] table) to tell Cargo you are opting in to use this unstable feature.
+  See https://doc.rust-lang.org/nightly/cargo/reference/unstable.html#profile-linker-and-link-args-options for more information about the status of this feature.
+
+"#]])
+        .run();
+}
@@ -0,0 +1,335 @@
+//! Tests for `-Zshared-build-cache`.
+
+use cargo_test_support::build_cache::BuildCacheServer;
+use cargo_test_support::paths;
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, str, Project};
+
+/// Creates a project at `path` named `name` that depends on the registry
+/// package `bar`.
+fn project_with_bar(path: &str, name: &str, bar_features: &str) -> Project {
+    project()
+        .at(path)
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                    [package]
+                    name = "{name}"
+                    version = "0.1.0"
+                    edition = "2015"
+
+                    [dependencies]
+                    bar = {{ version = "1.0", features = [{bar_features}] }}
+                "#
+            ),
+        )
+        .file("src/lib.rs", "pub fn f() { bar::f(); }")
+        .build()
+}
+
+fn publish_bar() {
+    Package::new("bar", "1.0.0")
+        .feature("feat", &[])
+        .file("src/lib.rs", "pub fn f() {}")
+        .publish();
+}
+
+fn build_cache_entries() -> Vec<String> {
+    let root = paths::home().join(".cargo/build-cache");
+    let Ok(dir) = root.read_dir() else {
+        return Vec::new();
+    };
+    let mut names: Vec<_> = dir
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+#[cargo_test]
+fn restores_registry_dependency_in_other_workspace() {
+    publish_bar();
+    let a = project_with_bar("a", "a", "");
+    let b = project_with_bar("b", "b", "");
+
+    a.cargo("check -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `dummy-registry`)
+[CHECKING] bar v1.0.0
+[CHECKING] a v0.1.0 ([ROOT]/a)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+    assert_eq!(build_cache_entries().len(), 1);
+
+    b.cargo("check -v -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[CHECKING] bar v1.0.0
+[RESTORED] bar v1.0.0
+[CHECKING] b v0.1.0 ([ROOT]/b)
+[RUNNING] `rustc --crate-name b [..]`
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+    assert_eq!(build_cache_entries().len(), 1);
+
+    // The restored unit is up to date like any other.
+    b.cargo("check -v -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_data(str![[r#"
+[FRESH] bar v1.0.0
+[FRESH] b v0.1.0 ([ROOT]/b)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn restored_dependency_links() {
+    publish_bar();
+    let a = project_with_bar("a", "a", "");
+    let b = project()
+        .at("b")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "b"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            "fn main() { bar::f(); println!(\"hello\"); }",
+        )
+        .build();
+
+    a.cargo("build -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .run();
+    b.cargo("run -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stdout_data(str![[r#"
+hello
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn different_features_are_not_shared() {
+    publish_bar();
+    let a = project_with_bar("a", "a", "");
+    let b = project_with_bar("b", "b", "\"feat\"");
+
+    a.cargo("check -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .run();
+    b.cargo("check -v -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_does_not_contain("[RESTORED] [..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]`")
+        .run();
+    assert_eq!(build_cache_entries().len(), 2);
+}
+
+#[cargo_test]
+fn path_dependencies_are_not_cached() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "bar/Cargo.toml",
+            &cargo_test_support::basic_lib_manifest("bar"),
+        )
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("check -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .run();
+    assert!(build_cache_entries().is_empty());
+}
+
+#[cargo_test]
+fn disabled_without_flag() {
+    publish_bar();
+    let a = project_with_bar("a", "a", "");
+
+    a.cargo("check").run();
+    assert!(build_cache_entries().is_empty());
+}
+
+#[cargo_test]
+fn gc_removes_entries() {
+    publish_bar();
+    let a = project_with_bar("a", "a", "");
+
+    a.cargo("check -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .run();
+    assert_eq!(build_cache_entries().len(), 1);
+
+    a.cargo("clean gc -v --max-build-cache-size=0 -Zgc")
+        .masquerade_as_nightly_cargo(&["gc"])
+        .with_stderr_data(str![[r#"
+[REMOVING] [ROOT]/home/.cargo/build-cache/[..]
+[REMOVED] [FILE_NUM] files, [FILE_SIZE]B total
+
+"#]])
+        .run();
+    assert!(build_cache_entries().is_empty());
+
+    // Missing entries are stored again.
+    a.cargo("clean").run();
+    a.cargo("check -v -Zshared-build-cache")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_contains("[RUNNING] `rustc --crate-name bar [..]`")
+        .run();
+    assert_eq!(build_cache_entries().len(), 1);
+}
+
+#[cargo_test]
+fn remote_cache_shares_entries() {
+    let server = BuildCacheServer::new();
+    publish_bar();
+    let a = project_with_bar("a", "a", "");
+    let b = project_with_bar("b", "b", "");
+    let config = format!("build-cache.remote='{}'", server.url());
+
+    a.cargo("check -Zshared-build-cache --config")
+        .arg(&config)
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .run();
+    assert_eq!(server.requests(), ["GET 404", "PUT 201"]);
+    assert_eq!(server.keys(), build_cache_entries());
+
+    // Another machine with an empty local cache.
+    paths::home().join(".cargo/build-cache").rm_rf();
+    b.cargo("check -v -Zshared-build-cache --config")
+        .arg(&config)
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_contains("[RESTORED] bar v1.0.0")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name bar [..]`")
+        .run();
+    assert_eq!(server.requests(), ["GET 200"]);
+    assert_eq!(server.keys(), build_cache_entries());
+}
+
+#[cargo_test]
+fn remote_cache_is_skipped_when_offline() {
+    let server = BuildCacheServer::new();
+    publish_bar();
+    let a = project_with_bar("a", "a", "");
+
+    a.cargo("fetch").run();
+    a.cargo("check --offline -Zshared-build-cache --config")
+        .arg(format!("build-cache.remote='{}'", server.url()))
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .run();
+    assert!(server.requests().is_empty());
+    assert_eq!(build_cache_entries().len(), 1);
+}
+
+#[cargo_test]
+fn unreachable_remote_cache_does_not_fail_build() {
+    let server = BuildCacheServer::new();
+    let url = server.url();
+    drop(server);
+    publish_bar();
+    let a = project_with_bar("a", "a", "");
+
+    a.cargo("check -Zshared-build-cache --config")
+        .arg(format!("build-cache.remote='{url}'"))
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v1.0.0 (registry `dummy-registry`)
+[CHECKING] bar v1.0.0
+[CHECKING] a v0.1.0 ([ROOT]/a)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[WARNING] 2 requests to the remote build cache `http://127.0.0.1:[..]/` failed, the affected units were compiled locally or not uploaded
+
+failed to download `http://127.0.0.1:[..]/v1/entries/[..]`
+
+Caused by:
+...
+
+"#]])
+        .run();
+    assert_eq!(build_cache_entries().len(), 1);
+}
+
+#[cargo_test]
+fn broken_http_config_does_not_fail_build() {
+    let server = BuildCacheServer::new();
+    publish_bar();
+    let a = project_with_bar("a", "a", "");
+
+    // The registry would fail with the same config.
+    a.cargo("fetch").run();
+    a.cargo("check -Zshared-build-cache --config")
+        .arg(format!("build-cache.remote='{}'", server.url()))
+        .arg("--config")
+        .arg("http.ssl-version='tls9'")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_stderr_data(str![[r#"
+[WARNING] the remote build cache `http://127.0.0.1:[..]/` is not used
+
+Invalid ssl version `tls9`,choose from 'default', 'tlsv1', 'tlsv1.0', 'tlsv1.1', 'tlsv1.2', 'tlsv1.3'.
+[CHECKING] bar v1.0.0
+[CHECKING] a v0.1.0 ([ROOT]/a)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+    assert!(server.requests().is_empty());
+    assert_eq!(build_cache_entries().len(), 1);
+}
+
+#[cargo_test]
+fn invalid_remote_url() {
+    publish_bar();
+    let a = project_with_bar("a", "a", "");
+
+    a.cargo("check -Zshared-build-cache --config")
+        .arg("build-cache.remote='ftp://example.com'")
+        .masquerade_as_nightly_cargo(&["shared-build-cache"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+...
+[ERROR] `build-cache.remote` must be an http or https URL, got `ftp://example.com`
+(defined in --config cli option)
+
+"#]])
+        .run();
+}
@@ -1,7 +1,9 @@
 //! Tests for the `cargo test` command.
 
+use std::fmt::Write as _;
 use std::fs;
 
+use cargo_test_support::compare::assert_e2e;
 use cargo_test_support::prelude::*;
 use cargo_test_support::registry::Package;
 use cargo_test_support::{
@@ -5587,3 +5589,451 @@ fn cargo_test_set_out_dir_env_var() {
     p.cargo("test --package foo --test case -- tests::test_add --exact --nocapture")
         .run();
 }
+
+#[cargo_test]
+fn shard_splits_test_targets() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file(
+            "src/lib.rs",
+            r#"
+                /// ```
+                /// foo::f();
+                /// ```
+                pub fn f() {}
+
+                #[test]
+                fn unit() {}
+            "#,
+        )
+        .file("tests/a.rs", "#[test] fn a() { foo::f(); }")
+        .file("tests/b.rs", "#[test] fn b() { foo::f(); }")
+        .build();
+
+    p.cargo("test -Zunstable-options --shard 1/3")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH])
+[RUNNING] tests/b.rs (target/debug/deps/b-[HASH])
+
+"#]])
+        .run();
+    p.cargo("test -Zunstable-options --shard 2/3")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[DOCTEST] foo
+
+"#]])
+        .run();
+    p.cargo("test -Zunstable-options --shard 3/3")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] tests/a.rs (target/debug/deps/a-[HASH])
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn shard_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --shard 1/2")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `--shard` requires `-Zunstable-options`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn shard_invalid() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test -Zunstable-options --shard 3/2")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] invalid shard `3/2`, expected `N/M` where N is between 1 and M
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn test_jobs_runs_executables_in_parallel() {
+    // Each test waits for the other one to start, so they only pass when run
+    // at the same time.
+    let rendezvous = |me: &str, other: &str| {
+        format!(
+            r#"
+                use std::path::Path;
+                use std::time::{{Duration, Instant}};
+
+                #[test]
+                fn {me}() {{
+                    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+                    std::fs::write(dir.join("{me}"), "").unwrap();
+                    let start = Instant::now();
+                    while !dir.join("{other}").exists() {{
+                        assert!(start.elapsed() < Duration::from_secs(60), "{other} never started");
+                        std::thread::sleep(Duration::from_millis(10));
+                    }}
+                }}
+            "#
+        )
+    };
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.0.1"))
+        .file("src/lib.rs", "")
+        .file("tests/a.rs", &rendezvous("a", "b"))
+        .file("tests/b.rs", &rendezvous("b", "a"))
+        .build();
+
+    p.cargo("test -Zunstable-options --test-jobs 2 --no-fail-fast")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(
+            str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[RUNNING] tests/a.rs (target/debug/deps/a-[HASH][EXE])
+[RUNNING] tests/b.rs (target/debug/deps/b-[HASH][EXE])
+[SUMMARY] 3 test executables: 3 passed, 0 failed; finished in [ELAPSED]s
+[DOCTEST] foo
+
+"#]]
+            .unordered(),
+        )
+        .with_stdout_data(
+            str![[r#"
+...
+test a ... ok
+...
+test b ... ok
+...
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn test_jobs_runs_all_executables() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+                /// ```
+                /// foo::f();
+                /// ```
+                pub fn f() {}
+            "#,
+        )
+        .file("tests/a.rs", "#[test] fn a() { panic!(); }")
+        .file("tests/b.rs", "#[test] fn b() {}")
+        .file("tests/c.rs", "#[test] fn c() { panic!(); }")
+        .build();
+
+    p.cargo("test -Zunstable-options --test-jobs 2")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(
+            str![[r#"
+[WARNING] `--test-jobs` implies `--no-fail-fast`, all test executables are run even if some of them fail
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[RUNNING] tests/a.rs (target/debug/deps/a-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--test a`
+[RUNNING] tests/b.rs (target/debug/deps/b-[HASH][EXE])
+[RUNNING] tests/c.rs (target/debug/deps/c-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--test c`
+[SUMMARY] 4 test executables: 2 passed, 2 failed; finished in [ELAPSED]s
+[DOCTEST] foo
+[ERROR] 2 targets failed:
+    `--test a`
+    `--test c`
+...
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn test_jobs_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --test-jobs 2")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `--test-jobs` requires `-Zunstable-options`
+
+"#]])
+        .run();
+}
+
+/// Summarizes the test events in the JSON messages of `stdout`.
+fn test_events(stdout: &[u8]) -> Vec<String> {
+    std::str::from_utf8(stdout)
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|msg| msg["reason"].as_str().unwrap().starts_with("test-"))
+        .map(|msg| {
+            let mut event = format!(
+                "{} {} {}",
+                msg["reason"].as_str().unwrap(),
+                msg["kind"].as_str().unwrap(),
+                msg["target"]["name"].as_str().unwrap(),
+            );
+            match msg["reason"].as_str().unwrap() {
+                "test-case-finished" => {
+                    write!(event, " `{}` {}", msg["name"], msg["outcome"]).unwrap()
+                }
+                "test-binary-finished" => {
+                    assert!(msg["duration"].as_f64().unwrap() >= 0.0);
+                    write!(event, " {} {}", msg["success"], msg["exit_code"]).unwrap()
+                }
+                _ => {}
+            }
+            event
+        })
+        .collect()
+}
+
+#[cargo_test]
+fn json_test_events() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+                /// ```
+                /// foo::f();
+                /// ```
+                pub fn f() {}
+
+                #[test]
+                fn works() {}
+
+                #[test]
+                #[ignore]
+                fn ignored() {}
+            "#,
+        )
+        .file("tests/t.rs", "#[test] fn fails() { panic!(); }")
+        .file(
+            "tests/no_harness.rs",
+            "fn main() { println!(\"test fake ... ok\"); }",
+        )
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [[test]]
+                name = "t"
+
+                [[test]]
+                name = "no_harness"
+                harness = false
+            "#,
+        )
+        .build();
+
+    let output = p
+        .cargo("test --no-fail-fast -Zunstable-options --message-format=json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] test failed, to rerun pass `--test t`")
+        .run();
+    assert_eq!(
+        test_events(&output.stdout),
+        [
+            "test-binary-started test foo",
+            "test-case-finished test foo `\"ignored\"` \"ignored\"",
+            "test-case-finished test foo `\"works\"` \"ok\"",
+            "test-binary-finished test foo true 0",
+            "test-binary-started test no_harness",
+            "test-binary-finished test no_harness true 0",
+            "test-binary-started test t",
+            "test-case-finished test t `\"fails\"` \"failed\"",
+            "test-binary-finished test t false 101",
+            "test-binary-started doctest foo",
+            "test-case-finished doctest foo `\"src/lib.rs - f (line 2)\"` \"ok\"",
+            "test-binary-finished doctest foo true 0",
+        ]
+    );
+}
+
+#[cargo_test]
+fn json_test_events_with_test_jobs() {
+    let p = project()
+        .file("src/lib.rs", "#[test] fn works() {}")
+        .file("tests/t.rs", "#[test] fn fails() { panic!(); }")
+        .build();
+
+    let output = p
+        .cargo("test --lib --test t -Zunstable-options --test-jobs 1 --message-format=json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] test failed, to rerun pass `--test t`")
+        .run();
+    assert_eq!(
+        test_events(&output.stdout),
+        [
+            "test-binary-started test foo",
+            "test-case-finished test foo `\"works\"` \"ok\"",
+            "test-binary-finished test foo true 0",
+            "test-binary-started test t",
+            "test-case-finished test t `\"fails\"` \"failed\"",
+            "test-binary-finished test t false 101",
+        ]
+    );
+}
+
+#[cargo_test]
+fn junit_report() {
+    let p = project()
+        .file(
+            "src/lib.rs",
+            r#"
+                /// ```
+                /// foo::f();
+                /// ```
+                pub fn f() {}
+
+                #[test]
+                fn works() {}
+
+                #[test]
+                #[ignore]
+                fn ignored() {}
+            "#,
+        )
+        .file(
+            "tests/t.rs",
+            r#"
+                #[test]
+                fn fails() {
+                    println!("a < b & c");
+                    panic!("oh no");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("test --no-fail-fast -Zunstable-options --junit target/junit.xml")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_contains("[ERROR] test failed, to rerun pass `--test t`")
+        .run();
+
+    let report = p.read_file("target/junit.xml");
+    assert_e2e().eq(
+        report,
+        str![[r#"
+<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="foo lib" tests="2" failures="0" errors="0" skipped="1" time="[..]">
+    <testcase classname="foo lib" name="ignored">
+      <skipped/>
+    </testcase>
+    <testcase classname="foo lib" name="works"/>
+  </testsuite>
+  <testsuite name="foo test &quot;t&quot;" tests="1" failures="1" errors="0" skipped="0" time="[..]">
+    <testcase classname="foo test &quot;t&quot;" name="fails">
+      <failure message="test failed">a &lt; b &amp; c
+
+thread 'fails'[..] panicked at tests/t.rs:5:21:
+oh no
+[NOTE] run with `RUST_BACKTRACE=1` environment variable to display a backtrace</failure>
+    </testcase>
+  </testsuite>
+  <testsuite name="foo doctests" tests="1" failures="0" errors="0" skipped="0" time="[..]">
+    <testcase classname="foo doctests" name="src/lib.rs - f (line 2)"/>
+  </testsuite>
+</testsuites>
+
+"#]],
+    );
+}
+
+#[cargo_test]
+fn junit_report_write_failure_keeps_test_error() {
+    let p = project()
+        .file("src/lib.rs", "#[test] fn fails() { panic!(); }")
+        .build();
+
+    p.cargo("test -Zunstable-options --junit missing/junit.xml")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `test` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[RUNNING] unittests src/lib.rs (target/debug/deps/foo-[HASH][EXE])
+[ERROR] test failed, to rerun pass `--lib`
+[WARNING] the JUnit report could not be written
+
+failed to write JUnit report to `[ROOT]/foo/missing/junit.xml`
+
+Caused by:
+  failed to write `[ROOT]/foo/missing/junit.xml`
+
+Caused by:
+  [NOT_FOUND]
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn junit_rejects_unparseable_libtest_output() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test -Zunstable-options --junit junit.xml -- --format terse")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `--junit` can't be used with `--format terse`, the outcome of each test is read from the default output of libtest
+
+"#]])
+        .run();
+
+    p.cargo("test -q -Zunstable-options --message-format=json")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `--message-format=json` can't be used with `--quiet`, the outcome of each test is read from the default output of libtest
+
+"#]])
+        .run();
+
+    p.cargo("test -Zunstable-options --junit junit.xml -- --format=pretty")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .run();
+}
+
+#[cargo_test]
+fn junit_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("test --junit junit.xml")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `--junit` requires `-Zunstable-options`
+
+"#]])
+        .run();
+}
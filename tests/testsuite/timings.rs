@@ -54,3 +54,179 @@ fn timings_works() {
 
     p.cargo("doc --timings").run();
 }
+
+#[cargo_test]
+fn timings_json_report() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build --timings=json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["timings=json"])
+        .with_stderr_data(str![[r#"
+[COMPILING] foo v0.0.1 ([ROOT]/foo)
+      Timing report saved to [ROOT]/foo/target/cargo-timings/cargo-timing-[..].json
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+
+"#]])
+        .run();
+
+    let report = p.read_file("target/cargo-timings/cargo-timing.json");
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report["version"], 1);
+    assert_eq!(report["profile"], "dev");
+    assert_eq!(report["dirty_units"], 2);
+    let targets: Vec<_> = report["units"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|unit| unit["target"].as_str().unwrap())
+        .collect();
+    assert_eq!(targets, ["lib", "bin \"foo\""]);
+    assert_eq!(report["units"][0]["unlocked_units"], serde_json::json!([1]));
+    assert!(!report["concurrency"].as_array().unwrap().is_empty());
+
+    p.cargo("report timings -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["timings=json"])
+        .with_stdout_data(str![[r#"
+build started at [..] took [..]s
+profile `dev`, 2 units (0 fresh, 2 dirty), jobs=[..]
+slowest units:
+...
+"#]])
+        .run();
+}
+
+fn timing_report(units: &[(&str, &str, f64)]) -> String {
+    let units: Vec<_> = units
+        .iter()
+        .map(|(name, target, duration)| {
+            serde_json::json!({
+                "package_id": format!("registry+https://github.com/rust-lang/crates.io-index#{name}@1.0.0"),
+                "name": name,
+                "version": "1.0.0",
+                "target": target,
+                "kind": "x86_64-unknown-linux-gnu",
+                "features": [],
+                "start": 0.0,
+                "duration": duration,
+                "rmeta_time": null,
+                "unlocked_units": [],
+                "unlocked_rmeta_units": [],
+            })
+        })
+        .collect();
+    let duration: f64 = units.iter().map(|u| u["duration"].as_f64().unwrap()).sum();
+    serde_json::json!({
+        "version": 1,
+        "start": "2024-01-01T00:00:00Z",
+        "duration": duration,
+        "profile": "dev",
+        "jobs": 4,
+        "ncpu": 4,
+        "rustc": "rustc 1.80.0",
+        "host": "x86_64-unknown-linux-gnu",
+        "requested_targets": ["x86_64-unknown-linux-gnu"],
+        "fresh_units": 0,
+        "dirty_units": units.len(),
+        "error": null,
+        "units": units,
+        "concurrency": [],
+        "cpu_usage": [],
+    })
+    .to_string()
+}
+
+#[cargo_test]
+fn report_timings_summary() {
+    let p = project()
+        .file(
+            "report.json",
+            &timing_report(&[
+                ("a", "lib", 1.0),
+                ("b", "lib", 3.5),
+                ("c", "build script (run)", 0.25),
+            ]),
+        )
+        .build();
+
+    p.cargo("report timings report.json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["timings=json"])
+        .with_stdout_data(str![[r#"
+build started at 2024-01-01T00:00:00Z took 4.75s
+profile `dev`, 3 units (0 fresh, 3 dirty), jobs=4
+slowest units:
+      3.50s  b v1.0.0 lib
+      1.00s  a v1.0.0 lib
+      0.25s  c v1.0.0 build script (run)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn report_timings_compare() {
+    let p = project()
+        .file(
+            "old.json",
+            &timing_report(&[
+                ("a", "lib", 1.0),
+                ("b", "lib", 2.0),
+                ("c", "lib", 0.5),
+                ("d", "lib", 0.01),
+                ("d", "bin \"d\"", 1.0),
+            ]),
+        )
+        .file(
+            "new.json",
+            &timing_report(&[
+                ("a", "lib", 1.05),
+                ("b", "lib", 3.0),
+                ("c", "lib", 0.75),
+                ("d", "lib", 0.05),
+                ("d", "bin \"d\"", 0.5),
+                ("e", "lib", 0.3),
+            ]),
+        )
+        .build();
+
+    p.cargo("report timings --compare old.json new.json -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["timings=json"])
+        .with_stdout_data(str![[r#"
+total time: 4.51s -> 5.65s (+25.3%)
+2 units regressed by more than 10%:
+     +1.00s (+50.0%)  b v1.0.0 lib (2.00s -> 3.00s)
+     +0.25s (+50.0%)  c v1.0.0 lib (0.50s -> 0.75s)
+1 unit only in the new report:
+      0.30s  e v1.0.0 lib
+
+"#]])
+        .run();
+
+    p.cargo("report timings --compare old.json new.json --threshold 60 -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["timings=json"])
+        .with_stdout_data(str![[r#"
+total time: 4.51s -> 5.65s (+25.3%)
+no units regressed by more than 60%
+1 unit only in the new report:
+      0.30s  e v1.0.0 lib
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn report_timings_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("report timings")
+        .masquerade_as_nightly_cargo(&["timings=json"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `cargo report timings` command is unstable, pass `-Z unstable-options` to enable it
+See https://github.com/rust-lang/cargo/issues/7405 for more information about the `cargo report timings` command.
+
+"#]])
+        .run();
+}
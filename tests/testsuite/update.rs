@@ -2709,3 +2709,290 @@ fn update_breaking_pre_release_upgrade() {
 "#]])
         .run();
 }
+
+/// Publishes `name` at `version`, exporting `f` unless it's `broken`.
+fn publish_verify_dep(name: &str, version: &str, broken: bool) {
+    let lib = if broken {
+        "pub fn g() {}"
+    } else {
+        "pub fn f() {}"
+    };
+    Package::new(name, version)
+        .file("src/lib.rs", lib)
+        .publish();
+}
+
+fn verify_project() -> cargo_test_support::Project {
+    project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                bad = "1.0"
+                good = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { bad::f(); good::f(); }")
+        .build()
+}
+
+#[cargo_test]
+fn update_verify_breaking() {
+    publish_verify_dep("bad", "1.0.0", false);
+    publish_verify_dep("good", "1.0.0", false);
+    let p = verify_project();
+    p.cargo("generate-lockfile").run();
+
+    publish_verify_dep("bad", "2.0.0", true);
+    publish_verify_dep("good", "2.0.0", false);
+
+    p.cargo("update -Zunstable-options --breaking --verify check")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[UPGRADING] bad ^1.0 -> ^2.0
+[LOCKING] 1 package to latest compatible version
+[UPDATING] bad v1.0.0 -> v2.0.0
+[VERIFYING] bad v1.0.0 -> v2.0.0 with `cargo check`
+...
+error[E0425]: cannot find function `f` in crate `bad`
+...
+[REVERTING] bad v1.0.0 -> v2.0.0 (`cargo check` failed)
+[UPGRADING] good ^1.0 -> ^2.0
+[LOCKING] 1 package to latest compatible version
+[UPDATING] good v1.0.0 -> v2.0.0
+[VERIFYING] good v1.0.0 -> v2.0.0 with `cargo check`
+...
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[SUMMARY] 1 update kept, 1 reverted
+[REVERTED] bad v1.0.0 -> v2.0.0 (`cargo check` failed)
+[KEPT] good v1.0.0 -> v2.0.0
+
+"#]])
+        .run();
+
+    let manifest = p.read_file("Cargo.toml");
+    assert!(manifest.contains(r#"bad = "1.0""#));
+    assert!(manifest.contains(r#"good = "2.0""#));
+    let lockfile = p.read_lockfile();
+    assert!(lockfile.contains("name = \"bad\"\nversion = \"1.0.0\""));
+    assert!(lockfile.contains("name = \"good\"\nversion = \"2.0.0\""));
+}
+
+#[cargo_test]
+fn update_verify_compatible() {
+    publish_verify_dep("bad", "1.0.0", false);
+    publish_verify_dep("good", "1.0.0", false);
+    let p = verify_project();
+    p.cargo("generate-lockfile").run();
+
+    publish_verify_dep("bad", "1.0.1", true);
+    publish_verify_dep("good", "1.0.1", false);
+
+    p.cargo("update -Zunstable-options --verify check")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(0)
+        .with_stderr_contains("[REVERTING] bad v1.0.0 -> v1.0.1 (`cargo check` failed)")
+        .with_stderr_contains(
+            "\
+[SUMMARY] 1 update kept, 1 reverted
+[REVERTED] bad v1.0.0 -> v1.0.1 (`cargo check` failed)
+[KEPT] good v1.0.0 -> v1.0.1
+",
+        )
+        .run();
+
+    let lockfile = p.read_lockfile();
+    assert!(lockfile.contains("name = \"bad\"\nversion = \"1.0.0\""));
+    assert!(lockfile.contains("name = \"good\"\nversion = \"1.0.1\""));
+
+    // The reverted update is not remembered, so it is tried and reverted again.
+    p.cargo("update -Zunstable-options --verify check")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[UPDATING] bad v1.0.0 -> v1.0.1
+[VERIFYING] bad v1.0.0 -> v1.0.1 with `cargo check`
+...
+[REVERTING] bad v1.0.0 -> v1.0.1 (`cargo check` failed)
+[SUMMARY] 0 updates kept, 1 reverted
+[REVERTED] bad v1.0.0 -> v1.0.1 (`cargo check` failed)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn update_verify_renamed() {
+    publish_verify_dep("bar", "1.0.0", false);
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                baz = { package = "bar", version = "1.0" }
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { baz::f(); }")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    publish_verify_dep("bar", "2.0.0", false);
+    p.cargo("update -Zunstable-options --breaking --verify check baz")
+        .masquerade_as_nightly_cargo(&["update-breaking"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[UPGRADING] bar ^1.0 -> ^2.0
+[LOCKING] 1 package to latest compatible version
+[UPDATING] bar v1.0.0 -> v2.0.0
+[VERIFYING] bar v1.0.0 -> v2.0.0 with `cargo check`
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v2.0.0 (registry `dummy-registry`)
+[CHECKING] bar v2.0.0
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[SUMMARY] 1 update kept, 0 reverted
+[KEPT] bar v1.0.0 -> v2.0.0
+
+"#]])
+        .run();
+    assert!(p
+        .read_file("Cargo.toml")
+        .contains(r#"baz = { package = "bar", version = "2.0" }"#));
+
+    publish_verify_dep("bar", "2.0.1", false);
+    p.cargo("update -Zunstable-options --verify check baz")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[UPDATING] bar v2.0.0 -> v2.0.1
+[VERIFYING] bar v2.0.0 -> v2.0.1 with `cargo check`
+[DOWNLOADING] crates ...
+[DOWNLOADED] bar v2.0.1 (registry `dummy-registry`)
+[CHECKING] bar v2.0.1
+[CHECKING] foo v0.0.1 ([ROOT]/foo)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[SUMMARY] 1 update kept, 0 reverted
+[KEPT] bar v2.0.0 -> v2.0.1
+
+"#]])
+        .run();
+    assert!(p
+        .read_lockfile()
+        .contains("name = \"bar\"\nversion = \"2.0.1\""));
+}
+
+#[cargo_test]
+fn update_verify_workspace() {
+    publish_verify_dep("good", "1.0.0", false);
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["a", "b"]
+            "#,
+        )
+        .file(
+            "a/Cargo.toml",
+            r#"
+                [package]
+                name = "a"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                good = "1.0"
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file("b/Cargo.toml", &basic_manifest("b", "0.0.1"))
+        .file("b/src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    publish_verify_dep("good", "1.0.1", false);
+    p.cargo("update -Zunstable-options --verify check --workspace")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_data(
+            str![[r#"
+[UPDATING] `dummy-registry` index
+[LOCKING] 1 package to latest compatible version
+[UPDATING] good v1.0.0 -> v1.0.1
+[VERIFYING] good v1.0.0 -> v1.0.1 with `cargo check`
+[DOWNLOADING] crates ...
+[DOWNLOADED] good v1.0.1 (registry `dummy-registry`)
+[CHECKING] good v1.0.1
+[CHECKING] a v0.0.1 ([ROOT]/foo/a)
+[CHECKING] b v0.0.1 ([ROOT]/foo/b)
+[FINISHED] `dev` profile [unoptimized + debuginfo] target(s) in [ELAPSED]s
+[SUMMARY] 1 update kept, 0 reverted
+[KEPT] good v1.0.0 -> v1.0.1
+
+"#]]
+            .unordered(),
+        )
+        .run();
+}
+
+#[cargo_test]
+fn update_verify_forwards_config() {
+    publish_verify_dep("good", "1.0.0", false);
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+                edition = "2015"
+
+                [dependencies]
+                good = "1.0"
+            "#,
+        )
+        .file(
+            "src/lib.rs",
+            r#"
+                #[cfg(not(verify_cfg))]
+                compile_error!("`--config` was not forwarded");
+            "#,
+        )
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    publish_verify_dep("good", "1.0.1", false);
+
+    p.cargo("update -Zunstable-options --verify check")
+        .arg("--config")
+        .arg(r#"build.rustflags=["--cfg=verify_cfg", "--check-cfg=cfg(verify_cfg)"]"#)
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stderr_contains("[KEPT] good v1.0.0 -> v1.0.1")
+        .run();
+}
+
+#[cargo_test]
+fn update_verify_requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("update --verify check")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] `--verify` requires `-Zunstable-options`
+
+"#]])
+        .run();
+}
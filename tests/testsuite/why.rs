@@ -0,0 +1,178 @@
+//! Tests for the `cargo why` command.
+
+use cargo_test_support::prelude::*;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{basic_manifest, project, str};
+
+#[cargo_test]
+fn explains_requirements_and_lockfile() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+                baz = { path = "baz" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "baz/Cargo.toml",
+            r#"
+                [package]
+                name = "baz"
+                version = "0.1.0"
+                edition = "2015"
+
+                [build-dependencies]
+                bar = ">=1.0, <1.5"
+            "#,
+        )
+        .file("baz/src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    Package::new("bar", "1.1.0").publish();
+    Package::new("bar", "1.2.0").yanked(true).publish();
+    Package::new("bar", "1.5.0").publish();
+    Package::new("bar", "2.0.0").publish();
+
+    p.cargo("why bar -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+bar v1.0.0
+  required by:
+    `>=1.0, <1.5` from baz v0.1.0 ([ROOT]/foo/baz) (build)
+    `^1.0` from foo v0.1.0 ([ROOT]/foo)
+  locked: Cargo.lock pins v1.0.0
+  newer versions not selected:
+    v2.0.0: does not match `>=1.0, <1.5` from baz v0.1.0 ([ROOT]/foo/baz) (build)
+    v1.5.0: does not match `>=1.0, <1.5` from baz v0.1.0 ([ROOT]/foo/baz) (build)
+    v1.2.0: yanked
+    v1.1.0: Cargo.lock pins v1.0.0, run `cargo update bar` to update
+
+"#]])
+        .with_stderr_data(str![[r#"
+[UPDATING] `dummy-registry` index
+
+"#]])
+        .run();
+
+    p.cargo("why baz -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+baz v0.1.0 ([ROOT]/foo/baz)
+  required by:
+    `*` from foo v0.1.0 ([ROOT]/foo)
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn explains_rust_version() {
+    Package::new("bar", "1.0.0").rust_version("1.60").publish();
+    Package::new("bar", "1.1.0").rust_version("1.70").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+                rust-version = "1.65"
+                resolver = "3"
+
+                [dependencies]
+                bar = "1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("why bar -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+bar v1.0.0
+  required by:
+    `^1.0` from foo v0.1.0 ([ROOT]/foo)
+  not locked: there is no Cargo.lock
+  rust-version: requires Rust 1.60, compatible with Rust 1.65
+  newer versions not selected:
+    v1.1.0: requires Rust 1.70, newer than Rust 1.65
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn explains_patch() {
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                edition = "2015"
+
+                [dependencies]
+                bar = "1.0"
+
+                [patch.crates-io]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "1.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("why bar -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_stdout_data(str![[r#"
+bar v1.0.1 ([ROOT]/foo/bar)
+  required by:
+    `^1.0` from foo v0.1.0 ([ROOT]/foo)
+  patched: provided by `[patch.crates-io]`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn not_found() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("why bar -Zunstable-options")
+        .masquerade_as_nightly_cargo(&["unstable-options"])
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] package ID specification `bar` did not match any packages
+
+[HELP] a package with a similar name exists: `foo`
+
+"#]])
+        .run();
+}
+
+#[cargo_test]
+fn requires_unstable_options() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("why foo")
+        .with_status(101)
+        .with_stderr_data(str![[r#"
+[ERROR] the `cargo why` command is unstable, pass `-Z unstable-options` to enable it
+
+"#]])
+        .run();
+}